@@ -19,10 +19,261 @@ export interface Color {
   b: number
 }
 export const MAGENTA: Color
+export const enum SearchStrategy {
+  /** Compares the target against every position of the source. */
+  Exhaustive = 0,
+  /**
+   * Finds candidates on downscaled copies of both images, then verifies them at full
+   * resolution. Returns the same matches as `Exhaustive`, but is much faster on large sources.
+   */
+  Pyramid = 1
+}
+export interface SearchOptions {
+  variant?: number
+  transColor?: Color
+  strategy?: SearchStrategy
+}
+export interface SearchResult {
+  point?: Point
+  /** How closely the match resembles the target, from 0 to 1 (identical). 0 when not found. */
+  score: number
+}
 export function readImageData(path: string): Promise<ImageData>
 export function saveImageData(path: string, imageData: ImageData): Promise<void>
-export function imageSearch(source: ImageData, target: ImageData, variant?: number | undefined | null, transColor?: Color | undefined | null): Promise<Point | null>
-export function multipleImageSearch(source: ImageData, target: ImageData, variant?: number | undefined | null, transColor?: Color | undefined | null): Promise<Array<Point>>
+export function imageSearch(source: ImageData, target: ImageData, variant?: number | undefined | null, transColor?: Color | undefined | null, strategy?: SearchStrategy | undefined | null): Promise<Point | null>
+export function multipleImageSearch(source: ImageData, target: ImageData, variant?: number | undefined | null, transColor?: Color | undefined | null, strategy?: SearchStrategy | undefined | null): Promise<Array<Point>>
+/**
+ * Searches `source` for each of `targets`, returning one result per target in the same order.
+ *
+ * The targets are searched in parallel against the same source, which is only prepared once
+ * (e.g. downscaled once per level with `SearchStrategy.Pyramid`), so this is much cheaper than
+ * calling `imageSearch` for every target.
+ */
+export function imageSearchMany(source: ImageData, targets: Array<ImageData>, options?: SearchOptions | undefined | null): Promise<Array<SearchResult>>
+/** A key combination such as "Ctrl+Alt+F5". */
+export interface Accelerator {
+  mods: Array<Modifiers>
+  key: Key
+}
+/**
+ * Parses a key combination written as modifier and key names joined by `+`, e.g. "Ctrl+Alt+F5"
+ * or "Super+Shift+ArrowLeft", or with AutoHotkey's modifier symbols, e.g. "^!F5" (`^` Ctrl,
+ * `!` Alt, `+` Shift, `#` Win). Names are case-insensitive. The key typing `+`, `=` on US
+ * keyboards, is written "Plus" or "+", e.g. "Ctrl++". A modifier of one side is written e.g.
+ * "LCtrl" or "RShift", or with AutoHotkey's `<` and `>` before the symbol, e.g. "<^a".
+ */
+export function parseAccelerator(accelerator: string): Accelerator
+/** Writes a key combination the way `parseAccelerator` reads it, e.g. "Ctrl+Shift+A". */
+export function formatAccelerator(accelerator: Accelerator): string
+export const enum BarcodeFormat {
+  QrCode = 0,
+  Code128 = 1
+}
+export interface Barcode {
+  format: BarcodeFormat
+  text: string
+  /** Corners of the symbol in image coordinates, clockwise from its top-left corner. */
+  corners: Array<Point>
+}
+/**
+ * Decodes every QR code and Code 128 barcode found in `image_data`, or only inside `region`.
+ *
+ * QR codes may be rotated but not perspective-distorted, which is what screenshots produce.
+ * Code 128 barcodes are read horizontally and vertically.
+ */
+export function decodeBarcodes(imageData: ImageData, region?: Rect | undefined | null, formats?: Array<BarcodeFormat> | undefined | null): Promise<Array<Barcode>>
+export interface TypingOptions {
+  /** Milliseconds between two keystrokes (or chunks), the input delay by default. */
+  delay?: number
+  /**
+   * Milliseconds each key is held down, 0 by default. Keys held down are typed one at a
+   * time, whatever `chunkSize` says.
+   */
+  pressDuration?: number
+  /**
+   * Up to this many milliseconds are randomly added to or taken from every delay and press
+   * duration, 0 by default.
+   */
+  jitter?: number
+  /**
+   * How many characters are sent at once. Defaults to 1 when typing with a delay, press
+   * duration or jitter, and to the whole text otherwise.
+   */
+  chunkSize?: number
+}
+/**
+ * Sets how many milliseconds every keyboard and mouse action waits after its input, like
+ * AutoHotkey's `SetKeyDelay` and `SetMouseDelay`. It is also the default time between the
+ * keystrokes of `Keyboard.typing`. 0 by default.
+ */
+export function setInputDelay(delay: number): void
+export function getInputDelay(): number
+export interface Monitor {
+  /** Physical pixels covered by the monitor on the virtual desktop. */
+  bounds: Rect
+  /**
+   * The same area as seen by applications that are not DPI aware: same top-left corner,
+   * size divided by `scaleFactor`.
+   */
+  logicalBounds: Rect
+  /** Physical pixels per logical pixel, e.g. 1.5 for 144 DPI. */
+  scaleFactor: number
+  primary: boolean
+}
+/**
+ * Every position taken or returned by `Mouse` and `takeScreenshot` is in physical pixels of the
+ * virtual desktop, the box around all monitors. On Windows (0, 0) is the top-left corner of the
+ * primary monitor, so monitors left of or above it have negative coordinates. On X11 it is the
+ * top-left corner of the root window.
+ */
+export interface VirtualDesktop {
+  /** Physical pixels covered by all monitors together. */
+  bounds: Rect
+  monitors: Array<Monitor>
+}
+/**
+ * Converts a point in logical pixels (e.g. from a DPI-unaware application) to the physical
+ * pixel it covers on `desktop`.
+ */
+export function logicalToPhysical(point: Point, desktop: VirtualDesktop): Point
+/**
+ * Converts a point in physical pixels, e.g. from `imageSearch` on a screenshot, to logical
+ * pixels on `desktop`.
+ */
+export function physicalToLogical(point: Point, desktop: VirtualDesktop): Point
+/** The `code` of the errors hotkey functions reject or throw with. */
+export const enum HotkeyErrorCode {
+  /** Another application already registered or grabbed the key combination. */
+  AlreadyRegistered = 'AlreadyRegistered',
+  /** No hotkey or hotkey sequence with the given id is registered. */
+  NotRegistered = 'NotRegistered',
+  /**
+   * The key or a modifier can't be used for a hotkey, e.g. because the keyboard layout has no
+   * key for it, or the platform has no such hotkey modifier.
+   */
+  Unsupported = 'Unsupported',
+  /**
+   * The key combination begins a hotkey sequence and can't be a hotkey too, or the other way
+   * around, as the sequence would take every press.
+   */
+  Conflict = 'Conflict',
+  /** Anything else, e.g. no connection to the X server. */
+  Failed = 'Failed'
+}
+/**
+ * Limits a hotkey to windows in the foreground, like AutoHotkey's `#IfWinActive`. Every given
+ * criterion has to match.
+ */
+export interface HotkeyScope {
+  /** A part of the window title, case-sensitive. */
+  title?: string
+  /** The window class, e.g. "Notepad" on Windows, or either name of `WM_CLASS` on Linux. */
+  className?: string
+  /**
+   * The name of the process' executable, case-insensitive and with or without ".exe", e.g.
+   * "notepad.exe" or "code".
+   */
+  process?: string
+}
+/** When a hotkey callback is called. */
+export const enum HotkeyPhase {
+  /** When the key combination is pressed. */
+  Press = 0,
+  /** When the key is released again. */
+  Release = 1,
+  /** When the key has been held down for `holdTime` milliseconds. */
+  Hold = 2,
+  /**
+   * When the hotkey stopped working and was unregistered, e.g. because another application
+   * took the key combination. Every callback of the hotkey, or of a hotkey sequence using it,
+   * is called with it, whatever its `trigger`, and `error` tells why.
+   */
+  Lost = 3
+}
+export interface HotkeyOptions {
+  /**
+   * Calls the callback only while a matching window is in the foreground. Otherwise the
+   * keystroke goes through to that window, unless another callback of the hotkey matches.
+   */
+  scope?: HotkeyScope
+  /** When to call the callback, `HotkeyPhase.Press` by default. */
+  trigger?: HotkeyPhase
+  /** Milliseconds the key has to be held down for `HotkeyPhase.Hold`, 500 by default. */
+  holdTime?: number
+  /**
+   * Whether a `HotkeyPhase.Press` callback is called again whenever the held key repeats.
+   * Defaults to false.
+   */
+  repeat?: boolean
+}
+/** What a hotkey callback is called with. */
+export interface HotkeyEvent {
+  /** The id of the hotkey, or of the hotkey sequence. */
+  id: number
+  phase: HotkeyPhase
+  /** Milliseconds since the Unix epoch. */
+  timestamp: number
+  /** How often the key repeated since it was pressed, 0 for the press itself. */
+  repeatCount: number
+  /** Why the hotkey stopped working, for `HotkeyPhase.Lost`. */
+  error?: string
+}
+export interface RegisteredHotkey {
+  id: number
+  mods: Array<Modifiers>
+  key: Key
+}
+export interface HotstringOptions {
+  /**
+   * The characters that complete the abbreviation, and one of which has to precede it unless
+   * it starts the input. Defaults to ``-()[]{}':;"/\,.?!``, space, tab and Enter.
+   */
+  endChars?: string
+  /**
+   * Whether the abbreviation has to be typed in the same case. Otherwise the replacement
+   * follows the case it was typed in: all capitals, a capital first letter, or as given.
+   * Defaults to false.
+   */
+  caseSensitive?: boolean
+  /**
+   * Whether to replace the abbreviation as soon as its last character is typed, without
+   * waiting for an end character. Defaults to false.
+   */
+  immediate?: boolean
+}
+export const enum MousePath {
+  /** A straight line at constant speed. */
+  Linear = 0,
+  /** A straight line that speeds up, then slows down before the target. */
+  Eased = 1,
+  /** A randomly bent cubic Bézier curve, eased like `Eased`. */
+  Bezier = 2,
+  /**
+   * A wandering path pulled towards the target (the WindMouse algorithm), overshooting it
+   * slightly on long moves before settling.
+   */
+  WindMouse = 3
+}
+export interface MoveOptions {
+  /** Shape of the path, `Eased` by default. */
+  path?: MousePath
+  /** Total time of the movement in milliseconds, 300 by default. */
+  duration?: number
+  /** Intermediate moves per second, 120 by default. */
+  rate?: number
+  /** Makes the random paths reproducible. */
+  seed?: number
+}
+/**
+ * Generates the points a smooth move from `from` to `to` goes through, without `from` and
+ * ending exactly on `to`. A move of `duration` ms at `rate` moves per second has
+ * `duration * rate / 1000` points (at least one, at most 10 000).
+ */
+export function generateMousePath(from: Point, to: Point, options?: MoveOptions | undefined | null): Array<Point>
+/**
+ * Modifier keys and lock states. The left and right ones only serve hotkeys, which then only
+ * fire while the modifier is held on that side.
+ */
 export const enum Modifiers {
   Alt = 1,
   AltGraph = 2,
@@ -37,127 +288,270 @@ export const enum Modifiers {
   Symbol = 1024,
   SymbolLock = 2048,
   Hyper = 4096,
-  Super = 8192
+  Super = 8192,
+  LeftShift = 16384,
+  RightShift = 32768,
+  LeftControl = 65536,
+  RightControl = 131072,
+  LeftAlt = 262144,
+  RightAlt = 524288,
+  LeftSuper = 1048576,
+  RightSuper = 2097152
 }
+/**
+ * A key, independent of the platform and the keyboard layout. The printable keys are
+ * named after the US layout. The values are not Windows virtual-key codes, so pass
+ * `Key.ArrowLeft` rather than a number like 37.
+ */
 export const enum Key {
   None = 0,
-  Back = 8,
-  Tab = 9,
-  LineFeed = 10,
-  Clear = 12,
-  Enter = 13,
-  Shift = 16,
-  Control = 17,
-  Alt = 18,
-  Pause = 19,
-  CapsLock = 20,
-  Esc = 27,
-  Space = 32,
-  PageUp = 33,
-  PageDown = 34,
-  End = 35,
-  Home = 36,
-  ArrowLeft = 37,
-  ArrowUp = 38,
-  ArrowRight = 39,
-  ArrowDown = 40,
-  Insert = 45,
-  Delete = 46,
-  D0 = 48,
-  D1 = 49,
-  D2 = 50,
-  D3 = 51,
-  D4 = 52,
-  D5 = 53,
-  D6 = 54,
-  D7 = 55,
-  D8 = 56,
-  D9 = 57,
-  A = 65,
-  B = 66,
-  C = 67,
-  D = 68,
-  E = 69,
-  F = 70,
-  G = 71,
-  H = 72,
-  I = 73,
-  J = 74,
-  K = 75,
-  L = 76,
-  M = 77,
-  N = 78,
-  O = 79,
-  P = 80,
-  Q = 81,
-  R = 82,
-  S = 83,
-  T = 84,
-  U = 85,
-  V = 86,
-  W = 87,
-  X = 88,
-  Y = 89,
-  Z = 90,
-  LeftWin = 91,
-  RightWin = 92,
-  Apps = 93,
-  Sleep = 95,
-  NumPad0 = 96,
-  NumPad1 = 97,
-  NumPad2 = 98,
-  NumPad3 = 99,
-  NumPad4 = 100,
-  NumPad5 = 101,
-  NumPad6 = 102,
-  NumPad7 = 103,
-  NumPad8 = 104,
-  NumPad9 = 105,
-  Multiply = 106,
-  Add = 107,
-  Separator = 108,
-  Subtract = 109,
-  Decimal = 110,
-  Divide = 111,
-  F1 = 112,
-  F2 = 113,
-  F3 = 114,
-  F4 = 115,
-  F5 = 116,
-  F6 = 117,
-  F7 = 118,
-  F8 = 119,
-  F9 = 120,
-  F10 = 121,
-  F11 = 122,
-  F12 = 123,
-  F13 = 124,
-  F14 = 125,
-  F15 = 126,
-  F16 = 127,
-  F17 = 128,
-  F18 = 129,
-  F19 = 130,
-  F20 = 131,
-  F21 = 132,
-  F22 = 133,
-  F23 = 134,
-  F24 = 135,
-  NumLock = 144,
-  ScrollLock = 145,
-  LeftShift = 160,
-  RightShift = 161,
-  LeftControl = 162,
-  RightControl = 163,
-  LeftAlt = 164,
-  RightAlt = 165
+  Back = 1,
+  Tab = 2,
+  LineFeed = 3,
+  Clear = 4,
+  Enter = 5,
+  Shift = 6,
+  Control = 7,
+  Alt = 8,
+  Pause = 9,
+  CapsLock = 10,
+  Esc = 11,
+  Space = 12,
+  PageUp = 13,
+  PageDown = 14,
+  End = 15,
+  Home = 16,
+  ArrowLeft = 17,
+  ArrowUp = 18,
+  ArrowRight = 19,
+  ArrowDown = 20,
+  PrintScreen = 21,
+  Insert = 22,
+  Delete = 23,
+  D0 = 24,
+  D1 = 25,
+  D2 = 26,
+  D3 = 27,
+  D4 = 28,
+  D5 = 29,
+  D6 = 30,
+  D7 = 31,
+  D8 = 32,
+  D9 = 33,
+  A = 34,
+  B = 35,
+  C = 36,
+  D = 37,
+  E = 38,
+  F = 39,
+  G = 40,
+  H = 41,
+  I = 42,
+  J = 43,
+  K = 44,
+  L = 45,
+  M = 46,
+  N = 47,
+  O = 48,
+  P = 49,
+  Q = 50,
+  R = 51,
+  S = 52,
+  T = 53,
+  U = 54,
+  V = 55,
+  W = 56,
+  X = 57,
+  Y = 58,
+  Z = 59,
+  LeftWin = 60,
+  RightWin = 61,
+  Apps = 62,
+  Sleep = 63,
+  NumPad0 = 64,
+  NumPad1 = 65,
+  NumPad2 = 66,
+  NumPad3 = 67,
+  NumPad4 = 68,
+  NumPad5 = 69,
+  NumPad6 = 70,
+  NumPad7 = 71,
+  NumPad8 = 72,
+  NumPad9 = 73,
+  Multiply = 74,
+  Add = 75,
+  Separator = 76,
+  Subtract = 77,
+  Decimal = 78,
+  Divide = 79,
+  F1 = 80,
+  F2 = 81,
+  F3 = 82,
+  F4 = 83,
+  F5 = 84,
+  F6 = 85,
+  F7 = 86,
+  F8 = 87,
+  F9 = 88,
+  F10 = 89,
+  F11 = 90,
+  F12 = 91,
+  F13 = 92,
+  F14 = 93,
+  F15 = 94,
+  F16 = 95,
+  F17 = 96,
+  F18 = 97,
+  F19 = 98,
+  F20 = 99,
+  F21 = 100,
+  F22 = 101,
+  F23 = 102,
+  F24 = 103,
+  NumLock = 104,
+  ScrollLock = 105,
+  LeftShift = 106,
+  RightShift = 107,
+  LeftControl = 108,
+  RightControl = 109,
+  LeftAlt = 110,
+  RightAlt = 111,
+  BrowserBack = 112,
+  BrowserForward = 113,
+  BrowserRefresh = 114,
+  BrowserStop = 115,
+  BrowserSearch = 116,
+  BrowserFavorites = 117,
+  BrowserHome = 118,
+  VolumeMute = 119,
+  VolumeDown = 120,
+  VolumeUp = 121,
+  MediaNext = 122,
+  MediaPrev = 123,
+  MediaStop = 124,
+  MediaPlayPause = 125,
+  LaunchMail = 126,
+  LaunchMedia = 127,
+  LaunchApp1 = 128,
+  LaunchApp2 = 129,
+  Semicolon = 130,
+  Equal = 131,
+  Comma = 132,
+  Minus = 133,
+  Period = 134,
+  Slash = 135,
+  Backquote = 136,
+  BracketLeft = 137,
+  Backslash = 138,
+  BracketRight = 139,
+  Quote = 140,
+  IntlBackslash = 141
+}
+export const enum KeyboardEventKind {
+  Down = 0,
+  Up = 1
+}
+export interface KeyboardEvent {
+  kind: KeyboardEventKind
+  /** The key, when `Key` has a variant for it. */
+  key?: Key
+  /** The layout-dependent key code: the virtual-key code on Windows, the keysym on X11. */
+  code: number
+  /** The layout-independent key code: the scan code on Windows, the keycode on X11. */
+  scanCode: number
+  /** The modifiers held when the event happened, not counting the key itself. */
+  modifiers: Array<Modifiers>
+  /** Milliseconds since the Unix epoch. */
+  timestamp: number
 }
 export const enum MouseButton {
   Left = 0,
   Right = 1,
-  Middle = 2
+  Middle = 2,
+  /** The "back" side button. */
+  X1 = 3,
+  /** The "forward" side button. */
+  X2 = 4
+}
+export const enum ScrollAxis {
+  Vertical = 0,
+  Horizontal = 1
+}
+export interface DragOptions {
+  /** The button held during the drag, `Left` by default. */
+  button?: MouseButton
+  /** Milliseconds to wait after pressing and before releasing the button, 50 by default. */
+  hold?: number
+  /** How to move between the two points, as in `moveSmooth`. */
+  movement?: MoveOptions
+}
+export const enum CursorShape {
+  /** No cursor is shown. */
+  Hidden = 0,
+  Arrow = 1,
+  /** The text cursor. */
+  IBeam = 2,
+  /** The busy cursor (an hourglass or spinner). */
+  Wait = 3,
+  /**
+   * An arrow with a small busy indicator, shown while an application starts or works in the
+   * background.
+   */
+  Progress = 4,
+  /** The pointing hand over links. */
+  Hand = 5,
+  Crosshair = 6,
+  /** Any other cursor, e.g. resize arrows or custom cursors. */
+  Other = 7
+}
+export const enum MouseEventKind {
+  Move = 0,
+  Press = 1,
+  Release = 2,
+  Wheel = 3
+}
+export interface MouseEvent {
+  kind: MouseEventKind
+  /** Pointer position on the virtual desktop when the event happened. */
+  x: number
+  y: number
+  /** The button pressed or released, for `Press` and `Release`. */
+  button?: MouseButton
+  /** For `Wheel`, 120 per notch, positive when scrolling down (or right). */
+  delta?: number
+  axis?: ScrollAxis
+  /** Milliseconds since the Unix epoch. */
+  timestamp: number
+}
+export const enum SendEventKind {
+  Press = 0,
+  Release = 1,
+  /** Literal text, typed as characters regardless of the keyboard layout. */
+  Text = 2
+}
+export interface SendEvent {
+  kind: SendEventKind
+  /** The key pressed or released, for `Press` and `Release`. */
+  key?: Key
+  text?: string
 }
+/**
+ * Compiles a key sequence in AutoHotkey's Send syntax into the input events `Keyboard.send`
+ * generates for it:
+ *
+ * - Plain characters are typed as text, newlines and tabs as Enter and Tab.
+ * - `{Name}` clicks a key, `{Name down}` and `{Name up}` press and release it, and `{Name 3}`
+ *   clicks it three times, up to 1000. `{{}`, `{}}`, `{^}` etc. type the character itself.
+ * - `^` (Ctrl), `!` (Alt), `+` (Shift) and `#` (Win) hold the modifier for the next character
+ *   or key, e.g. `^a` or `+{Tab}`.
+ */
+export function parseSendSequence(sequence: string): Array<SendEvent>
+export function getVirtualDesktop(): Promise<VirtualDesktop>
 export function getScreenSize(): Promise<Point>
+/**
+ * Captures the given area of the virtual desktop, which may span several monitors. Pixel
+ * (`px`, `py`) of the result is at (`x + px`, `y + py`) for `Mouse`.
+ */
 export function takeScreenshot(x: number, y: number, width: number, height: number): Promise<ImageData>
 export interface Process {
   pid: number
@@ -187,25 +581,166 @@ export const enum ProcessAccess {
 export function openProcess(access: ProcessAccess, pid: number): Promise<OpenedProcess>
 export function getProcesses(): Promise<Array<Process>>
 export class ImageData {
-  data: Array<number>
   width: number
   height: number
   pixelWidth: number
+  /**
+   * Wraps existing pixel memory without copying it, e.g. a `Buffer` or the `Uint8ClampedArray`
+   * of a canvas `ImageData`. `pixelWidth` defaults to 4 (RGBA).
+   */
+  constructor(data: Buffer | Uint8ClampedArray | Uint8Array, width: number, height: number, pixelWidth?: number | undefined | null)
+  /** The underlying pixel memory, shared with (not copied from) the native side. */
+  get data(): Buffer | Uint8ClampedArray | Uint8Array
+  /**
+   * Outlines `rect` (`right`/`bottom` exclusive), or fills it when `fill` is true.
+   * The outline is drawn inside the rectangle and defaults to 1px.
+   */
+  drawRect(rect: Rect, color: Color, thickness?: number | undefined | null, fill?: boolean | undefined | null): void
+  drawLine(from: Point, to: Point, color: Color, thickness?: number | undefined | null): void
+  /** Marks `center` with a cross whose arms are `size` pixels long (10 by default). */
+  drawCrosshair(center: Point, color: Color, size?: number | undefined | null, thickness?: number | undefined | null): void
+  /**
+   * Writes `text` with its top-left corner at `position` using a bundled 6x10 bitmap font.
+   * Characters outside of ASCII are drawn as `?`.
+   */
+  drawText(text: string, position: Point, color: Color, background?: Color | undefined | null): void
 }
 export class Keyboard {
   static press(key: Key): Promise<void>
   static release(key: Key): Promise<void>
   static click(key: Key): Promise<void>
-  static typing(text: string): Promise<void>
-  static registerHotkey(mods: Array<Modifiers>, key: Key, callback: (...args: any[]) => any): number
+  /**
+   * Types `text` as Unicode characters, independent of the keyboard layout, with the pauses
+   * `options` asks for.
+   */
+  static typing(text: string, options?: TypingOptions | undefined | null): Promise<void>
+  /**
+   * Sends a key sequence in AutoHotkey's Send syntax, e.g. "Hello{Enter}^a{Tab 3}", see
+   * `parseSendSequence`.
+   */
+  static send(sequence: string): Promise<void>
+  /**
+   * The modifiers held down right now, plus `CapsLock`, `NumLock` and `ScrollLock` when those
+   * are toggled on.
+   */
+  static getModifiers(): Promise<Array<Modifiers>>
+  /**
+   * Calls `callback` with every key press and release, including synthetic ones and those of
+   * registered hotkeys. Returns an id for `unlisten`.
+   */
+  static listen(callback: (event: KeyboardEvent) => void): number
+  static unlisten(id: number): void
+  /**
+   * Replaces `abbreviation` whenever it is typed as a word of its own, erasing it with
+   * Backspace and typing `replacement` instead. Returns an id for `unregisterHotstring`.
+   */
+  static registerHotstring(abbreviation: string, replacement: string, options?: HotstringOptions | undefined | null): number
+  static unregisterHotstring(id: number): void
+  /**
+   * Calls `callback` with a `HotkeyEvent` whenever the key combination is pressed, or
+   * released or held down as `options` say. The combination is given either as `mods` and
+   * `key` or as an accelerator string such as "Ctrl+Alt+F5", see `parseAccelerator`. The
+   * promise resolves with the hotkey's id, or rejects with an error whose `code` is a
+   * `HotkeyErrorCode` when the combination can't be registered. The callback can be scoped
+   * to windows with `options`, registering the combination again adds callbacks for other
+   * windows, phases or sides. A modifier of one side, like `Modifiers.LeftControl` or "LCtrl",
+   * registers the combination for either side and calls the callback only while the modifier
+   * is held on that side; otherwise the keystroke goes through as if out of scope. A key
+   * combination that begins a hotkey sequence is refused with the code `Conflict`. Should the
+   * hotkey stop working later on, the callback is called once more with `HotkeyPhase.Lost`.
+   */
+  static registerHotkey(mods: Array<Modifiers>, key: Key, callback: (event: HotkeyEvent) => void, options?: HotkeyOptions | undefined | null): Promise<number>
+  static registerHotkey(accelerator: string, callback: (event: HotkeyEvent) => void, options?: HotkeyOptions | undefined | null): Promise<number>
+  /**
+   * Unregisters the hotkey `id` with all its callbacks. Throws an error with the code
+   * `NotRegistered` for unknown ids.
+   */
   static unregisterHotkey(id: number): void
+  /**
+   * Calls `callback` whenever the key combinations `steps`, accelerator strings such as
+   * "Ctrl+K", are pressed one after another, each within `timeoutMs` of the previous one. A
+   * key combination that doesn't continue the sequence cancels it, and reaches the foreground
+   * window as usual unless it starts another sequence or is a hotkey. The promise resolves
+   * with the sequence's id, or rejects like `registerHotkey`, and with the code `Conflict` when
+   * the first step is a hotkey. Steps can't ask for a modifier of one side like "LCtrl". Like
+   * hotkeys, the callback is called with `HotkeyPhase.Lost` when a step stops working.
+   */
+  static registerHotkeySequence(steps: Array<string>, timeoutMs: number, callback: (event: HotkeyEvent) => void): Promise<number>
+  /**
+   * Unregisters the hotkey sequence `id`. Throws an error with the code `NotRegistered` for
+   * unknown ids.
+   */
+  static unregisterHotkeySequence(id: number): void
+  /**
+   * Unregisters every hotkey and hotkey sequence and stops the thread waiting for them, which
+   * lets the process exit.
+   */
+  static unregisterAllHotkeys(): Promise<void>
+  /** The hotkeys registered right now. */
+  static getHotkeys(): Array<RegisteredHotkey>
 }
 export class Mouse {
   static move(x: number, y: number): Promise<void>
+  /**
+   * Moves to (`x`, `y`) through intermediate points instead of jumping there, see
+   * `generateMousePath`.
+   */
+  static moveSmooth(x: number, y: number, options?: MoveOptions | undefined | null): Promise<void>
+  /**
+   * Moves by (`dx`, `dy`) mickeys as raw relative motion, which games that capture the mouse
+   * read instead of the cursor position. The system pointer speed and acceleration settings
+   * apply, so the cursor may travel a different number of pixels.
+   */
+  static moveRelative(dx: number, dy: number): Promise<void>
+  /** Moves by (`dx`, `dy`) in small relative steps along a path like `moveSmooth`'s. */
+  static moveRelativeSmooth(dx: number, dy: number, options?: MoveOptions | undefined | null): Promise<void>
   static press(button: MouseButton): Promise<void>
   static release(button: MouseButton): Promise<void>
   static click(button: MouseButton, x: number, y: number): Promise<void>
+  /**
+   * Turns the wheel by whole notches. Positive values scroll down (or right), like the deltas
+   * of DOM wheel events.
+   */
+  static scroll(notches: number, axis?: ScrollAxis | undefined | null): Promise<void>
+  /**
+   * Turns the wheel by `delta`, where 120 is one notch. Smaller deltas give the fine-grained
+   * scrolling of touchpads and free-spinning wheels to applications that support it.
+   */
+  static scrollDelta(delta: number, axis?: ScrollAxis | undefined | null): Promise<void>
+  /** Presses `button` at `from`, moves to `to` along a smooth path and releases it there. */
+  static drag(from: Point, to: Point, options?: DragOptions | undefined | null): Promise<void>
+  static doubleClick(button: MouseButton, x: number, y: number): Promise<void>
+  /**
+   * Clicks `count` times in a row at (`x`, `y`), `interval` ms apart. The interval defaults to
+   * a fifth of the double-click time and is kept below it, so that applications see a single
+   * double (triple, ...) click.
+   */
+  static multiClick(button: MouseButton, x: number, y: number, count: number, interval?: number | undefined | null): Promise<void>
+  /** The longest time between two clicks making a double click, in milliseconds. */
+  static getDoubleClickTime(): Promise<number>
   static getPosition(): Promise<Point>
+  /** Whether `button` is held down right now, by the user or by `press`. */
+  static isPressed(button: MouseButton): Promise<boolean>
+  /**
+   * Calls `callback` with every move, button press or release and wheel turn of the mouse,
+   * including synthetic ones. Returns an id for `unlisten`.
+   */
+  static listen(callback: (event: MouseEvent) => void): number
+  static unlisten(id: number): void
+  /**
+   * Keeps the cursor inside `rect` until `unconfine` is called, moving it there first if it
+   * is outside. Windows lifts the confinement by itself when the foreground window changes.
+   * On X11 the cursor is held back by pointer barriers instead of a pointer grab, so clicks
+   * and key presses keep going to the applications under it.
+   */
+  static confine(rect: Rect): Promise<void>
+  /** Lets the cursor leave the area given to `confine` again. */
+  static unconfine(): Promise<void>
+  /**
+   * Compares the current cursor with the standard system cursors. Applications that load
+   * their own cursor images report `Other`.
+   */
+  static getCursorShape(): Promise<CursorShape>
 }
 export class Window {
   minimize(): Promise<void>
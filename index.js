@@ -295,19 +295,40 @@ if (!nativeBinding) {
   throw new Error(`Failed to load native binding`)
 }
 
-const { ImageData, MAGENTA, readImageData, saveImageData, imageSearch, multipleImageSearch, Modifiers, Key, Keyboard, MouseButton, Mouse, getScreenSize, takeScreenshot, Window, ProcessAccess, OpenedProcess, openProcess, getProcesses } = nativeBinding
+const { ImageData, MAGENTA, SearchStrategy, readImageData, saveImageData, imageSearch, multipleImageSearch, imageSearchMany, parseAccelerator, formatAccelerator, BarcodeFormat, decodeBarcodes, setInputDelay, getInputDelay, logicalToPhysical, physicalToLogical, HotkeyErrorCode, HotkeyPhase, MousePath, generateMousePath, Modifiers, Key, Keyboard, KeyboardEventKind, MouseButton, ScrollAxis, Mouse, CursorShape, MouseEventKind, SendEventKind, parseSendSequence, getVirtualDesktop, getScreenSize, takeScreenshot, Window, ProcessAccess, OpenedProcess, openProcess, getProcesses } = nativeBinding
 
 module.exports.ImageData = ImageData
 module.exports.MAGENTA = MAGENTA
+module.exports.SearchStrategy = SearchStrategy
 module.exports.readImageData = readImageData
 module.exports.saveImageData = saveImageData
 module.exports.imageSearch = imageSearch
 module.exports.multipleImageSearch = multipleImageSearch
+module.exports.imageSearchMany = imageSearchMany
+module.exports.parseAccelerator = parseAccelerator
+module.exports.formatAccelerator = formatAccelerator
+module.exports.BarcodeFormat = BarcodeFormat
+module.exports.decodeBarcodes = decodeBarcodes
+module.exports.setInputDelay = setInputDelay
+module.exports.getInputDelay = getInputDelay
+module.exports.logicalToPhysical = logicalToPhysical
+module.exports.physicalToLogical = physicalToLogical
+module.exports.HotkeyErrorCode = HotkeyErrorCode
+module.exports.HotkeyPhase = HotkeyPhase
+module.exports.MousePath = MousePath
+module.exports.generateMousePath = generateMousePath
 module.exports.Modifiers = Modifiers
 module.exports.Key = Key
 module.exports.Keyboard = Keyboard
+module.exports.KeyboardEventKind = KeyboardEventKind
 module.exports.MouseButton = MouseButton
+module.exports.ScrollAxis = ScrollAxis
 module.exports.Mouse = Mouse
+module.exports.CursorShape = CursorShape
+module.exports.MouseEventKind = MouseEventKind
+module.exports.SendEventKind = SendEventKind
+module.exports.parseSendSequence = parseSendSequence
+module.exports.getVirtualDesktop = getVirtualDesktop
 module.exports.getScreenSize = getScreenSize
 module.exports.takeScreenshot = takeScreenshot
 module.exports.Window = Window
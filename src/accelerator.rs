@@ -0,0 +1,224 @@
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+
+use crate::key::{key_from_name, key_name, Key};
+use crate::modifiers::Modifiers;
+
+/// A key combination such as "Ctrl+Alt+F5".
+#[napi(object)]
+#[derive(Debug, Clone)]
+pub struct Accelerator {
+    pub mods: Vec<Modifiers>,
+    pub key: Key,
+}
+
+/// Names of the modifiers usable in an accelerator, in the order `formatAccelerator` writes
+/// them. The first name of each is the one it uses.
+const MODIFIER_NAMES: &[(Modifiers, &[&str])] = &[
+    (Modifiers::Control, &["Ctrl", "Control"]),
+    (Modifiers::LeftControl, &["LCtrl", "LControl", "LeftCtrl", "LeftControl"]),
+    (Modifiers::RightControl, &["RCtrl", "RControl", "RightCtrl", "RightControl"]),
+    (Modifiers::Alt, &["Alt", "Option"]),
+    (Modifiers::LeftAlt, &["LAlt", "LeftAlt"]),
+    (Modifiers::RightAlt, &["RAlt", "RightAlt"]),
+    (Modifiers::AltGraph, &["AltGr", "AltGraph"]),
+    (Modifiers::Shift, &["Shift"]),
+    (Modifiers::LeftShift, &["LShift", "LeftShift"]),
+    (Modifiers::RightShift, &["RShift", "RightShift"]),
+    (Modifiers::Super, &["Super", "Win", "Cmd", "Command"]),
+    (Modifiers::LeftSuper, &["LSuper", "LWin", "LeftSuper", "LeftWin"]),
+    (Modifiers::RightSuper, &["RSuper", "RWin", "RightSuper", "RightWin"]),
+    (Modifiers::Meta, &["Meta"]),
+    (Modifiers::Hyper, &["Hyper"]),
+    (Modifiers::Fn, &["Fn"]),
+];
+
+/// Parses a key combination written as modifier and key names joined by `+`, e.g. "Ctrl+Alt+F5"
+/// or "Super+Shift+ArrowLeft", or with AutoHotkey's modifier symbols, e.g. "^!F5" (`^` Ctrl,
+/// `!` Alt, `+` Shift, `#` Win). Names are case-insensitive. The key typing `+`, `=` on US
+/// keyboards, is written "Plus" or "+", e.g. "Ctrl++". A modifier of one side is written e.g.
+/// "LCtrl" or "RShift", or with AutoHotkey's `<` and `>` before the symbol, e.g. "<^a".
+#[napi]
+pub fn parse_accelerator(accelerator: String) -> Result<Accelerator> {
+    parse_accelerator_inner(&accelerator).map_err(|e| Error::new(Status::InvalidArg, e))
+}
+
+/// Writes a key combination the way `parseAccelerator` reads it, e.g. "Ctrl+Shift+A".
+#[napi]
+pub fn format_accelerator(accelerator: Accelerator) -> String {
+    let mut parts: Vec<&str> = MODIFIER_NAMES
+        .iter()
+        .filter(|(modifier, _)| accelerator.mods.contains(modifier))
+        .map(|(_, names)| names[0])
+        .collect();
+
+    parts.push(key_name(accelerator.key));
+    parts.join("+")
+}
+
+pub fn parse_accelerator_inner(accelerator: &str) -> std::result::Result<Accelerator, String> {
+    let text = accelerator.trim();
+    let mut mods = Vec::new();
+
+    // AutoHotkey symbols come first; the rest is a key name, possibly with more modifiers.
+    let mut rest = text;
+    while let Some(symbol) = rest.chars().next() {
+        if rest.len() == 1 {
+            break;
+        }
+
+        if let Some(modifier) = modifier_from_symbol(symbol) {
+            add_modifier(&mut mods, modifier);
+            rest = &rest[1..];
+            continue;
+        }
+
+        if symbol != '<' && symbol != '>' {
+            break;
+        }
+        match rest[1..].chars().next().and_then(|it| sided_modifier_from_symbol(symbol, it)) {
+            Some(modifier) => add_modifier(&mut mods, modifier),
+            None => return Err(format!("Accelerator \"{}\" has no modifier symbol after \"{}\"", accelerator, symbol)),
+        }
+        rest = &rest[2..];
+    }
+
+    // The "+" key adds a "+" of its own after the last separator, as in "Ctrl++".
+    let rest = match rest.strip_suffix("++") {
+        Some(mods) => format!("{}+Plus", mods),
+        None if rest.trim() == "+" => "Plus".to_string(),
+        None => rest.to_string(),
+    };
+
+    let mut names: Vec<&str> = rest.split('+').map(|it| it.trim()).collect();
+    let key = match names.pop() {
+        Some(name) if !name.is_empty() => name,
+        _ => return Err(format!("Accelerator \"{}\" has no key", accelerator)),
+    };
+
+    for name in names {
+        match modifier_from_name(name) {
+            Some(modifier) => add_modifier(&mut mods, modifier),
+            None if name.is_empty() => return Err(format!("Accelerator \"{}\" has an empty part", accelerator)),
+            None => return Err(format!("Unknown modifier \"{}\" in accelerator \"{}\"", name, accelerator)),
+        }
+    }
+
+    // `Equal` is the key with `VK_OEM_PLUS`, the one typing "+".
+    let found = if key.eq_ignore_ascii_case("Plus") { Some(Key::Equal) } else { key_from_name(key) };
+    match found {
+        Some(key) => Ok(Accelerator { mods, key }),
+        None => Err(format!("Unknown key \"{}\" in accelerator \"{}\"", key, accelerator)),
+    }
+}
+
+/// The modifier of an AutoHotkey symbol.
+fn modifier_from_symbol(symbol: char) -> Option<Modifiers> {
+    match symbol {
+        '^' => Some(Modifiers::Control),
+        '!' => Some(Modifiers::Alt),
+        '+' => Some(Modifiers::Shift),
+        '#' => Some(Modifiers::Super),
+        _ => None,
+    }
+}
+
+/// The modifier of an AutoHotkey symbol preceded by `<` for its left or `>` for its right side.
+fn sided_modifier_from_symbol(side: char, symbol: char) -> Option<Modifiers> {
+    match (side, symbol) {
+        ('<', '^') => Some(Modifiers::LeftControl),
+        ('>', '^') => Some(Modifiers::RightControl),
+        ('<', '!') => Some(Modifiers::LeftAlt),
+        ('>', '!') => Some(Modifiers::RightAlt),
+        ('<', '+') => Some(Modifiers::LeftShift),
+        ('>', '+') => Some(Modifiers::RightShift),
+        ('<', '#') => Some(Modifiers::LeftSuper),
+        ('>', '#') => Some(Modifiers::RightSuper),
+        _ => None,
+    }
+}
+
+fn add_modifier(mods: &mut Vec<Modifiers>, modifier: Modifiers) {
+    if !mods.contains(&modifier) {
+        mods.push(modifier);
+    }
+}
+
+fn modifier_from_name(name: &str) -> Option<Modifiers> {
+    MODIFIER_NAMES
+        .iter()
+        .find(|(_, names)| names.iter().any(|it| it.eq_ignore_ascii_case(name)))
+        .map(|(modifier, _)| *modifier)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(accelerator: &str) -> (Vec<Modifiers>, Key) {
+        let parsed = parse_accelerator_inner(accelerator).unwrap();
+        (parsed.mods, parsed.key)
+    }
+
+    fn error(accelerator: &str) -> String {
+        parse_accelerator_inner(accelerator).unwrap_err()
+    }
+
+    #[test]
+    fn parses_names() {
+        assert_eq!(parse("Ctrl+Alt+F5"), (vec![Modifiers::Control, Modifiers::Alt], Key::F5));
+        assert_eq!(parse(" super + SHIFT + arrowleft "), (vec![Modifiers::Super, Modifiers::Shift], Key::ArrowLeft));
+        assert_eq!(parse("Control+Ctrl+;"), (vec![Modifiers::Control], Key::Semicolon));
+        assert_eq!(parse("Escape"), (vec![], Key::Esc));
+    }
+
+    #[test]
+    fn parses_symbols() {
+        assert_eq!(parse("^!F5"), (vec![Modifiers::Control, Modifiers::Alt], Key::F5));
+        assert_eq!(parse("#+a"), (vec![Modifiers::Super, Modifiers::Shift], Key::A));
+        assert_eq!(parse("^Alt+Tab"), (vec![Modifiers::Control, Modifiers::Alt], Key::Tab));
+        assert_eq!(parse("`"), (vec![], Key::Backquote));
+    }
+
+    #[test]
+    fn parses_the_plus_key() {
+        assert_eq!(parse("Ctrl++"), (vec![Modifiers::Control], Key::Equal));
+        assert_eq!(parse("Ctrl+Plus"), (vec![Modifiers::Control], Key::Equal));
+        assert_eq!(parse("^+"), (vec![Modifiers::Control], Key::Equal));
+        assert_eq!(parse("++"), (vec![Modifiers::Shift], Key::Equal));
+        assert_eq!(parse("+"), (vec![], Key::Equal));
+    }
+
+    #[test]
+    fn formats_what_it_parses() {
+        for accelerator in ["Ctrl+Alt+F5", "Ctrl+Shift+A", "Super+ArrowLeft", "AltGr+Equal", "MediaPlayPause", "Ctrl+Semicolon"] {
+            let parsed = parse_accelerator_inner(accelerator).unwrap();
+            assert_eq!(format_accelerator(parsed), accelerator);
+        }
+
+        let parsed = parse_accelerator_inner("#!^+a").unwrap();
+        assert_eq!(format_accelerator(parsed), "Ctrl+Alt+Shift+Super+A");
+    }
+
+    #[test]
+    fn rejects_unknown_names() {
+        assert_eq!(error("Ctrl+Banana"), "Unknown key \"Banana\" in accelerator \"Ctrl+Banana\"");
+        assert_eq!(error("Cmdd+A"), "Unknown modifier \"Cmdd\" in accelerator \"Cmdd+A\"");
+        assert_eq!(error("Ctrl++A"), "Accelerator \"Ctrl++A\" has an empty part");
+        assert_eq!(error(""), "Accelerator \"\" has no key");
+        assert_eq!(error("Ctrl+"), "Accelerator \"Ctrl+\" has no key");
+    }
+
+    #[test]
+    fn parses_sided_modifiers() {
+        assert_eq!(parse("LCtrl+RShift+A"), (vec![Modifiers::LeftControl, Modifiers::RightShift], Key::A));
+        assert_eq!(parse("rightwin+leftalt+F1"), (vec![Modifiers::RightSuper, Modifiers::LeftAlt], Key::F1));
+        assert_eq!(parse("<^a"), (vec![Modifiers::LeftControl], Key::A));
+        assert_eq!(parse("^>!a"), (vec![Modifiers::Control, Modifiers::RightAlt], Key::A));
+        assert_eq!(parse("<#>+Tab"), (vec![Modifiers::LeftSuper, Modifiers::RightShift], Key::Tab));
+        assert_eq!(format_accelerator(parse_accelerator_inner("<^>!a").unwrap()), "LCtrl+RAlt+A");
+
+        assert_eq!(error("<a"), "Accelerator \"<a\" has no modifier symbol after \"<\"");
+        assert_eq!(error(">"), "Unknown key \">\" in accelerator \">\"");
+    }
+}
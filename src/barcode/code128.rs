@@ -0,0 +1,435 @@
+use crate::geometry::Point;
+use super::{Barcode, BarcodeFormat, BitMatrix};
+
+// Bar and space widths, in modules, of every symbol value. The stop symbol (106) is followed by
+// an extra 2 module bar.
+static PATTERNS: [[u8; 6]; 107] = [
+    [2, 1, 2, 2, 2, 2], [2, 2, 2, 1, 2, 2], [2, 2, 2, 2, 2, 1], [1, 2, 1, 2, 2, 3], [1, 2, 1, 3, 2, 2],
+    [1, 3, 1, 2, 2, 2], [1, 2, 2, 2, 1, 3], [1, 2, 2, 3, 1, 2], [1, 3, 2, 2, 1, 2], [2, 2, 1, 2, 1, 3],
+    [2, 2, 1, 3, 1, 2], [2, 3, 1, 2, 1, 2], [1, 1, 2, 2, 3, 2], [1, 2, 2, 1, 3, 2], [1, 2, 2, 2, 3, 1],
+    [1, 1, 3, 2, 2, 2], [1, 2, 3, 1, 2, 2], [1, 2, 3, 2, 2, 1], [2, 2, 3, 2, 1, 1], [2, 2, 1, 1, 3, 2],
+    [2, 2, 1, 2, 3, 1], [2, 1, 3, 2, 1, 2], [2, 2, 3, 1, 1, 2], [3, 1, 2, 1, 3, 1], [3, 1, 1, 2, 2, 2],
+    [3, 2, 1, 1, 2, 2], [3, 2, 1, 2, 2, 1], [3, 1, 2, 2, 1, 2], [3, 2, 2, 1, 1, 2], [3, 2, 2, 2, 1, 1],
+    [2, 1, 2, 1, 2, 3], [2, 1, 2, 3, 2, 1], [2, 3, 2, 1, 2, 1], [1, 1, 1, 3, 2, 3], [1, 3, 1, 1, 2, 3],
+    [1, 3, 1, 3, 2, 1], [1, 1, 2, 3, 1, 3], [1, 3, 2, 1, 1, 3], [1, 3, 2, 3, 1, 1], [2, 1, 1, 3, 1, 3],
+    [2, 3, 1, 1, 1, 3], [2, 3, 1, 3, 1, 1], [1, 1, 2, 1, 3, 3], [1, 1, 2, 3, 3, 1], [1, 3, 2, 1, 3, 1],
+    [1, 1, 3, 1, 2, 3], [1, 1, 3, 3, 2, 1], [1, 3, 3, 1, 2, 1], [3, 1, 3, 1, 2, 1], [2, 1, 1, 3, 3, 1],
+    [2, 3, 1, 1, 3, 1], [2, 1, 3, 1, 1, 3], [2, 1, 3, 3, 1, 1], [2, 1, 3, 1, 3, 1], [3, 1, 1, 1, 2, 3],
+    [3, 1, 1, 3, 2, 1], [3, 3, 1, 1, 2, 1], [3, 1, 2, 1, 1, 3], [3, 1, 2, 3, 1, 1], [3, 3, 2, 1, 1, 1],
+    [3, 1, 4, 1, 1, 1], [2, 2, 1, 4, 1, 1], [4, 3, 1, 1, 1, 1], [1, 1, 1, 2, 2, 4], [1, 1, 1, 4, 2, 2],
+    [1, 2, 1, 1, 2, 4], [1, 2, 1, 4, 2, 1], [1, 4, 1, 1, 2, 2], [1, 4, 1, 2, 2, 1], [1, 1, 2, 2, 1, 4],
+    [1, 1, 2, 4, 1, 2], [1, 2, 2, 1, 1, 4], [1, 2, 2, 4, 1, 1], [1, 4, 2, 1, 1, 2], [1, 4, 2, 2, 1, 1],
+    [2, 4, 1, 2, 1, 1], [2, 2, 1, 1, 1, 4], [4, 1, 3, 1, 1, 1], [2, 4, 1, 1, 1, 2], [1, 3, 4, 1, 1, 1],
+    [1, 1, 1, 2, 4, 2], [1, 2, 1, 1, 4, 2], [1, 2, 1, 2, 4, 1], [1, 1, 4, 2, 1, 2], [1, 2, 4, 1, 1, 2],
+    [1, 2, 4, 2, 1, 1], [4, 1, 1, 2, 1, 2], [4, 2, 1, 1, 1, 2], [4, 2, 1, 2, 1, 1], [2, 1, 2, 1, 4, 1],
+    [2, 1, 4, 1, 2, 1], [4, 1, 2, 1, 2, 1], [1, 1, 1, 1, 4, 3], [1, 1, 1, 3, 4, 1], [1, 3, 1, 1, 4, 1],
+    [1, 1, 4, 1, 1, 3], [1, 1, 4, 3, 1, 1], [4, 1, 1, 1, 1, 3], [4, 1, 1, 3, 1, 1], [1, 1, 3, 1, 4, 1],
+    [1, 1, 4, 1, 3, 1], [3, 1, 1, 1, 4, 1], [4, 1, 1, 1, 3, 1], [2, 1, 1, 4, 1, 2], [2, 1, 1, 2, 1, 4],
+    [2, 1, 1, 2, 3, 2], [2, 3, 3, 1, 1, 1],
+];
+
+const START_A: usize = 103;
+const START_B: usize = 104;
+const START_C: usize = 105;
+const STOP: usize = 106;
+
+const MAX_AVERAGE_VARIANCE: f64 = 0.25;
+const QUIET_ZONE_MODULES: f64 = 5.0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum CodeSet {
+    A,
+    B,
+    C,
+}
+
+/// The edge of the image the start symbol is closest to.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum StartSide {
+    Left,
+    Right,
+    Top,
+    Bottom,
+}
+
+#[derive(Debug)]
+struct Found {
+    text: String,
+    left: u32,
+    top: u32,
+    right: u32,
+    bottom: u32,
+    start_side: StartSide,
+}
+
+pub(super) fn decode(matrix: &BitMatrix) -> Vec<Barcode> {
+    let mut found = Vec::new();
+
+    scan_rows(matrix, false, &mut found);
+    scan_rows(&matrix.transpose(), true, &mut found);
+
+    found
+        .into_iter()
+        .map(|it| {
+            let top_left = Point::new(it.left as i32, it.top as i32);
+            let top_right = Point::new(it.right as i32, it.top as i32);
+            let bottom_right = Point::new(it.right as i32, it.bottom as i32);
+            let bottom_left = Point::new(it.left as i32, it.bottom as i32);
+
+            let corners = match it.start_side {
+                StartSide::Left => vec![top_left, top_right, bottom_right, bottom_left],
+                StartSide::Right => vec![bottom_right, bottom_left, top_left, top_right],
+                StartSide::Top => vec![top_right, bottom_right, bottom_left, top_left],
+                StartSide::Bottom => vec![bottom_left, top_left, top_right, bottom_right],
+            };
+
+            Barcode {
+                format: BarcodeFormat::Code128,
+                text: it.text,
+                corners,
+            }
+        })
+        .collect()
+}
+
+fn scan_rows(matrix: &BitMatrix, transposed: bool, found: &mut Vec<Found>) {
+    for y in 0..matrix.height {
+        // Run-length encode the row as (start, length, is_dark).
+        let mut runs: Vec<(u32, u32, bool)> = Vec::new();
+        for x in 0..matrix.width {
+            let dark = matrix.get(x, y);
+            match runs.last_mut() {
+                Some(run) if run.2 == dark => run.1 += 1,
+                _ => runs.push((x, 1, dark)),
+            }
+        }
+
+        let mut hits = decode_runs(&runs, false);
+
+        let reversed: Vec<(u32, u32, bool)> = runs
+            .iter()
+            .rev()
+            .map(|&(start, length, dark)| (matrix.width - start - length, length, dark))
+            .collect();
+        hits.extend(decode_runs(&reversed, true).into_iter().map(|(text, start, end, _)| {
+            (text, matrix.width - end, matrix.width - start, true)
+        }));
+
+        for (text, start, end, is_reversed) in hits {
+            let start_side = match (transposed, is_reversed) {
+                (false, false) => StartSide::Left,
+                (false, true) => StartSide::Right,
+                (true, false) => StartSide::Top,
+                (true, true) => StartSide::Bottom,
+            };
+
+            let (left, top, right, bottom) = if transposed { (y, start, y + 1, end) } else { (start, y, end, y + 1) };
+
+            let existing = found.iter_mut().find(|it| {
+                it.text == text
+                    && it.start_side == start_side
+                    && left < it.right + 1
+                    && it.left < right + 1
+                    && top < it.bottom + 1
+                    && it.top < bottom + 1
+            });
+
+            match existing {
+                Some(it) => {
+                    it.left = it.left.min(left);
+                    it.top = it.top.min(top);
+                    it.right = it.right.max(right);
+                    it.bottom = it.bottom.max(bottom);
+                }
+                None => found.push(Found {
+                    text,
+                    left,
+                    top,
+                    right,
+                    bottom,
+                    start_side,
+                }),
+            }
+        }
+    }
+}
+
+/// Matches six run lengths against every symbol, returning the closest one if it's close enough.
+fn match_symbol(runs: &[(u32, u32, bool)]) -> Option<usize> {
+    let total: u32 = runs.iter().map(|run| run.1).sum();
+    let module_size = total as f64 / 11.0;
+
+    let (variance, value) = PATTERNS
+        .iter()
+        .enumerate()
+        .map(|(value, pattern)| {
+            let variance: f64 = runs
+                .iter()
+                .zip(pattern.iter())
+                .map(|(run, &width)| (run.1 as f64 - width as f64 * module_size).abs())
+                .sum();
+            (variance / total as f64, value)
+        })
+        .min_by(|a, b| a.0.partial_cmp(&b.0).unwrap())?;
+
+    if variance < MAX_AVERAGE_VARIANCE {
+        Some(value)
+    } else {
+        None
+    }
+}
+
+/// Returns (text, start x, end x, reversed) for every barcode in a row.
+fn decode_runs(runs: &[(u32, u32, bool)], reversed: bool) -> Vec<(String, u32, u32, bool)> {
+    let mut hits = Vec::new();
+    let mut i = 0;
+
+    while i + 6 <= runs.len() {
+        if !runs[i].2 {
+            i += 1;
+            continue;
+        }
+
+        let start = match match_symbol(&runs[i..i + 6]) {
+            Some(value @ (START_A | START_B | START_C)) => value,
+            _ => {
+                i += 1;
+                continue;
+            }
+        };
+
+        let module_size = runs[i..i + 6].iter().map(|run| run.1).sum::<u32>() as f64 / 11.0;
+        let has_quiet_zone = i == 0 || runs[i - 1].1 as f64 >= module_size * QUIET_ZONE_MODULES;
+        if !has_quiet_zone {
+            i += 1;
+            continue;
+        }
+
+        let mut values = vec![start];
+        let mut j = i + 6;
+        let mut end = None;
+
+        while j + 6 <= runs.len() {
+            match match_symbol(&runs[j..j + 6]) {
+                Some(STOP) => {
+                    if j + 7 <= runs.len() && runs[j + 6].2 {
+                        end = Some(runs[j + 6].0 + runs[j + 6].1);
+                    }
+                    break;
+                }
+                Some(value) if value < START_A => {
+                    values.push(value);
+                    j += 6;
+                }
+                _ => break,
+            }
+        }
+
+        let text = match end {
+            Some(_) => decode_values(&values),
+            None => None,
+        };
+
+        match (text, end) {
+            (Some(text), Some(end)) => {
+                hits.push((text, runs[i].0, end, reversed));
+                i = j + 7;
+            }
+            _ => i += 1,
+        }
+    }
+
+    hits
+}
+
+/// Verifies the checksum and converts symbol values (start symbol first, checksum last) to text.
+fn decode_values(values: &[usize]) -> Option<String> {
+    if values.len() < 3 {
+        return None;
+    }
+
+    let (checksum, symbols) = values.split_last()?;
+    let sum = symbols
+        .iter()
+        .enumerate()
+        .map(|(i, &value)| if i == 0 { value } else { i * value })
+        .sum::<usize>();
+
+    if sum % 103 != *checksum {
+        return None;
+    }
+
+    let mut code_set = match symbols[0] {
+        START_A => CodeSet::A,
+        START_B => CodeSet::B,
+        _ => CodeSet::C,
+    };
+    let mut shifted = false;
+    let mut extended = false;
+    let mut text = String::new();
+
+    for (i, &value) in symbols.iter().enumerate().skip(1) {
+        let current = match (shifted, code_set) {
+            (true, CodeSet::A) => CodeSet::B,
+            (true, CodeSet::B) => CodeSet::A,
+            _ => code_set,
+        };
+        shifted = false;
+
+        let character = match (current, value) {
+            (CodeSet::C, 0..=99) => {
+                text.push_str(&format!("{:02}", value));
+                continue;
+            }
+            (CodeSet::A, 0..=63) | (CodeSet::B, 0..=95) => value as u32 + 32,
+            (CodeSet::A, 64..=95) => value as u32 - 64,
+            // FNC1 separates GS1 fields, except in first position where it only flags the format.
+            (_, 102) => {
+                if i > 1 {
+                    text.push('\u{1d}');
+                }
+                continue;
+            }
+            (CodeSet::A, 101) | (CodeSet::B, 100) => {
+                extended = true;
+                continue;
+            }
+            (CodeSet::A | CodeSet::B, 98) => {
+                shifted = true;
+                continue;
+            }
+            (CodeSet::A | CodeSet::B, 99) => {
+                code_set = CodeSet::C;
+                continue;
+            }
+            (CodeSet::A | CodeSet::C, 100) => {
+                code_set = CodeSet::B;
+                continue;
+            }
+            (CodeSet::B | CodeSet::C, 101) => {
+                code_set = CodeSet::A;
+                continue;
+            }
+            // FNC2 and FNC3 have no textual representation.
+            (CodeSet::A | CodeSet::B, 96 | 97) => continue,
+            _ => return None,
+        };
+
+        let character = if extended { character + 128 } else { character };
+        extended = false;
+        text.push(char::from_u32(character)?);
+    }
+
+    Some(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CODE_B: usize = 100;
+
+    /// Symbol values from the start symbol to the stop symbol, with the checksum.
+    fn symbol(start: usize, values: &[usize]) -> Vec<usize> {
+        let mut symbol = vec![start];
+        symbol.extend_from_slice(values);
+
+        let checksum = symbol.iter().enumerate().map(|(i, &value)| if i == 0 { value } else { i * value }).sum::<usize>();
+        symbol.push(checksum % 103);
+        symbol.push(STOP);
+        symbol
+    }
+
+    fn code_b(text: &str) -> Vec<usize> {
+        text.bytes().map(|byte| byte as usize - 32).collect()
+    }
+
+    /// Draws the bars `scale` pixels per module and `height` rows high, inside a 10 module quiet
+    /// zone.
+    fn render(symbol: &[usize], scale: u32, height: u32) -> BitMatrix {
+        let mut row = vec![false; 10 * scale as usize];
+        for &value in symbol {
+            for (i, &width) in PATTERNS[value].iter().enumerate() {
+                row.extend(std::iter::repeat_n(i % 2 == 0, (width as u32 * scale) as usize));
+            }
+        }
+        row.extend(std::iter::repeat_n(true, 2 * scale as usize));
+        row.extend(std::iter::repeat_n(false, 10 * scale as usize));
+
+        BitMatrix {
+            width: row.len() as u32,
+            height,
+            bits: row.iter().copied().cycle().take(row.len() * height as usize).collect(),
+        }
+    }
+
+    fn mirror(matrix: &BitMatrix) -> BitMatrix {
+        let mut bits = Vec::with_capacity(matrix.bits.len());
+        for y in 0..matrix.height {
+            for x in (0..matrix.width).rev() {
+                bits.push(matrix.get(x, y));
+            }
+        }
+
+        BitMatrix { width: matrix.width, height: matrix.height, bits }
+    }
+
+    fn texts(matrix: &BitMatrix) -> Vec<String> {
+        decode(matrix).into_iter().map(|it| it.text).collect()
+    }
+
+    #[test]
+    fn decodes_code_set_b() {
+        let matrix = render(&symbol(START_B, &code_b("Hello, World!")), 2, 10);
+        let barcodes = decode(&matrix);
+
+        assert_eq!(barcodes.len(), 1);
+        assert_eq!(barcodes[0].text, "Hello, World!");
+
+        // 20 pixels of quiet zone, then 16 symbols of 11 modules plus the final bar, 2 pixels each.
+        let corners: Vec<(i32, i32)> = barcodes[0].corners.iter().map(|it| (it.x, it.y)).collect();
+        assert_eq!(corners, vec![(20, 0), (20 + 2 * (16 * 11 + 2), 0), (20 + 2 * (16 * 11 + 2), 10), (20, 10)]);
+    }
+
+    #[test]
+    fn switches_code_sets() {
+        // "1234" as digit pairs in code set C, then "AB" in code set B.
+        let mut values = vec![12, 34, CODE_B];
+        values.extend(code_b("AB"));
+
+        assert_eq!(texts(&render(&symbol(START_C, &values), 3, 5)), vec!["1234AB".to_string()]);
+    }
+
+    #[test]
+    fn decodes_in_every_direction() {
+        let matrix = render(&symbol(START_B, &code_b("sideways")), 2, 6);
+
+        let upside_down = decode(&mirror(&matrix));
+        assert_eq!(upside_down.len(), 1);
+        assert_eq!(upside_down[0].text, "sideways");
+
+        let vertical = decode(&matrix.transpose());
+        assert_eq!(vertical.len(), 1);
+        assert_eq!(vertical[0].text, "sideways");
+        assert_eq!((vertical[0].corners[0].x, vertical[0].corners[0].y), (6, 20));
+    }
+
+    #[test]
+    fn rejects_a_wrong_checksum() {
+        let mut symbol = symbol(START_B, &code_b("checksum"));
+        let checksum = symbol.len() - 2;
+        symbol[checksum] = (symbol[checksum] + 1) % 103;
+
+        assert!(texts(&render(&symbol, 2, 4)).is_empty());
+    }
+
+    #[test]
+    fn requires_a_quiet_zone() {
+        let mut matrix = render(&symbol(START_B, &code_b("quiet")), 2, 1);
+        for x in 10..20 {
+            matrix.bits[x] = true;
+        }
+
+        assert!(texts(&matrix).is_empty());
+    }
+}
@@ -0,0 +1,286 @@
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+use crate::geometry::{Point, Rect};
+use crate::screen::ImageData;
+use crate::utils::handle_result;
+
+mod code128;
+mod qr;
+
+/// How much a pixel has to be darker than its neighbourhood to count as dark, and the standard
+/// deviation below which a neighbourhood counts as flat.
+const FLAT_DEVIATION: u64 = 8;
+
+#[napi]
+#[derive(Debug, PartialEq)]
+pub enum BarcodeFormat {
+    QrCode,
+    Code128,
+}
+
+#[napi(object)]
+#[derive(Debug, Clone)]
+pub struct Barcode {
+    pub format: BarcodeFormat,
+    pub text: String,
+    /// Corners of the symbol in image coordinates, clockwise from its top-left corner.
+    pub corners: Vec<Point>,
+}
+
+/// Decodes every QR code and Code 128 barcode found in `image_data`, or only inside `region`.
+///
+/// QR codes may be rotated but not perspective-distorted, which is what screenshots produce.
+/// Code 128 barcodes are read horizontally and vertically.
+#[napi]
+pub async fn decode_barcodes(
+    image_data: &ImageData,
+    region: Option<Rect>,
+    formats: Option<Vec<BarcodeFormat>>,
+) -> Result<Vec<Barcode>> {
+    let image_data = image_data.clone();
+    let formats = formats.unwrap_or(vec![BarcodeFormat::QrCode, BarcodeFormat::Code128]);
+
+    let task = tokio::spawn(async move {
+        let region = clamp_region(&image_data, region);
+        let matrix = BitMatrix::binarize(&image_data, &region);
+        let mut barcodes = Vec::new();
+
+        if formats.contains(&BarcodeFormat::QrCode) {
+            barcodes.extend(qr::decode(&matrix));
+        }
+
+        if formats.contains(&BarcodeFormat::Code128) {
+            barcodes.extend(code128::decode(&matrix));
+        }
+
+        for barcode in barcodes.iter_mut() {
+            for corner in barcode.corners.iter_mut() {
+                corner.x += region.left;
+                corner.y += region.top;
+            }
+        }
+
+        Ok(barcodes)
+    });
+
+    handle_result(task).await
+}
+
+fn clamp_region(image_data: &ImageData, region: Option<Rect>) -> Rect {
+    let width = image_data.width as i32;
+    let height = image_data.height as i32;
+
+    match region {
+        Some(region) => Rect::new(
+            region.left.clamp(0, width),
+            region.top.clamp(0, height),
+            region.right.clamp(region.left.clamp(0, width), width),
+            region.bottom.clamp(region.top.clamp(0, height), height),
+        ),
+        None => Rect::new(0, 0, width, height),
+    }
+}
+
+/// A black and white version of an image, `true` being dark.
+pub(crate) struct BitMatrix {
+    pub width: u32,
+    pub height: u32,
+    bits: Vec<bool>,
+}
+
+impl BitMatrix {
+    /// Thresholds every pixel against the mean of its neighbourhood, so codes stay readable on
+    /// unevenly lit or gradient backgrounds. Inside large flat areas, where the local mean says
+    /// nothing, pixels fall back to a global Otsu threshold, and an image without any contrast is
+    /// light throughout.
+    fn binarize(image_data: &ImageData, region: &Rect) -> Self {
+        let pixels = image_data.data.as_slice();
+        let pixel_width = image_data.pixel_width as usize;
+
+        let width = (region.right - region.left) as u32;
+        let height = (region.bottom - region.top) as u32;
+
+        let mut luminance = Vec::with_capacity((width * height) as usize);
+        for y in 0..height {
+            for x in 0..width {
+                let index = (((region.top as u32 + y) * image_data.width + region.left as u32 + x) as usize) * pixel_width;
+                let red = pixels[index] as u32;
+                let green = pixels[index + 1] as u32;
+                let blue = pixels[index + 2] as u32;
+                luminance.push(((red * 299 + green * 587 + blue * 114) / 1000) as u8);
+            }
+        }
+
+        let global = otsu_threshold(&luminance);
+
+        // Summed-area tables of the luminance and its square, one row and column larger than the
+        // image.
+        let stride = width as usize + 1;
+        let mut integral = vec![0u64; stride * (height as usize + 1)];
+        let mut squares = vec![0u64; stride * (height as usize + 1)];
+        for y in 0..height as usize {
+            let mut row_sum = 0u64;
+            let mut row_squares = 0u64;
+            for x in 0..width as usize {
+                let value = luminance[y * width as usize + x] as u64;
+                row_sum += value;
+                row_squares += value * value;
+                integral[(y + 1) * stride + x + 1] = integral[y * stride + x + 1] + row_sum;
+                squares[(y + 1) * stride + x + 1] = squares[y * stride + x + 1] + row_squares;
+            }
+        }
+        let area_sum = |table: &[u64], top: usize, bottom: usize, left: usize, right: usize| {
+            table[bottom * stride + right] + table[top * stride + left] - table[top * stride + right] - table[bottom * stride + left]
+        };
+
+        let radius = (width.min(height) / 16).max(16) as i64;
+        let mut bits = Vec::with_capacity(luminance.len());
+
+        for y in 0..height as i64 {
+            let top = (y - radius).max(0) as usize;
+            let bottom = (y + radius + 1).min(height as i64) as usize;
+
+            for x in 0..width as i64 {
+                let left = (x - radius).max(0) as usize;
+                let right = (x + radius + 1).min(width as i64) as usize;
+
+                let count = ((bottom - top) * (right - left)) as u64;
+                let sum = area_sum(&integral, top, bottom, left, right);
+                let mean = sum / count;
+                // Less than FLAT_DEVIATION of standard deviation, i.e. no edge nearby.
+                let flat = count * area_sum(&squares, top, bottom, left, right) - sum * sum
+                    < FLAT_DEVIATION * FLAT_DEVIATION * count * count;
+
+                let value = luminance[y as usize * width as usize + x as usize] as u64;
+                bits.push(match flat {
+                    true => global.is_some_and(|global| value <= global as u64),
+                    false => value + FLAT_DEVIATION < mean,
+                });
+            }
+        }
+
+        Self { width, height, bits }
+    }
+
+    pub fn get(&self, x: u32, y: u32) -> bool {
+        self.bits[(y * self.width + x) as usize]
+    }
+
+    /// Same as `get`, but anything outside of the matrix is light.
+    pub fn get_signed(&self, x: i32, y: i32) -> bool {
+        x >= 0 && y >= 0 && (x as u32) < self.width && (y as u32) < self.height && self.get(x as u32, y as u32)
+    }
+
+    pub fn transpose(&self) -> Self {
+        let mut bits = Vec::with_capacity(self.bits.len());
+        for x in 0..self.width {
+            for y in 0..self.height {
+                bits.push(self.get(x, y));
+            }
+        }
+
+        Self {
+            width: self.height,
+            height: self.width,
+            bits,
+        }
+    }
+}
+
+/// The luminance separating dark (at most the threshold) from light pixels best, `None` when
+/// all pixels are equally bright.
+fn otsu_threshold(luminance: &[u8]) -> Option<u8> {
+    let mut histogram = [0u64; 256];
+    for &value in luminance {
+        histogram[value as usize] += 1;
+    }
+
+    let total = luminance.len() as f64;
+    let sum: f64 = histogram.iter().enumerate().map(|(i, &count)| i as f64 * count as f64).sum();
+
+    let mut background_sum = 0.0;
+    let mut background_weight = 0.0;
+    let mut best_threshold = None;
+    let mut best_variance = 0.0;
+
+    for (i, &count) in histogram.iter().enumerate() {
+        background_weight += count as f64;
+        if background_weight == 0.0 {
+            continue;
+        }
+
+        let foreground_weight = total - background_weight;
+        if foreground_weight == 0.0 {
+            break;
+        }
+
+        background_sum += i as f64 * count as f64;
+        let background_mean = background_sum / background_weight;
+        let foreground_mean = (sum - background_sum) / foreground_weight;
+        let variance = background_weight * foreground_weight * (background_mean - foreground_mean).powi(2);
+
+        if variance > best_variance {
+            best_variance = variance;
+            best_threshold = Some(i as u8);
+        }
+    }
+
+    best_threshold
+}
+
+#[cfg(test)]
+mod tests {
+    use std::mem::ManuallyDrop;
+    use super::*;
+
+    /// A gray image, leaked rather than dropped: dropping an `ImageData` links in the N-API calls
+    /// releasing a JS `Buffer`, which are unresolved outside of Node.
+    fn image(width: u32, height: u32, luminance: impl Fn(u32, u32) -> u8) -> &'static ImageData {
+        let mut data = Vec::with_capacity((width * height * 4) as usize);
+        for y in 0..height {
+            for x in 0..width {
+                let value = luminance(x, y);
+                data.extend_from_slice(&[value, value, value, 255]);
+            }
+        }
+
+        let image = Box::leak(Box::new(ManuallyDrop::new(ImageData { data: data.into(), width, height, pixel_width: 4 })));
+        image
+    }
+
+    fn binarize(image: &ImageData) -> BitMatrix {
+        BitMatrix::binarize(image, &clamp_region(image, None))
+    }
+
+    #[test]
+    fn keeps_large_flat_areas_dark_or_light() {
+        // The square is wider than the neighbourhoods, its middle sees no white.
+        let square = |x: u32, y: u32| (20..100).contains(&x) && (20..100).contains(&y);
+        let matrix = binarize(image(120, 120, |x, y| if square(x, y) { 0 } else { 255 }));
+
+        assert!((40..80).all(|y| (40..80).all(|x| matrix.get(x, y))));
+        assert!((0..120).all(|x| !matrix.get(x, 0) && !matrix.get(x, 119)));
+        assert!((20..100).all(|i| matrix.get(i, 20) && matrix.get(20, i) && matrix.get(i, 99) && matrix.get(99, i)));
+        assert!((20..100).all(|i| !matrix.get(i, 19) && !matrix.get(19, i) && !matrix.get(i, 100) && !matrix.get(100, i)));
+    }
+
+    #[test]
+    fn follows_the_neighbourhood_on_gradients() {
+        // A dark line on a background brightening from left to right, which is darker on the left
+        // than the line is on the right.
+        let matrix = binarize(image(200, 40, |x, y| {
+            let background = 60 + (x * 190 / 200) as u8;
+            if y == 20 { background - 50 } else { background }
+        }));
+
+        assert!((0..200).all(|x| matrix.get(x, 20)));
+        assert!((0..200).all(|x| !matrix.get(x, 10)));
+    }
+
+    #[test]
+    fn leaves_an_image_without_contrast_light() {
+        let matrix = binarize(image(40, 40, |_, _| 90));
+
+        assert!((0..40).all(|y| (0..40).all(|x| !matrix.get(x, y))));
+    }
+}
@@ -0,0 +1,1224 @@
+use crate::geometry::Point;
+use super::{Barcode, BarcodeFormat, BitMatrix};
+
+const MAX_FINDER_PATTERNS: usize = 40;
+
+// Indexed by error correction level ordinal (L, M, Q, H), then by version.
+static ECC_CODEWORDS_PER_BLOCK: [[u8; 41]; 4] = [
+    [0, 7, 10, 15, 20, 26, 18, 20, 24, 30, 18, 20, 24, 26, 30, 22, 24, 28, 30, 28, 28, 28, 28, 30, 30, 26, 28, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30],
+    [0, 10, 16, 26, 18, 24, 16, 18, 22, 22, 26, 30, 22, 22, 24, 24, 28, 28, 26, 26, 26, 26, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28],
+    [0, 13, 22, 18, 26, 18, 24, 18, 22, 20, 24, 28, 26, 24, 20, 30, 24, 28, 28, 26, 30, 28, 30, 30, 30, 30, 28, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30],
+    [0, 17, 28, 22, 16, 22, 28, 26, 26, 24, 28, 24, 28, 22, 24, 24, 30, 28, 28, 26, 28, 30, 24, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30],
+];
+
+static NUM_ERROR_CORRECTION_BLOCKS: [[u8; 41]; 4] = [
+    [0, 1, 1, 1, 1, 1, 2, 2, 2, 2, 4, 4, 4, 4, 4, 6, 6, 6, 6, 7, 8, 8, 9, 9, 10, 12, 12, 12, 13, 14, 15, 16, 17, 18, 19, 19, 20, 21, 22, 24, 25],
+    [0, 1, 1, 1, 2, 2, 4, 4, 4, 5, 5, 5, 8, 9, 9, 10, 10, 11, 13, 14, 16, 17, 17, 18, 20, 21, 23, 25, 26, 28, 29, 31, 33, 35, 37, 38, 40, 43, 45, 47, 49],
+    [0, 1, 1, 2, 2, 4, 4, 6, 6, 8, 8, 8, 10, 12, 16, 12, 17, 16, 18, 21, 20, 23, 23, 25, 27, 29, 34, 34, 35, 38, 40, 43, 45, 48, 51, 53, 56, 59, 62, 65, 68],
+    [0, 1, 1, 2, 4, 4, 4, 5, 6, 8, 8, 11, 11, 16, 16, 18, 16, 19, 21, 25, 25, 25, 34, 30, 32, 35, 37, 40, 42, 45, 48, 51, 54, 57, 60, 63, 66, 70, 74, 77, 81],
+];
+
+const ALPHANUMERIC_CHARSET: &[u8] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZ $%*+-./:";
+
+#[derive(Debug, Clone, Copy)]
+struct FinderPattern {
+    x: f64,
+    y: f64,
+    module_size: f64,
+    count: u32,
+}
+
+pub(super) fn decode(matrix: &BitMatrix) -> Vec<Barcode> {
+    let patterns = find_finder_patterns(matrix);
+    let mut used = vec![false; patterns.len()];
+    let mut barcodes = Vec::new();
+
+    for [top_left, top_right, bottom_left] in group_finder_patterns(&patterns) {
+        if used[top_left] || used[top_right] || used[bottom_left] {
+            continue;
+        }
+
+        if let Some(barcode) = decode_symbol(matrix, &patterns[top_left], &patterns[top_right], &patterns[bottom_left]) {
+            used[top_left] = true;
+            used[top_right] = true;
+            used[bottom_left] = true;
+            barcodes.push(barcode);
+        }
+    }
+
+    barcodes
+}
+
+/// Checks run lengths against the 1:1:3:1:1 ratio of a finder pattern.
+fn is_finder_ratio(counts: &[u32]) -> bool {
+    let total: u32 = counts.iter().sum();
+    if total < 7 {
+        return false;
+    }
+
+    let module_size = total as f64 / 7.0;
+    let max_variance = module_size / 2.0;
+
+    (module_size - counts[0] as f64).abs() < max_variance
+        && (module_size - counts[1] as f64).abs() < max_variance
+        && (3.0 * module_size - counts[2] as f64).abs() < 3.0 * max_variance
+        && (module_size - counts[3] as f64).abs() < max_variance
+        && (module_size - counts[4] as f64).abs() < max_variance
+}
+
+/// Measures the finder pattern through (`x`, `y`) along (`dx`, `dy`), returning the center of its
+/// dark core on that axis and the pattern's total length.
+fn cross_check(matrix: &BitMatrix, x: i32, y: i32, dx: i32, dy: i32, max_count: u32) -> Option<(f64, u32)> {
+    let inside = |i: i32| {
+        let px = x + dx * i;
+        let py = y + dy * i;
+        px >= 0 && py >= 0 && (px as u32) < matrix.width && (py as u32) < matrix.height
+    };
+    let at = |i: i32| matrix.get_signed(x + dx * i, y + dy * i);
+
+    let mut counts = [0u32; 5];
+
+    let mut i = 0;
+    while inside(i) && at(i) {
+        counts[2] += 1;
+        i -= 1;
+    }
+    let core_start = i + 1;
+
+    while inside(i) && !at(i) && counts[1] <= max_count {
+        counts[1] += 1;
+        i -= 1;
+    }
+    if !inside(i) || counts[1] > max_count {
+        return None;
+    }
+
+    while inside(i) && at(i) && counts[0] <= max_count {
+        counts[0] += 1;
+        i -= 1;
+    }
+    if counts[0] > max_count {
+        return None;
+    }
+
+    let mut i = 1;
+    while inside(i) && at(i) {
+        counts[2] += 1;
+        i += 1;
+    }
+    let core_end = i - 1;
+
+    while inside(i) && !at(i) && counts[3] <= max_count {
+        counts[3] += 1;
+        i += 1;
+    }
+    if !inside(i) || counts[3] > max_count {
+        return None;
+    }
+
+    while inside(i) && at(i) && counts[4] <= max_count {
+        counts[4] += 1;
+        i += 1;
+    }
+    if counts[4] > max_count {
+        return None;
+    }
+
+    if counts[2] == 0 || !is_finder_ratio(&counts) {
+        return None;
+    }
+
+    let base = if dx != 0 { x } else { y };
+    let center = base as f64 + (core_start + core_end + 1) as f64 / 2.0;
+
+    Some((center, counts.iter().sum()))
+}
+
+fn find_finder_patterns(matrix: &BitMatrix) -> Vec<FinderPattern> {
+    let mut candidates: Vec<FinderPattern> = Vec::new();
+
+    for y in 0..matrix.height {
+        // Run-length encode the row as (start, length, is_dark).
+        let mut runs: Vec<(u32, u32, bool)> = Vec::new();
+        for x in 0..matrix.width {
+            let dark = matrix.get(x, y);
+            match runs.last_mut() {
+                Some(run) if run.2 == dark => run.1 += 1,
+                _ => runs.push((x, 1, dark)),
+            }
+        }
+
+        for i in 0..runs.len().saturating_sub(4) {
+            if !runs[i].2 {
+                continue;
+            }
+
+            let counts = [runs[i].1, runs[i + 1].1, runs[i + 2].1, runs[i + 3].1, runs[i + 4].1];
+            if !is_finder_ratio(&counts) {
+                continue;
+            }
+
+            let horizontal_total: u32 = counts.iter().sum();
+            let column = (runs[i + 2].0 + runs[i + 2].1 / 2) as i32;
+
+            let (center_y, vertical_total) = match cross_check(matrix, column, y as i32, 0, 1, horizontal_total) {
+                Some(result) => result,
+                None => continue,
+            };
+
+            if 5 * (vertical_total as i64 - horizontal_total as i64).unsigned_abs() >= 2 * horizontal_total as u64 {
+                continue;
+            }
+
+            let (center_x, horizontal_total) = match cross_check(matrix, column, center_y as i32, 1, 0, horizontal_total) {
+                Some(result) => result,
+                None => continue,
+            };
+
+            let module_size = (horizontal_total + vertical_total) as f64 / 14.0;
+
+            let existing = candidates.iter_mut().find(|it| {
+                (it.x - center_x).abs() <= it.module_size
+                    && (it.y - center_y).abs() <= it.module_size
+                    && (it.module_size - module_size).abs() <= it.module_size.max(1.0)
+            });
+
+            match existing {
+                Some(it) => {
+                    let count = it.count as f64;
+                    it.x = (it.x * count + center_x) / (count + 1.0);
+                    it.y = (it.y * count + center_y) / (count + 1.0);
+                    it.module_size = (it.module_size * count + module_size) / (count + 1.0);
+                    it.count += 1;
+                }
+                None => candidates.push(FinderPattern {
+                    x: center_x,
+                    y: center_y,
+                    module_size,
+                    count: 1,
+                }),
+            }
+        }
+    }
+
+    candidates.retain(|it| it.count >= 2);
+    candidates.sort_by_key(|it| std::cmp::Reverse(it.count));
+    candidates.truncate(MAX_FINDER_PATTERNS);
+    candidates
+}
+
+fn distance(a: &FinderPattern, b: &FinderPattern) -> f64 {
+    ((a.x - b.x).powi(2) + (a.y - b.y).powi(2)).sqrt()
+}
+
+/// Finder patterns are measured along the image axes, which cuts through rotated ones at an angle
+/// and overestimates their size. Multiplying by this, for the side from `a` to `b`, undoes that.
+fn slant(a: &FinderPattern, b: &FinderPattern) -> f64 {
+    (a.x - b.x).abs().max((a.y - b.y).abs()) / distance(a, b)
+}
+
+/// Finds triples of finder patterns laid out as the corners of a symbol, best fitting first,
+/// as indices of (top-left, top-right, bottom-left).
+fn group_finder_patterns(patterns: &[FinderPattern]) -> Vec<[usize; 3]> {
+    let mut groups = Vec::new();
+
+    for i in 0..patterns.len() {
+        for j in i + 1..patterns.len() {
+            for k in j + 1..patterns.len() {
+                let sizes = [patterns[i].module_size, patterns[j].module_size, patterns[k].module_size];
+                let min_size = sizes.iter().cloned().fold(f64::MAX, f64::min);
+                let max_size = sizes.iter().cloned().fold(0.0, f64::max);
+                if max_size > min_size * 1.5 {
+                    continue;
+                }
+
+                // The corner opposite the longest side is the top-left one.
+                let mut sides = [
+                    (distance(&patterns[j], &patterns[k]), i, j, k),
+                    (distance(&patterns[i], &patterns[k]), j, i, k),
+                    (distance(&patterns[i], &patterns[j]), k, i, j),
+                ];
+                sides.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+
+                let (leg_a, leg_b, hypotenuse) = (sides[0].0, sides[1].0, sides[2].0);
+                let (_, top_left, p, q) = sides[2];
+
+                let module_size = sizes.iter().sum::<f64>() / 3.0 * slant(&patterns[top_left], &patterns[p]);
+                if leg_a / module_size < 12.0 {
+                    continue;
+                }
+
+                let leg_error = (leg_b - leg_a) / leg_b;
+                let hypotenuse_error = (hypotenuse - (leg_a * leg_a + leg_b * leg_b).sqrt()).abs() / hypotenuse;
+                if leg_error > 0.2 || hypotenuse_error > 0.1 {
+                    continue;
+                }
+
+                let origin = &patterns[top_left];
+                let cross = (patterns[p].x - origin.x) * (patterns[q].y - origin.y)
+                    - (patterns[p].y - origin.y) * (patterns[q].x - origin.x);
+                let (top_right, bottom_left) = if cross > 0.0 { (p, q) } else { (q, p) };
+
+                groups.push((leg_error + hypotenuse_error, [top_left, top_right, bottom_left]));
+            }
+        }
+    }
+
+    groups.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+    groups.into_iter().map(|(_, group)| group).collect()
+}
+
+/// Maps module coordinates onto the image, anchored on the centers of the three finder patterns.
+struct Grid {
+    origin: (f64, f64),
+    right: (f64, f64),
+    down: (f64, f64),
+}
+
+impl Grid {
+    fn new(top_left: &FinderPattern, top_right: &FinderPattern, bottom_left: &FinderPattern, dimension: u32) -> Self {
+        let span = (dimension - 7) as f64;
+
+        Self {
+            origin: (top_left.x, top_left.y),
+            right: ((top_right.x - top_left.x) / span, (top_right.y - top_left.y) / span),
+            down: ((bottom_left.x - top_left.x) / span, (bottom_left.y - top_left.y) / span),
+        }
+    }
+
+    fn map(&self, column: f64, row: f64) -> (f64, f64) {
+        let column = column - 3.5;
+        let row = row - 3.5;
+
+        (
+            self.origin.0 + column * self.right.0 + row * self.down.0,
+            self.origin.1 + column * self.right.1 + row * self.down.1,
+        )
+    }
+
+    fn sample(&self, matrix: &BitMatrix, dimension: u32) -> Vec<bool> {
+        let mut modules = Vec::with_capacity((dimension * dimension) as usize);
+        for row in 0..dimension {
+            for column in 0..dimension {
+                let (x, y) = self.map(column as f64 + 0.5, row as f64 + 0.5);
+                modules.push(matrix.get_signed(x.floor() as i32, y.floor() as i32));
+            }
+        }
+
+        modules
+    }
+}
+
+fn decode_symbol(
+    matrix: &BitMatrix,
+    top_left: &FinderPattern,
+    top_right: &FinderPattern,
+    bottom_left: &FinderPattern,
+) -> Option<Barcode> {
+    let top_side = distance(top_left, top_right);
+    let module_size = (top_left.module_size + top_right.module_size + bottom_left.module_size) / 3.0
+        * slant(top_left, top_right);
+    let modules_across = (top_side + distance(top_left, bottom_left)) / 2.0 / module_size + 7.0;
+    let estimate = ((modules_across - 17.0) / 4.0).round() as i32;
+
+    let mut tried = Vec::new();
+    let mut queue = vec![estimate, estimate - 1, estimate + 1];
+
+    while let Some(version) = queue.pop() {
+        if !(1..=40).contains(&version) || tried.contains(&version) {
+            continue;
+        }
+        tried.push(version);
+
+        let version = version as u32;
+        let dimension = 17 + 4 * version;
+        let grid = Grid::new(top_left, top_right, bottom_left, dimension);
+        let modules = grid.sample(matrix, dimension);
+
+        if version >= 7 {
+            match read_version(&modules, dimension) {
+                Some(read) if read == version => {}
+                Some(read) => {
+                    queue.push(read as i32);
+                    continue;
+                }
+                None => continue,
+            }
+        }
+
+        if let Some(text) = decode_modules(&modules, version) {
+            let corners = [(0.0, 0.0), (dimension as f64, 0.0), (dimension as f64, dimension as f64), (0.0, dimension as f64)]
+                .iter()
+                .map(|&(column, row)| {
+                    let (x, y) = grid.map(column, row);
+                    Point::new(x.round() as i32, y.round() as i32)
+                })
+                .collect();
+
+            return Some(Barcode {
+                format: BarcodeFormat::QrCode,
+                text,
+                corners,
+            });
+        }
+    }
+
+    None
+}
+
+fn module(modules: &[bool], dimension: u32, x: u32, y: u32) -> bool {
+    modules[(y * dimension + x) as usize]
+}
+
+fn encode_format(data: u32) -> u32 {
+    let mut rem = data;
+    for _ in 0..10 {
+        rem = (rem << 1) ^ ((rem >> 9) * 0x537);
+    }
+
+    (data << 10 | rem) ^ 0x5412
+}
+
+fn encode_version(version: u32) -> u32 {
+    let mut rem = version;
+    for _ in 0..12 {
+        rem = (rem << 1) ^ ((rem >> 11) * 0x1F25);
+    }
+
+    version << 12 | rem
+}
+
+/// Returns (error correction level ordinal, mask).
+fn read_format(modules: &[bool], dimension: u32) -> Option<(usize, u32)> {
+    let mut first = 0u32;
+    let mut second = 0u32;
+
+    for i in 0..15 {
+        let (x, y) = match i {
+            0..=5 => (8, i),
+            6 => (8, 7),
+            7 => (8, 8),
+            8 => (7, 8),
+            _ => (14 - i, 8),
+        };
+        if module(modules, dimension, x, y) {
+            first |= 1 << i;
+        }
+
+        let (x, y) = if i < 8 { (dimension - 1 - i, 8) } else { (8, dimension - 15 + i) };
+        if module(modules, dimension, x, y) {
+            second |= 1 << i;
+        }
+    }
+
+    let (errors, data) = (0..32)
+        .map(|data| {
+            let code = encode_format(data);
+            ((code ^ first).count_ones().min((code ^ second).count_ones()), data)
+        })
+        .min()?;
+
+    if errors > 3 {
+        return None;
+    }
+
+    let level = match data >> 3 {
+        1 => 0,
+        0 => 1,
+        3 => 2,
+        _ => 3,
+    };
+
+    Some((level, data & 7))
+}
+
+fn read_version(modules: &[bool], dimension: u32) -> Option<u32> {
+    let mut first = 0u32;
+    let mut second = 0u32;
+
+    for i in 0..18 {
+        let a = dimension - 11 + i % 3;
+        let b = i / 3;
+
+        if module(modules, dimension, a, b) {
+            first |= 1 << i;
+        }
+        if module(modules, dimension, b, a) {
+            second |= 1 << i;
+        }
+    }
+
+    let (errors, version) = (7..=40)
+        .map(|version| {
+            let code = encode_version(version);
+            ((code ^ first).count_ones().min((code ^ second).count_ones()), version)
+        })
+        .min()?;
+
+    if errors > 3 {
+        None
+    } else {
+        Some(version)
+    }
+}
+
+fn alignment_pattern_positions(version: u32) -> Vec<u32> {
+    if version == 1 {
+        return Vec::new();
+    }
+
+    let dimension = 17 + 4 * version;
+    let count = version / 7 + 2;
+    let step = if version == 32 {
+        26
+    } else {
+        (version * 4 + count * 2 + 1) / (count * 2 - 2) * 2
+    };
+
+    let mut positions: Vec<u32> = (0..count - 1).map(|i| dimension - 7 - i * step).collect();
+    positions.push(6);
+    positions.reverse();
+    positions
+}
+
+/// Marks the modules that don't carry data: finders, timing, alignment, format and version.
+fn function_modules(version: u32) -> Vec<bool> {
+    let dimension = 17 + 4 * version;
+    let mut function = vec![false; (dimension * dimension) as usize];
+    let mut mark = |x: u32, y: u32| function[(y * dimension + x) as usize] = true;
+
+    for i in 0..dimension {
+        mark(6, i);
+        mark(i, 6);
+    }
+
+    for &(cx, cy) in &[(3, 3), (dimension - 4, 3), (3, dimension - 4)] {
+        for dy in -4i32..=4 {
+            for dx in -4i32..=4 {
+                let x = cx as i32 + dx;
+                let y = cy as i32 + dy;
+                if x >= 0 && y >= 0 && (x as u32) < dimension && (y as u32) < dimension {
+                    mark(x as u32, y as u32);
+                }
+            }
+        }
+    }
+
+    let positions = alignment_pattern_positions(version);
+    let last = positions.len().saturating_sub(1);
+    for (i, &cy) in positions.iter().enumerate() {
+        for (j, &cx) in positions.iter().enumerate() {
+            // These three overlap the finder patterns.
+            if (i == 0 && (j == 0 || j == last)) || (i == last && j == 0) {
+                continue;
+            }
+
+            for dy in 0..5 {
+                for dx in 0..5 {
+                    mark(cx + dx - 2, cy + dy - 2);
+                }
+            }
+        }
+    }
+
+    for i in 0..9 {
+        mark(8, i);
+        mark(i, 8);
+    }
+    for i in 0..8 {
+        mark(dimension - 1 - i, 8);
+        mark(8, dimension - 1 - i);
+    }
+
+    if version >= 7 {
+        for i in 0..18 {
+            let a = dimension - 11 + i % 3;
+            let b = i / 3;
+            mark(a, b);
+            mark(b, a);
+        }
+    }
+
+    function
+}
+
+fn is_masked(mask: u32, x: u32, y: u32) -> bool {
+    match mask {
+        0 => (x + y).is_multiple_of(2),
+        1 => y.is_multiple_of(2),
+        2 => x.is_multiple_of(3),
+        3 => (x + y).is_multiple_of(3),
+        4 => (x / 3 + y / 2).is_multiple_of(2),
+        5 => x * y % 2 + x * y % 3 == 0,
+        6 => (x * y % 2 + x * y % 3).is_multiple_of(2),
+        _ => ((x + y) % 2 + x * y % 3).is_multiple_of(2),
+    }
+}
+
+fn raw_codeword_count(version: u32) -> usize {
+    let version = version as usize;
+    let mut result = (16 * version + 128) * version + 64;
+
+    if version >= 2 {
+        let count = version / 7 + 2;
+        result -= (25 * count - 10) * count - 55;
+        if version >= 7 {
+            result -= 36;
+        }
+    }
+
+    result / 8
+}
+
+fn decode_modules(modules: &[bool], version: u32) -> Option<String> {
+    let dimension = 17 + 4 * version;
+    let (level, mask) = read_format(modules, dimension)?;
+    let function = function_modules(version);
+
+    // Read the codewords in the zigzag order they were placed in.
+    let raw_count = raw_codeword_count(version);
+    let mut raw = vec![0u8; raw_count];
+    let mut bit = 0;
+    let mut right = dimension as i32 - 1;
+
+    while right >= 1 {
+        if right == 6 {
+            right = 5;
+        }
+
+        for vertical in 0..dimension {
+            for j in 0..2 {
+                let x = (right - j) as u32;
+                let upward = (right + 1) & 2 == 0;
+                let y = if upward { dimension - 1 - vertical } else { vertical };
+
+                if !function[(y * dimension + x) as usize] && bit < raw_count * 8 {
+                    if module(modules, dimension, x, y) != is_masked(mask, x, y) {
+                        raw[bit >> 3] |= 1 << (7 - (bit & 7));
+                    }
+                    bit += 1;
+                }
+            }
+        }
+
+        right -= 2;
+    }
+
+    // Undo the interleaving. Short blocks are one data codeword shorter than long blocks, which
+    // leaves a gap at that index when reading across blocks.
+    let block_count = NUM_ERROR_CORRECTION_BLOCKS[level][version as usize] as usize;
+    let ecc_length = ECC_CODEWORDS_PER_BLOCK[level][version as usize] as usize;
+    let short_block_count = block_count - raw_count % block_count;
+    let short_block_length = raw_count / block_count;
+    let short_data_length = short_block_length - ecc_length;
+
+    let mut blocks = vec![Vec::with_capacity(short_block_length + 1); block_count];
+    let mut codewords = raw.into_iter();
+    for i in 0..=short_block_length {
+        for (j, block) in blocks.iter_mut().enumerate() {
+            if i == short_data_length && j < short_block_count {
+                continue;
+            }
+
+            block.push(codewords.next()?);
+        }
+    }
+
+    let galois = Galois::new();
+    let mut data = Vec::new();
+    for mut block in blocks {
+        if !galois.correct_errors(&mut block, ecc_length) {
+            return None;
+        }
+
+        let data_length = block.len() - ecc_length;
+        data.extend_from_slice(&block[..data_length]);
+    }
+
+    decode_segments(&data, version)
+}
+
+struct BitReader<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl BitReader<'_> {
+    fn remaining(&self) -> usize {
+        self.data.len() * 8 - self.position
+    }
+
+    fn read(&mut self, count: usize) -> Option<u32> {
+        if count > self.remaining() {
+            return None;
+        }
+
+        let mut value = 0;
+        for _ in 0..count {
+            let bit = (self.data[self.position >> 3] >> (7 - (self.position & 7))) & 1;
+            value = value << 1 | bit as u32;
+            self.position += 1;
+        }
+
+        Some(value)
+    }
+}
+
+fn decode_segments(data: &[u8], version: u32) -> Option<String> {
+    let size_class = match version {
+        1..=9 => 0,
+        10..=26 => 1,
+        _ => 2,
+    };
+
+    let mut reader = BitReader { data, position: 0 };
+    let mut bytes = Vec::new();
+
+    while reader.remaining() >= 4 {
+        match reader.read(4)? {
+            0 => break,
+            // Numeric
+            1 => {
+                let mut count = reader.read([10, 12, 14][size_class])?;
+                while count > 0 {
+                    let (bits, digits, max) = match count {
+                        1 => (4, 1, 9),
+                        2 => (7, 2, 99),
+                        _ => (10, 3, 999),
+                    };
+
+                    let value = reader.read(bits)?;
+                    if value > max {
+                        return None;
+                    }
+
+                    bytes.extend(format!("{:0width$}", value, width = digits as usize).bytes());
+                    count -= digits;
+                }
+            }
+            // Alphanumeric
+            2 => {
+                let mut count = reader.read([9, 11, 13][size_class])?;
+                while count >= 2 {
+                    let value = reader.read(11)? as usize;
+                    if value >= 45 * 45 {
+                        return None;
+                    }
+
+                    bytes.push(ALPHANUMERIC_CHARSET[value / 45]);
+                    bytes.push(ALPHANUMERIC_CHARSET[value % 45]);
+                    count -= 2;
+                }
+
+                if count == 1 {
+                    bytes.push(*ALPHANUMERIC_CHARSET.get(reader.read(6)? as usize)?);
+                }
+            }
+            // Structured append
+            3 => {
+                reader.read(16)?;
+            }
+            // Byte
+            4 => {
+                let count = reader.read([8, 16, 16][size_class])?;
+                for _ in 0..count {
+                    bytes.push(reader.read(8)? as u8);
+                }
+            }
+            // FNC1 in first position
+            5 => {}
+            // ECI, we assume UTF-8 (falling back to Latin-1) regardless of the designator.
+            7 => {
+                let first = reader.read(8)?;
+                if first & 0x80 != 0 {
+                    reader.read(if first & 0x40 == 0 { 8 } else { 16 })?;
+                }
+            }
+            // Kanji, Shift JIS isn't mapped so every character is replaced.
+            8 => {
+                let count = reader.read([8, 10, 12][size_class])?;
+                for _ in 0..count {
+                    reader.read(13)?;
+                    bytes.extend("\u{FFFD}".bytes());
+                }
+            }
+            // FNC1 in second position
+            9 => {
+                reader.read(8)?;
+            }
+            _ => return None,
+        }
+    }
+
+    Some(match String::from_utf8(bytes) {
+        Ok(text) => text,
+        Err(e) => e.into_bytes().iter().map(|&b| b as char).collect(),
+    })
+}
+
+/// Arithmetic in GF(2^8) with the QR code polynomial x^8 + x^4 + x^3 + x^2 + 1.
+struct Galois {
+    exp: [u8; 512],
+    log: [u8; 256],
+}
+
+impl Galois {
+    fn new() -> Self {
+        let mut exp = [0u8; 512];
+        let mut log = [0u8; 256];
+        let mut x = 1u32;
+
+        for (i, value) in exp.iter_mut().enumerate().take(255) {
+            *value = x as u8;
+            log[x as usize] = i as u8;
+            x <<= 1;
+            if x & 0x100 != 0 {
+                x ^= 0x11D;
+            }
+        }
+
+        // Repeat the table so sums of two logarithms don't need a modulo.
+        exp.copy_within(0..255, 255);
+
+        Self { exp, log }
+    }
+
+    fn mul(&self, a: u8, b: u8) -> u8 {
+        if a == 0 || b == 0 {
+            0
+        } else {
+            self.exp[self.log[a as usize] as usize + self.log[b as usize] as usize]
+        }
+    }
+
+    fn div(&self, a: u8, b: u8) -> u8 {
+        if a == 0 {
+            0
+        } else {
+            self.exp[self.log[a as usize] as usize + 255 - self.log[b as usize] as usize]
+        }
+    }
+
+    /// Evaluates a polynomial whose coefficients are in ascending order of degree.
+    fn evaluate(&self, polynomial: &[u8], x: u8) -> u8 {
+        polynomial.iter().rev().fold(0, |acc, &coefficient| self.mul(acc, x) ^ coefficient)
+    }
+
+    fn syndromes(&self, block: &[u8], ecc_length: usize) -> Vec<u8> {
+        (0..ecc_length)
+            .map(|i| block.iter().fold(0, |acc, &b| self.mul(acc, self.exp[i]) ^ b))
+            .collect()
+    }
+
+    /// Reed-Solomon error correction in place (Berlekamp-Massey, Chien search and Forney).
+    /// The first codeword of `block` is the highest degree coefficient.
+    fn correct_errors(&self, block: &mut [u8], ecc_length: usize) -> bool {
+        let syndromes = self.syndromes(block, ecc_length);
+        if syndromes.iter().all(|&s| s == 0) {
+            return true;
+        }
+
+        let mut locator = vec![1u8];
+        let mut previous = vec![1u8];
+        let mut errors = 0;
+        let mut shift = 1;
+        let mut previous_discrepancy = 1u8;
+
+        for k in 0..ecc_length {
+            let mut discrepancy = syndromes[k];
+            for i in 1..=errors.min(locator.len() - 1) {
+                discrepancy ^= self.mul(locator[i], syndromes[k - i]);
+            }
+
+            if discrepancy == 0 {
+                shift += 1;
+                continue;
+            }
+
+            let coefficient = self.div(discrepancy, previous_discrepancy);
+            let mut next = locator.clone();
+            if next.len() < previous.len() + shift {
+                next.resize(previous.len() + shift, 0);
+            }
+            for (i, &p) in previous.iter().enumerate() {
+                next[i + shift] ^= self.mul(coefficient, p);
+            }
+
+            if 2 * errors <= k {
+                previous = locator;
+                errors = k + 1 - errors;
+                previous_discrepancy = discrepancy;
+                shift = 1;
+            } else {
+                shift += 1;
+            }
+
+            locator = next;
+        }
+
+        while locator.len() > 1 && *locator.last().unwrap() == 0 {
+            locator.pop();
+        }
+
+        if 2 * errors > ecc_length || locator.len() - 1 != errors {
+            return false;
+        }
+
+        let length = block.len();
+        let positions: Vec<usize> = (0..length)
+            .filter(|&p| self.evaluate(&locator, self.exp[(255 - p % 255) % 255]) == 0)
+            .collect();
+
+        if positions.len() != errors {
+            return false;
+        }
+
+        let evaluator: Vec<u8> = (0..ecc_length)
+            .map(|i| {
+                (0..=i.min(locator.len() - 1)).fold(0, |acc, j| acc ^ self.mul(syndromes[i - j], locator[j]))
+            })
+            .collect();
+
+        let derivative: Vec<u8> = (1..locator.len())
+            .map(|i| if i % 2 == 1 { locator[i] } else { 0 })
+            .collect();
+
+        for p in positions {
+            let x = self.exp[p % 255];
+            let x_inverse = self.exp[(255 - p % 255) % 255];
+
+            let denominator = self.evaluate(&derivative, x_inverse);
+            if denominator == 0 {
+                return false;
+            }
+
+            let magnitude = self.div(self.mul(x, self.evaluate(&evaluator, x_inverse)), denominator);
+            block[length - 1 - p] ^= magnitude;
+        }
+
+        self.syndromes(block, ecc_length).iter().all(|&s| s == 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LEVEL_L: usize = 0;
+    const LEVEL_M: usize = 1;
+    const LEVEL_Q: usize = 2;
+
+    /// The error correction codewords of `data`, the remainder of dividing it by the generator
+    /// polynomial whose roots are the powers of 2 `correct_errors` computes syndromes with.
+    fn reed_solomon(galois: &Galois, data: &[u8], ecc_length: usize) -> Vec<u8> {
+        let mut generator = vec![1u8];
+        for i in 0..ecc_length {
+            let mut next = vec![0u8; generator.len() + 1];
+            for (j, &coefficient) in generator.iter().enumerate() {
+                next[j] ^= coefficient;
+                next[j + 1] ^= galois.mul(coefficient, galois.exp[i]);
+            }
+            generator = next;
+        }
+
+        let mut remainder = vec![0u8; ecc_length];
+        for &byte in data {
+            let factor = byte ^ remainder.remove(0);
+            remainder.push(0);
+            for (r, &g) in remainder.iter_mut().zip(&generator[1..]) {
+                *r ^= galois.mul(g, factor);
+            }
+        }
+
+        remainder
+    }
+
+    fn push_bits(bits: &mut Vec<bool>, value: u32, count: usize) {
+        for i in (0..count).rev() {
+            bits.push(value >> i & 1 == 1);
+        }
+    }
+
+    /// `text` as one byte mode segment, split into blocks with their error correction and
+    /// interleaved, i.e. the codewords in placement order.
+    fn codewords(text: &str, version: u32, level: usize) -> Vec<u8> {
+        let raw_count = raw_codeword_count(version);
+        let block_count = NUM_ERROR_CORRECTION_BLOCKS[level][version as usize] as usize;
+        let ecc_length = ECC_CODEWORDS_PER_BLOCK[level][version as usize] as usize;
+        let capacity = raw_count - block_count * ecc_length;
+
+        let mut bits = Vec::new();
+        push_bits(&mut bits, 4, 4);
+        push_bits(&mut bits, text.len() as u32, if version < 10 { 8 } else { 16 });
+        for byte in text.bytes() {
+            push_bits(&mut bits, byte as u32, 8);
+        }
+        assert!(bits.len() <= capacity * 8, "\"{}\" doesn't fit in version {}", text, version);
+
+        let terminator = (capacity * 8 - bits.len()).min(4);
+        push_bits(&mut bits, 0, terminator);
+        while bits.len() % 8 != 0 {
+            bits.push(false);
+        }
+
+        let mut data: Vec<u8> = bits.chunks(8).map(|it| it.iter().fold(0, |acc, &bit| acc << 1 | bit as u8)).collect();
+        for pad in [0xEC, 0x11].iter().cycle() {
+            if data.len() == capacity {
+                break;
+            }
+            data.push(*pad);
+        }
+
+        let galois = Galois::new();
+        let short_block_count = block_count - raw_count % block_count;
+        let short_data_length = raw_count / block_count - ecc_length;
+        let mut blocks = Vec::new();
+        let mut rest = data.as_slice();
+        for i in 0..block_count {
+            let length = if i < short_block_count { short_data_length } else { short_data_length + 1 };
+            let (block, remaining) = rest.split_at(length);
+            blocks.push((block.to_vec(), reed_solomon(&galois, block, ecc_length)));
+            rest = remaining;
+        }
+
+        let mut result = Vec::new();
+        for i in 0..=short_data_length {
+            result.extend(blocks.iter().filter_map(|(data, _)| data.get(i)));
+        }
+        for i in 0..ecc_length {
+            result.extend(blocks.iter().map(|(_, ecc)| ecc[i]));
+        }
+
+        result
+    }
+
+    /// The modules of a QR code, `true` being dark.
+    fn encode(text: &str, version: u32, level: usize, mask: u32) -> Vec<bool> {
+        let dimension = 17 + 4 * version;
+        let mut modules = vec![false; (dimension * dimension) as usize];
+        let mut set = |x: u32, y: u32, dark: bool| modules[(y * dimension + x) as usize] = dark;
+
+        for &(cx, cy) in &[(3, 3), (dimension - 4, 3), (3, dimension - 4)] {
+            for dy in -4i32..=4 {
+                for dx in -4i32..=4 {
+                    let x = cx as i32 + dx;
+                    let y = cy as i32 + dy;
+                    if x >= 0 && y >= 0 && (x as u32) < dimension && (y as u32) < dimension {
+                        let ring = dx.abs().max(dy.abs());
+                        set(x as u32, y as u32, ring != 2 && ring != 4);
+                    }
+                }
+            }
+        }
+
+        for i in 8..dimension - 8 {
+            set(6, i, i % 2 == 0);
+            set(i, 6, i % 2 == 0);
+        }
+
+        let positions = alignment_pattern_positions(version);
+        let last = positions.len().saturating_sub(1);
+        for (i, &cy) in positions.iter().enumerate() {
+            for (j, &cx) in positions.iter().enumerate() {
+                if (i == 0 && (j == 0 || j == last)) || (i == last && j == 0) {
+                    continue;
+                }
+
+                for dy in -2i32..=2 {
+                    for dx in -2i32..=2 {
+                        set((cx as i32 + dx) as u32, (cy as i32 + dy) as u32, dx.abs().max(dy.abs()) != 1);
+                    }
+                }
+            }
+        }
+
+        set(8, dimension - 8, true);
+
+        let level_bits = [1, 0, 3, 2][level];
+        let format = encode_format(level_bits << 3 | mask);
+        for i in 0..15 {
+            let dark = format >> i & 1 == 1;
+            let (x, y) = match i {
+                0..=5 => (8, i),
+                6 => (8, 7),
+                7 => (8, 8),
+                8 => (7, 8),
+                _ => (14 - i, 8),
+            };
+            set(x, y, dark);
+
+            let (x, y) = if i < 8 { (dimension - 1 - i, 8) } else { (8, dimension - 15 + i) };
+            set(x, y, dark);
+        }
+
+        if version >= 7 {
+            let code = encode_version(version);
+            for i in 0..18 {
+                let dark = code >> i & 1 == 1;
+                let a = dimension - 11 + i % 3;
+                let b = i / 3;
+                set(a, b, dark);
+                set(b, a, dark);
+            }
+        }
+
+        let codewords = codewords(text, version, level);
+        let function = function_modules(version);
+        let mut bit = 0;
+        let mut right = dimension as i32 - 1;
+        while right >= 1 {
+            if right == 6 {
+                right = 5;
+            }
+
+            for vertical in 0..dimension {
+                for j in 0..2 {
+                    let x = (right - j) as u32;
+                    let upward = (right + 1) & 2 == 0;
+                    let y = if upward { dimension - 1 - vertical } else { vertical };
+
+                    if !function[(y * dimension + x) as usize] {
+                        let dark = bit < codewords.len() * 8 && codewords[bit >> 3] >> (7 - (bit & 7)) & 1 == 1;
+                        set(x, y, dark != is_masked(mask, x, y));
+                        bit += 1;
+                    }
+                }
+            }
+
+            right -= 2;
+        }
+
+        modules
+    }
+
+    /// Draws the modules `scale` pixels wide inside a 4 module quiet zone.
+    fn render(modules: &[bool], scale: u32) -> BitMatrix {
+        let dimension = (modules.len() as f64).sqrt() as u32;
+        let size = (dimension + 8) * scale;
+        let mut bits = Vec::with_capacity((size * size) as usize);
+        for y in 0..size {
+            for x in 0..size {
+                let column = (x / scale) as i32 - 4;
+                let row = (y / scale) as i32 - 4;
+                bits.push(
+                    column >= 0
+                        && row >= 0
+                        && (column as u32) < dimension
+                        && (row as u32) < dimension
+                        && modules[(row as u32 * dimension + column as u32) as usize],
+                );
+            }
+        }
+
+        BitMatrix { width: size, height: size, bits }
+    }
+
+    /// Turns the matrix a quarter clockwise.
+    fn rotate(matrix: &BitMatrix) -> BitMatrix {
+        let mut bits = Vec::with_capacity(matrix.bits.len());
+        for y in 0..matrix.width {
+            for x in 0..matrix.height {
+                bits.push(matrix.get(y, matrix.height - 1 - x));
+            }
+        }
+
+        BitMatrix { width: matrix.height, height: matrix.width, bits }
+    }
+
+    fn texts(matrix: &BitMatrix) -> Vec<String> {
+        decode(matrix).into_iter().map(|it| it.text).collect()
+    }
+
+    #[test]
+    fn decodes_generated_symbols() {
+        let cases = [
+            ("HELLO WORLD", 1, LEVEL_M, 0),
+            ("https://example.com/a?b=1", 3, LEVEL_Q, 5),
+            ("Version 7 carries version information and six alignment patterns, spread over two blocks.", 7, LEVEL_L, 2),
+            ("Mixed block lengths", 5, LEVEL_Q, 7),
+        ];
+
+        for (text, version, level, mask) in cases {
+            let matrix = render(&encode(text, version, level, mask), 4);
+            assert_eq!(texts(&matrix), vec![text.to_string()], "version {}", version);
+        }
+    }
+
+    #[test]
+    fn reports_the_corners() {
+        let matrix = render(&encode("CORNERS", 1, LEVEL_M, 3), 4);
+        let barcodes = decode(&matrix);
+        let corners: Vec<(i32, i32)> = barcodes[0].corners.iter().map(|it| (it.x, it.y)).collect();
+
+        // 21 modules of 4 pixels, after a quiet zone of 16.
+        assert_eq!(corners, vec![(16, 16), (100, 16), (100, 100), (16, 100)]);
+    }
+
+    #[test]
+    fn decodes_a_rotated_symbol() {
+        let matrix = render(&encode("ROTATED", 2, LEVEL_M, 1), 3);
+        let rotated = rotate(&matrix);
+        assert_eq!(texts(&rotated), vec!["ROTATED".to_string()]);
+        assert_eq!(texts(&rotate(&rotate(&rotated))), vec!["ROTATED".to_string()]);
+    }
+
+    #[test]
+    fn corrects_damaged_modules() {
+        let version = 2;
+        let dimension = 17 + 4 * version;
+        let mut modules = encode("DAMAGED BUT READABLE", version, LEVEL_M, 4);
+
+        // The bottom right 2x4 modules hold the first codeword, flip it entirely.
+        for y in dimension - 4..dimension {
+            for x in dimension - 2..dimension {
+                let index = (y * dimension + x) as usize;
+                modules[index] = !modules[index];
+            }
+        }
+
+        assert_eq!(texts(&render(&modules, 4)), vec!["DAMAGED BUT READABLE".to_string()]);
+    }
+
+    #[test]
+    fn corrects_up_to_half_the_ecc_length() {
+        let galois = Galois::new();
+        let data: Vec<u8> = (0..16).map(|i| i * 13 + 7).collect();
+        let mut block = data.clone();
+        block.extend(reed_solomon(&galois, &data, 10));
+        let original = block.clone();
+
+        let mut damaged = original.clone();
+        for (i, position) in [0, 3, 11, 17, 25].into_iter().enumerate() {
+            damaged[position] ^= 0x5a + i as u8;
+        }
+        assert!(galois.correct_errors(&mut damaged, 10));
+        assert_eq!(damaged, original);
+
+        let mut intact = original.clone();
+        assert!(galois.correct_errors(&mut intact, 10));
+        assert_eq!(intact, original);
+
+        let mut beyond = original.clone();
+        for position in [1, 4, 8, 12, 19, 23] {
+            beyond[position] ^= 0xff;
+        }
+        assert!(!galois.correct_errors(&mut beyond, 10));
+    }
+
+    #[test]
+    fn ignores_symbols_with_too_many_errors() {
+        let version = 1;
+        let dimension = 17 + 4 * version;
+        let mut modules = encode("TOO DAMAGED", version, LEVEL_L, 0);
+
+        // Version 1-L corrects 3 codewords, the rightmost 8 columns below row 9 hold 12.
+        for y in 9..dimension {
+            for x in dimension - 8..dimension {
+                let index = (y * dimension + x) as usize;
+                modules[index] = !modules[index];
+            }
+        }
+
+        assert!(texts(&render(&modules, 4)).is_empty());
+    }
+}
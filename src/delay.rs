@@ -0,0 +1,89 @@
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::Duration;
+use napi_derive::napi;
+use crate::mouse_path::Rng;
+
+/// Milliseconds to wait after every keyboard and mouse action.
+static INPUT_DELAY: AtomicU32 = AtomicU32::new(0);
+
+#[napi(object)]
+#[derive(Debug, Clone, Default)]
+pub struct TypingOptions {
+    /// Milliseconds between two keystrokes (or chunks), the input delay by default.
+    pub delay: Option<u32>,
+    /// Milliseconds each key is held down, 0 by default. Keys held down are typed one at a
+    /// time, whatever `chunkSize` says.
+    pub press_duration: Option<u32>,
+    /// Up to this many milliseconds are randomly added to or taken from every delay and press
+    /// duration, 0 by default.
+    pub jitter: Option<u32>,
+    /// How many characters are sent at once. Defaults to 1 when typing with a delay, press
+    /// duration or jitter, and to the whole text otherwise.
+    pub chunk_size: Option<u32>,
+}
+
+/// A chunk of text typed in one go, how long its keys are held down and the pause before the
+/// next chunk.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TypingStep {
+    pub text: String,
+    pub hold: Duration,
+    pub pause: Duration,
+}
+
+/// Sets how many milliseconds every keyboard and mouse action waits after its input, like
+/// AutoHotkey's `SetKeyDelay` and `SetMouseDelay`. It is also the default time between the
+/// keystrokes of `Keyboard.typing`. 0 by default.
+#[napi]
+pub fn set_input_delay(delay: u32) {
+    INPUT_DELAY.store(delay, Ordering::Relaxed);
+}
+
+#[napi]
+pub fn get_input_delay() -> u32 {
+    INPUT_DELAY.load(Ordering::Relaxed)
+}
+
+pub async fn wait_input_delay() {
+    let delay = get_input_delay();
+    if delay > 0 {
+        tokio::time::sleep(Duration::from_millis(delay as u64)).await;
+    }
+}
+
+/// Splits `text` into the chunks `Keyboard.typing` sends with `options`. The last chunk has no
+/// pause, the input delay follows it as after any action.
+pub fn typing_steps(text: &str, options: &TypingOptions) -> Vec<TypingStep> {
+    let delay = options.delay.unwrap_or_else(get_input_delay);
+    let hold = options.press_duration.unwrap_or(0);
+    let jitter = options.jitter.unwrap_or(0) as f64;
+
+    let chunk_size = match options.chunk_size {
+        _ if hold > 0 => 1,
+        Some(size) => (size as usize).max(1),
+        None if delay > 0 || jitter > 0.0 => 1,
+        None => usize::MAX,
+    };
+
+    let mut rng = Rng::new(None);
+    let mut vary = |millis: u32| {
+        let millis = millis as f64 + rng.range(-jitter, jitter);
+        Duration::from_millis(millis.max(0.0).round() as u64)
+    };
+
+    let chars: Vec<char> = text.chars().collect();
+    let mut steps: Vec<TypingStep> = chars
+        .chunks(chunk_size.min(chars.len().max(1)))
+        .map(|chunk| TypingStep {
+            text: chunk.iter().collect(),
+            hold: if hold > 0 { vary(hold) } else { Duration::ZERO },
+            pause: vary(delay),
+        })
+        .collect();
+
+    if let Some(last) = steps.last_mut() {
+        last.pause = Duration::ZERO;
+    }
+
+    steps
+}
@@ -0,0 +1,216 @@
+use napi_derive::napi;
+use crate::geometry::{Point, Rect};
+
+#[napi(object)]
+#[derive(Debug, Clone)]
+pub struct Monitor {
+    /// Physical pixels covered by the monitor on the virtual desktop.
+    pub bounds: Rect,
+    /// The same area as seen by applications that are not DPI aware: same top-left corner,
+    /// size divided by `scaleFactor`.
+    pub logical_bounds: Rect,
+    /// Physical pixels per logical pixel, e.g. 1.5 for 144 DPI.
+    pub scale_factor: f64,
+    pub primary: bool,
+}
+
+/// Every position taken or returned by `Mouse` and `takeScreenshot` is in physical pixels of the
+/// virtual desktop, the box around all monitors. On Windows (0, 0) is the top-left corner of the
+/// primary monitor, so monitors left of or above it have negative coordinates. On X11 it is the
+/// top-left corner of the root window.
+#[napi(object)]
+#[derive(Debug, Clone)]
+pub struct VirtualDesktop {
+    /// Physical pixels covered by all monitors together.
+    pub bounds: Rect,
+    pub monitors: Vec<Monitor>,
+}
+
+impl Monitor {
+    pub fn new(bounds: Rect, scale_factor: f64, primary: bool) -> Self {
+        let width = ((bounds.right - bounds.left) as f64 / scale_factor).round() as i32;
+        let height = ((bounds.bottom - bounds.top) as f64 / scale_factor).round() as i32;
+
+        Self {
+            logical_bounds: Rect::new(bounds.left, bounds.top, bounds.left + width, bounds.top + height),
+            bounds,
+            scale_factor,
+            primary,
+        }
+    }
+}
+
+/// Converts a point in logical pixels (e.g. from a DPI-unaware application) to the physical
+/// pixel it covers on `desktop`.
+#[napi]
+pub fn logical_to_physical(point: Point, desktop: VirtualDesktop) -> Point {
+    match closest_monitor(&desktop.monitors, &point, |monitor| &monitor.logical_bounds) {
+        Some(monitor) => Point::new(
+            monitor.bounds.left + ((point.x - monitor.logical_bounds.left) as f64 * monitor.scale_factor).round() as i32,
+            monitor.bounds.top + ((point.y - monitor.logical_bounds.top) as f64 * monitor.scale_factor).round() as i32,
+        ),
+        None => point,
+    }
+}
+
+/// Converts a point in physical pixels, e.g. from `imageSearch` on a screenshot, to logical
+/// pixels on `desktop`.
+#[napi]
+pub fn physical_to_logical(point: Point, desktop: VirtualDesktop) -> Point {
+    match closest_monitor(&desktop.monitors, &point, |monitor| &monitor.bounds) {
+        Some(monitor) => Point::new(
+            monitor.logical_bounds.left + ((point.x - monitor.bounds.left) as f64 / monitor.scale_factor).round() as i32,
+            monitor.logical_bounds.top + ((point.y - monitor.bounds.top) as f64 / monitor.scale_factor).round() as i32,
+        ),
+        None => point,
+    }
+}
+
+/// The monitor containing `point`, or the nearest one when it is outside of all of them.
+fn closest_monitor<'a>(monitors: &'a [Monitor], point: &Point, bounds: impl Fn(&Monitor) -> &Rect) -> Option<&'a Monitor> {
+    monitors.iter().min_by_key(|monitor| {
+        let bounds = bounds(monitor);
+        let dx = (bounds.left - point.x).max(point.x - (bounds.right - 1)).max(0) as i64;
+        let dy = (bounds.top - point.y).max(point.y - (bounds.bottom - 1)).max(0) as i64;
+        dx * dx + dy * dy
+    })
+}
+
+/// Maps a pixel of the virtual desktop to the 0..=65535 range of absolute mouse input, picking
+/// the smallest value that the system maps back onto that very pixel.
+pub fn normalize_absolute(point: &Point, desktop_bounds: &Rect) -> (i32, i32) {
+    let normalize = |value: i32, start: i32, end: i32| {
+        let size = (end - start).max(1) as i64;
+        let offset = (value - start).clamp(0, size as i32 - 1) as i64;
+        ((offset * 65536 + size - 1) / size).min(65535) as i32
+    };
+
+    (
+        normalize(point.x, desktop_bounds.left, desktop_bounds.right),
+        normalize(point.y, desktop_bounds.top, desktop_bounds.bottom),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A 4K monitor at 150% right of a 1080p primary, and a 1440p monitor at 125% left of and
+    /// above it.
+    fn desktop() -> VirtualDesktop {
+        VirtualDesktop {
+            bounds: Rect::new(-2560, -300, 5760, 2160),
+            monitors: vec![
+                Monitor::new(Rect::new(0, 0, 1920, 1080), 1.0, true),
+                Monitor::new(Rect::new(1920, 0, 5760, 2160), 1.5, false),
+                Monitor::new(Rect::new(-2560, -300, 0, 1140), 1.25, false),
+            ],
+        }
+    }
+
+    fn to_physical(x: i32, y: i32) -> (i32, i32) {
+        let point = logical_to_physical(Point::new(x, y), desktop());
+        (point.x, point.y)
+    }
+
+    fn to_logical(x: i32, y: i32) -> (i32, i32) {
+        let point = physical_to_logical(Point::new(x, y), desktop());
+        (point.x, point.y)
+    }
+
+    #[test]
+    fn scales_logical_bounds() {
+        let desktop = desktop();
+        let logical = |i: usize| {
+            let bounds = &desktop.monitors[i].logical_bounds;
+            (bounds.left, bounds.top, bounds.right, bounds.bottom)
+        };
+
+        assert_eq!(logical(0), (0, 0, 1920, 1080));
+        assert_eq!(logical(1), (1920, 0, 4480, 1440));
+        assert_eq!(logical(2), (-2560, -300, -512, 852));
+    }
+
+    #[test]
+    fn converts_on_mixed_dpi_monitors() {
+        assert_eq!(to_physical(100, 200), (100, 200));
+        assert_eq!(to_physical(2000, 100), (2040, 150));
+        assert_eq!(to_physical(4479, 1439), (5759, 2159));
+
+        assert_eq!(to_logical(100, 200), (100, 200));
+        assert_eq!(to_logical(2040, 150), (2000, 100));
+        assert_eq!(to_logical(5759, 2159), (4479, 1439));
+    }
+
+    #[test]
+    fn converts_on_monitors_with_a_negative_origin() {
+        assert_eq!(to_physical(-2560, -300), (-2560, -300));
+        assert_eq!(to_physical(-2000, 0), (-1860, 75));
+        assert_eq!(to_physical(-513, 851), (-1, 1139));
+
+        assert_eq!(to_logical(-1860, 75), (-2000, 0));
+        assert_eq!(to_logical(-1, 1139), (-513, 851));
+    }
+
+    #[test]
+    fn converts_points_outside_of_every_monitor_with_the_nearest() {
+        // Below the primary monitor, and right of the 4K one.
+        assert_eq!(to_physical(100, 1200), (100, 1200));
+        assert_eq!(to_physical(5000, 100), (6540, 150));
+        assert_eq!(to_logical(6540, 150), (5000, 100));
+    }
+
+    #[test]
+    fn round_trips() {
+        for monitor in &desktop().monitors {
+            let bounds = &monitor.logical_bounds;
+            for y in (bounds.top..bounds.bottom).step_by(37) {
+                for x in (bounds.left..bounds.right).step_by(41) {
+                    let (px, py) = to_physical(x, y);
+                    assert_eq!(to_logical(px, py), (x, y));
+                }
+            }
+
+            // Physical pixels between two logical ones come back to the nearest of them.
+            let bounds = &monitor.bounds;
+            let tolerance = (monitor.scale_factor / 2.0).ceil() as i32;
+            for y in (bounds.top..bounds.bottom).step_by(37) {
+                for x in (bounds.left..bounds.right).step_by(41) {
+                    let (lx, ly) = to_logical(x, y);
+                    let (px, py) = to_physical(lx, ly);
+                    assert!((px - x).abs() <= tolerance && (py - y).abs() <= tolerance, "({}, {})", x, y);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn normalizes_to_the_edges_of_the_virtual_desktop() {
+        let bounds = desktop().bounds;
+        let normalize = |x, y| normalize_absolute(&Point::new(x, y), &bounds);
+
+        assert_eq!(normalize(-2560, -300), (0, 0));
+        assert_eq!(normalize(5759, 2159).0, (8319 * 65536 + 8319) / 8320);
+        assert_eq!(normalize(5759, 2159).1, (2459 * 65536 + 2459) / 2460);
+
+        // Outside of the desktop clamps to its edges.
+        assert_eq!(normalize(-5000, -5000), (0, 0));
+        assert_eq!(normalize(9000, 9000), normalize(5759, 2159));
+    }
+
+    #[test]
+    fn normalizes_every_pixel_back_onto_itself() {
+        // The system maps an absolute value n to pixel n * size / 65536, rounded down.
+        for size in [1, 2, 3, 640, 1080, 2460, 8320, 65535, 65536, 100_000] {
+            let bounds = Rect::new(-size / 3, 0, size - size / 3, 1);
+            for offset in (0..size).step_by(((size / 5000) as usize).max(1)).chain([size - 1]) {
+                let (value, _) = normalize_absolute(&Point::new(bounds.left + offset, 0), &bounds);
+
+                assert!((0..=65535).contains(&value));
+                if size <= 65536 {
+                    assert_eq!(value as i64 * size as i64 / 65536, offset as i64, "{} of {}", offset, size);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,244 @@
+use std::convert::Infallible;
+use embedded_graphics::mono_font::ascii::FONT_6X10;
+use embedded_graphics::mono_font::MonoTextStyleBuilder;
+use embedded_graphics::pixelcolor::{Rgb888, RgbColor};
+use embedded_graphics::prelude::{Dimensions, DrawTarget, OriginDimensions, Pixel, Primitive, Size};
+use embedded_graphics::primitives::{Line, PrimitiveStyleBuilder, Rectangle, StrokeAlignment};
+use embedded_graphics::text::{Baseline, Text};
+use embedded_graphics::Drawable;
+use napi_derive::napi;
+use crate::geometry::{Point, Rect};
+use crate::screen::{Color, ImageData};
+
+const DEFAULT_CROSSHAIR_SIZE: u32 = 10;
+
+/// Draws straight into the pixel memory of an `ImageData`, clipping anything out of bounds.
+struct Canvas<'a> {
+    pixels: &'a mut [u8],
+    width: u32,
+    height: u32,
+    pixel_width: u32,
+}
+
+impl<'a> Canvas<'a> {
+    fn new(image_data: &'a mut ImageData) -> Self {
+        Self {
+            width: image_data.width,
+            height: image_data.height,
+            pixel_width: image_data.pixel_width as u32,
+            pixels: image_data.data.as_mut_slice(),
+        }
+    }
+}
+
+impl OriginDimensions for Canvas<'_> {
+    fn size(&self) -> Size {
+        Size::new(self.width, self.height)
+    }
+}
+
+impl DrawTarget for Canvas<'_> {
+    type Color = Rgb888;
+    type Error = Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> std::result::Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        let bounds = self.bounding_box();
+
+        for Pixel(point, color) in pixels {
+            if !bounds.contains(point) {
+                continue;
+            }
+
+            let index = ((point.y as u32 * self.width + point.x as u32) * self.pixel_width) as usize;
+            self.pixels[index] = color.r();
+            self.pixels[index + 1] = color.g();
+            self.pixels[index + 2] = color.b();
+
+            if self.pixel_width == 4 {
+                self.pixels[index + 3] = 255;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn to_rgb(color: &Color) -> Rgb888 {
+    Rgb888::new(color.r, color.g, color.b)
+}
+
+fn to_gfx_point(point: &Point) -> embedded_graphics::prelude::Point {
+    embedded_graphics::prelude::Point::new(point.x, point.y)
+}
+
+#[napi]
+impl ImageData {
+    /// Outlines `rect` (`right`/`bottom` exclusive), or fills it when `fill` is true.
+    /// The outline is drawn inside the rectangle and defaults to 1px.
+    #[napi]
+    pub fn draw_rect(&mut self, rect: Rect, color: Color, thickness: Option<u32>, fill: Option<bool>) {
+        let size = Size::new(
+            (rect.right - rect.left).max(0) as u32,
+            (rect.bottom - rect.top).max(0) as u32,
+        );
+        let mut style = PrimitiveStyleBuilder::new();
+        style = if fill.unwrap_or(false) {
+            style.fill_color(to_rgb(&color))
+        } else {
+            style
+                .stroke_color(to_rgb(&color))
+                .stroke_width(thickness.unwrap_or(1))
+                .stroke_alignment(StrokeAlignment::Inside)
+        };
+
+        let _ = Rectangle::new(embedded_graphics::prelude::Point::new(rect.left, rect.top), size)
+            .into_styled(style.build())
+            .draw(&mut Canvas::new(self));
+    }
+
+    #[napi]
+    pub fn draw_line(&mut self, from: Point, to: Point, color: Color, thickness: Option<u32>) {
+        let style = PrimitiveStyleBuilder::new()
+            .stroke_color(to_rgb(&color))
+            .stroke_width(thickness.unwrap_or(1))
+            .build();
+
+        let _ = Line::new(to_gfx_point(&from), to_gfx_point(&to))
+            .into_styled(style)
+            .draw(&mut Canvas::new(self));
+    }
+
+    /// Marks `center` with a cross whose arms are `size` pixels long (10 by default).
+    #[napi]
+    pub fn draw_crosshair(&mut self, center: Point, color: Color, size: Option<u32>, thickness: Option<u32>) {
+        let size = size.unwrap_or(DEFAULT_CROSSHAIR_SIZE) as i32;
+
+        self.draw_line(
+            Point::new(center.x - size, center.y),
+            Point::new(center.x + size, center.y),
+            color.clone(),
+            thickness,
+        );
+        self.draw_line(
+            Point::new(center.x, center.y - size),
+            Point::new(center.x, center.y + size),
+            color,
+            thickness,
+        );
+    }
+
+    /// Writes `text` with its top-left corner at `position` using a bundled 6x10 bitmap font.
+    /// Characters outside of ASCII are drawn as `?`.
+    #[napi]
+    pub fn draw_text(&mut self, text: String, position: Point, color: Color, background: Option<Color>) {
+        let mut style = MonoTextStyleBuilder::new()
+            .font(&FONT_6X10)
+            .text_color(to_rgb(&color));
+
+        if let Some(background) = background {
+            style = style.background_color(to_rgb(&background));
+        }
+
+        let _ = Text::with_baseline(&text, to_gfx_point(&position), style.build(), Baseline::Top)
+            .draw(&mut Canvas::new(self));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::mem::ManuallyDrop;
+    use super::*;
+
+    const RED: Color = Color { r: 255, g: 0, b: 0 };
+    const BLUE: Color = Color { r: 0, g: 0, b: 255 };
+
+    /// A black image, leaked rather than dropped: dropping an `ImageData` links in the N-API calls
+    /// releasing a JS `Buffer`, which are unresolved outside of Node.
+    fn image(width: u32, height: u32) -> &'static mut ImageData {
+        let data = vec![0; (width * height * 4) as usize];
+        let image = Box::leak(Box::new(ManuallyDrop::new(ImageData { data: data.into(), width, height, pixel_width: 4 })));
+        &mut *image
+    }
+
+    /// The pixels of `image` in `color`, row by row.
+    fn painted(image: &ImageData, color: &Color) -> Vec<(u32, u32)> {
+        let pixels = image.data.as_slice();
+        let mut painted = Vec::new();
+        for y in 0..image.height {
+            for x in 0..image.width {
+                let index = ((y * image.width + x) * 4) as usize;
+                if pixels[index..index + 3] == [color.r, color.g, color.b] {
+                    painted.push((x, y));
+                }
+            }
+        }
+        painted
+    }
+
+    fn area(left: u32, top: u32, right: u32, bottom: u32) -> Vec<(u32, u32)> {
+        (top..bottom).flat_map(|y| (left..right).map(move |x| (x, y))).collect()
+    }
+
+    #[test]
+    fn fills_rectangles_without_their_right_and_bottom_edge() {
+        let image = image(6, 6);
+        image.draw_rect(Rect::new(1, 2, 4, 5), RED, None, Some(true));
+
+        assert_eq!(painted(image, &RED), area(1, 2, 4, 5));
+    }
+
+    #[test]
+    fn outlines_inside_the_rectangle() {
+        let image = image(8, 8);
+        image.draw_rect(Rect::new(1, 1, 7, 7), RED, Some(2), None);
+
+        let inside = area(3, 3, 5, 5);
+        let outline: Vec<(u32, u32)> = area(1, 1, 7, 7).into_iter().filter(|it| !inside.contains(it)).collect();
+        assert_eq!(painted(image, &RED), outline);
+    }
+
+    #[test]
+    fn clips_at_the_edges() {
+        let image = image(4, 4);
+        image.draw_rect(Rect::new(-2, -2, 2, 3), RED, None, Some(true));
+        assert_eq!(painted(image, &RED), area(0, 0, 2, 3));
+
+        image.draw_line(Point::new(-10, 3), Point::new(10, 3), BLUE, None);
+        assert_eq!(painted(image, &BLUE), area(0, 3, 4, 4));
+
+        image.draw_crosshair(Point::new(100, 100), RED, None, None);
+        assert_eq!(painted(image, &RED), area(0, 0, 2, 3));
+    }
+
+    #[test]
+    fn draws_crosshair_arms() {
+        let image = image(9, 9);
+        image.draw_crosshair(Point::new(4, 4), RED, Some(2), None);
+
+        let mut arms: Vec<(u32, u32)> = (2..=6).map(|x| (x, 4)).chain((2..=6).map(|y| (4, y))).collect();
+        arms.sort_by_key(|(x, y)| (*y, *x));
+        arms.dedup();
+        assert_eq!(painted(image, &RED), arms);
+    }
+
+    #[test]
+    fn draws_text_with_a_background() {
+        let image = image(14, 12);
+        image.draw_text("A".to_string(), Point::new(1, 1), RED, Some(BLUE));
+
+        // The background fills the 6x10 cell of the font, wherever the glyph doesn't.
+        let mut cell = painted(image, &RED);
+        cell.extend(painted(image, &BLUE));
+        cell.sort_by_key(|(x, y)| (*y, *x));
+        assert_eq!(cell, area(1, 1, 7, 11));
+        assert!(!painted(image, &RED).is_empty());
+
+        let plain = self::image(14, 12);
+        plain.draw_text("A".to_string(), Point::new(1, 1), RED, None);
+        assert_eq!(painted(plain, &RED), painted(image, &RED));
+        assert!(painted(plain, &BLUE).is_empty());
+    }
+}
@@ -0,0 +1,860 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use lazy_static::lazy_static;
+use napi::bindgen_prelude::*;
+use napi::threadsafe_function::{ErrorStrategy, ThreadsafeFunction, ThreadsafeFunctionCallMode};
+use napi::{JsDeferred, JsObject};
+use napi_derive::napi;
+use crate::accelerator::parse_accelerator_inner;
+use crate::hotkey_sequence::{HotkeySequence, HotkeySequenceMatcher, SequenceInput};
+use crate::key::{key_name, Key};
+use crate::modifiers::Modifiers;
+
+#[cfg(target_os = "linux")]
+use crate::linux::hotkey as platform;
+#[cfg(target_os = "linux")]
+use crate::linux::keyboard::Keyboard;
+#[cfg(target_os = "windows")]
+use crate::win::hotkey as platform;
+#[cfg(target_os = "windows")]
+use crate::win::keyboard::Keyboard;
+
+/// The `code` of the errors hotkey functions reject or throw with.
+#[napi(string_enum)]
+#[derive(Debug, PartialEq)]
+pub enum HotkeyErrorCode {
+    /// Another application already registered or grabbed the key combination.
+    AlreadyRegistered,
+    /// No hotkey or hotkey sequence with the given id is registered.
+    NotRegistered,
+    /// The key or a modifier can't be used for a hotkey, e.g. because the keyboard layout has no
+    /// key for it, or the platform has no such hotkey modifier.
+    Unsupported,
+    /// The key combination begins a hotkey sequence and can't be a hotkey too, or the other way
+    /// around, as the sequence would take every press.
+    Conflict,
+    /// Anything else, e.g. no connection to the X server.
+    Failed,
+}
+
+/// Limits a hotkey to windows in the foreground, like AutoHotkey's `#IfWinActive`. Every given
+/// criterion has to match.
+#[napi(object)]
+#[derive(Debug, Clone, Default)]
+pub struct HotkeyScope {
+    /// A part of the window title, case-sensitive.
+    pub title: Option<String>,
+    /// The window class, e.g. "Notepad" on Windows, or either name of `WM_CLASS` on Linux.
+    pub class_name: Option<String>,
+    /// The name of the process' executable, case-insensitive and with or without ".exe", e.g.
+    /// "notepad.exe" or "code".
+    pub process: Option<String>,
+}
+
+/// When a hotkey callback is called.
+#[napi]
+#[derive(Debug, PartialEq)]
+pub enum HotkeyPhase {
+    /// When the key combination is pressed.
+    Press,
+    /// When the key is released again.
+    Release,
+    /// When the key has been held down for `holdTime` milliseconds.
+    Hold,
+    /// When the hotkey stopped working and was unregistered, e.g. because another application
+    /// took the key combination. Every callback of the hotkey, or of a hotkey sequence using it,
+    /// is called with it, whatever its `trigger`, and `error` tells why.
+    Lost,
+}
+
+/// How long a key has to be held down for `HotkeyPhase.Hold` unless `holdTime` says otherwise.
+const DEFAULT_HOLD_TIME: u32 = 500;
+
+#[napi(object)]
+#[derive(Debug, Clone, Default)]
+pub struct HotkeyOptions {
+    /// Calls the callback only while a matching window is in the foreground. Otherwise the
+    /// keystroke goes through to that window, unless another callback of the hotkey matches.
+    pub scope: Option<HotkeyScope>,
+    /// When to call the callback, `HotkeyPhase.Press` by default.
+    pub trigger: Option<HotkeyPhase>,
+    /// Milliseconds the key has to be held down for `HotkeyPhase.Hold`, 500 by default.
+    pub hold_time: Option<u32>,
+    /// Whether a `HotkeyPhase.Press` callback is called again whenever the held key repeats.
+    /// Defaults to false.
+    pub repeat: Option<bool>,
+}
+
+/// What a hotkey callback is called with.
+#[napi(object)]
+#[derive(Debug, Clone)]
+pub struct HotkeyEvent {
+    /// The id of the hotkey, or of the hotkey sequence.
+    pub id: u32,
+    pub phase: HotkeyPhase,
+    /// Milliseconds since the Unix epoch.
+    pub timestamp: f64,
+    /// How often the key repeated since it was pressed, 0 for the press itself.
+    pub repeat_count: u32,
+    /// Why the hotkey stopped working, for `HotkeyPhase.Lost`.
+    pub error: Option<String>,
+}
+
+impl HotkeyEvent {
+    fn new(id: u32, phase: HotkeyPhase, repeat_count: u32) -> Self {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|it| it.as_secs_f64() * 1000.0)
+            .unwrap_or(0.0);
+
+        Self {
+            id,
+            phase,
+            timestamp,
+            repeat_count,
+            error: None,
+        }
+    }
+
+    fn lost(id: u32, error: &HotkeyError) -> Self {
+        Self {
+            error: Some(error.message.clone()),
+            ..Self::new(id, HotkeyPhase::Lost, 0)
+        }
+    }
+}
+
+/// What happened to the key of a registered key combination, as the platform tells it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HotkeyAction {
+    Press,
+    /// The held key was pressed again by auto-repeat.
+    Repeat,
+    Release,
+}
+
+/// The window in the foreground, as far as hotkey scopes are concerned. Everything is empty when
+/// there is none or it can't be queried.
+#[derive(Debug, Clone, Default)]
+pub struct ForegroundWindow {
+    pub title: String,
+    pub class_names: Vec<String>,
+    pub process: String,
+}
+
+impl HotkeyScope {
+    pub fn matches(&self, window: &ForegroundWindow) -> bool {
+        let process_name = |name: &str| {
+            let name = name.to_lowercase();
+            name.strip_suffix(".exe").map(str::to_string).unwrap_or(name)
+        };
+
+        self.title.as_ref().is_none_or(|title| window.title.contains(title.as_str()))
+            && self.class_name.as_ref().is_none_or(|class_name| window.class_names.contains(class_name))
+            && self.process.as_ref().is_none_or(|process| !window.process.is_empty() && process_name(process) == process_name(&window.process))
+    }
+}
+
+#[napi(object)]
+#[derive(Debug, Clone)]
+pub struct RegisteredHotkey {
+    pub id: u32,
+    pub mods: Vec<Modifiers>,
+    pub key: Key,
+}
+
+#[derive(Debug, Clone)]
+pub struct Hotkey {
+    pub id: u32,
+    pub key: Key,
+    pub mods: Vec<Modifiers>,
+}
+
+impl Hotkey {
+    pub fn new(mods: Vec<Modifiers>, key: Key) -> Self {
+        let id = Hotkey::generate_hash(&mods, key);
+
+        Self {
+            id,
+            key,
+            mods,
+        }
+    }
+
+    /// The id of a key combination, the same however its modifiers are ordered, so that
+    /// registering a combination again adds a callback to it.
+    pub fn generate_hash(mods: &[Modifiers], key: Key) -> u32 {
+        let mods: Vec<Modifiers> = mods.iter().map(|it| it.either_side()).collect();
+        let mut hotkey_str = String::new();
+        if mods.contains(&Modifiers::Shift) {
+            hotkey_str.push_str("Shift+");
+        }
+
+        if mods.contains(&Modifiers::Control) {
+            hotkey_str.push_str("Control+");
+        }
+
+        if mods.contains(&Modifiers::Alt) {
+            hotkey_str.push_str("Alt+");
+        }
+
+        if mods.contains(&Modifiers::Super) || mods.contains(&Modifiers::Meta) {
+            hotkey_str.push_str("Super+");
+        }
+
+        hotkey_str.push_str(key_name(key));
+
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        hotkey_str.hash(&mut hasher);
+        std::hash::Hasher::finish(&hasher) as u32
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct HotkeyError {
+    pub code: HotkeyErrorCode,
+    pub message: String,
+}
+
+impl HotkeyError {
+    pub fn new(code: HotkeyErrorCode, message: String) -> Self {
+        Self { code, message }
+    }
+
+    /// A JavaScript `Error` with this message and `code`.
+    pub fn into_napi(self, env: &Env) -> Error {
+        let mut error = match env.create_error(Error::new(Status::GenericFailure, self.message)) {
+            Ok(error) => error,
+            Err(e) => return e,
+        };
+
+        if let Err(e) = error.set_named_property("code", self.code) {
+            return e;
+        }
+
+        Error::from(error.into_unknown())
+    }
+}
+
+pub type HotkeyResolver<T> = Box<dyn FnOnce(Env) -> Result<T> + Send>;
+pub type HotkeyDeferred<T> = JsDeferred<T, HotkeyResolver<T>>;
+
+/// Resolves or rejects the promise of a hotkey function, from any thread.
+pub fn settle<T: ToNapiValue + Send + 'static>(deferred: HotkeyDeferred<T>, result: std::result::Result<T, HotkeyError>) {
+    deferred.resolve(Box::new(move |env| result.map_err(|e| e.into_napi(&env))));
+}
+
+/// Called with the outcome of registering a hotkey, by the platform or once it reported it.
+pub type HotkeyDone = Box<dyn FnOnce(std::result::Result<(), HotkeyError>) + Send>;
+
+/// Tells everyone waiting for the `registration` of the key combination `id` its outcome,
+/// forgetting the key combination again when it failed. Outcomes of registrations that were
+/// given up on in the meantime are ignored.
+fn finish_registration(id: u32, registration: u64, result: std::result::Result<(), HotkeyError>) {
+    let waiting = {
+        let mut hotkeys = HOTKEYS.lock().unwrap();
+        let entry = match hotkeys.get_mut(&id) {
+            Some(entry) if entry.pending.as_ref().is_some_and(|it| it.registration == registration) => entry,
+            _ => return,
+        };
+
+        let waiting = entry.pending.take().map(|it| it.waiting).unwrap_or_default();
+        if result.is_err() {
+            hotkeys.remove(&id);
+        }
+        waiting
+    };
+
+    for done in waiting {
+        done(result.clone());
+    }
+}
+
+/// Asks the platform for the `registration` of `hotkey`, whose entry is pending. `HOTKEYS` must
+/// not be locked, as the platform may report the outcome right away.
+fn register_entry(hotkey: Hotkey, registration: u64) {
+    let id = hotkey.id;
+    platform::register(hotkey, Box::new(move |result| finish_registration(id, registration, result)));
+}
+
+/// Rejects those waiting for the registrations of the removed `entries`, which were given up on.
+/// `HOTKEYS` must not be locked.
+fn abandon_registrations(entries: Vec<HotkeyEntry>) {
+    for entry in entries {
+        for done in entry.pending.map(|it| it.waiting).unwrap_or_default() {
+            done(Err(HotkeyError::new(
+                HotkeyErrorCode::NotRegistered,
+                format!("{:?} with {:?} was unregistered before its registration finished", entry.hotkey.key, entry.hotkey.mods),
+            )));
+        }
+    }
+}
+
+/// Forgets the hotkey `id` after the platform lost its registration, along with the sequences
+/// it is a step of, and tells their callbacks about `error` with `HotkeyPhase::Lost`, as there is
+/// no promise left to reject.
+pub fn lose_hotkey(id: u32, error: HotkeyError) {
+    if let Some(entry) = HOTKEYS.lock().unwrap().remove(&id) {
+        for callback in &entry.callbacks {
+            callback.function.call(HotkeyEvent::lost(id, &error), ThreadsafeFunctionCallMode::NonBlocking);
+        }
+    }
+
+    let sequences = SEQUENCES.lock().unwrap().matcher.containing(id);
+    for sequence in sequences {
+        if let Some(callback) = SEQUENCES.lock().unwrap().callbacks.get(&sequence) {
+            callback.call(HotkeyEvent::lost(sequence, &error), ThreadsafeFunctionCallMode::NonBlocking);
+        }
+        remove_sequence(sequence);
+    }
+}
+
+type HotkeyFunction = ThreadsafeFunction<HotkeyEvent, ErrorStrategy::Fatal>;
+
+fn hotkey_function(callback: JsFunction) -> Result<HotkeyFunction> {
+    callback.create_threadsafe_function(0, |ctx| {
+        Ok(vec![ctx.value])
+    })
+}
+
+struct HotkeyCallback {
+    scope: Option<HotkeyScope>,
+    /// The modifiers that have to be held on one side, like `LeftControl`.
+    sides: Vec<Modifiers>,
+    trigger: HotkeyPhase,
+    hold_time: Duration,
+    repeat: bool,
+    function: HotkeyFunction,
+}
+
+impl HotkeyCallback {
+    fn new(function: HotkeyFunction, options: HotkeyOptions, sides: Vec<Modifiers>) -> Self {
+        Self {
+            scope: options.scope,
+            sides,
+            trigger: options.trigger.unwrap_or(HotkeyPhase::Press),
+            hold_time: Duration::from_millis(options.hold_time.unwrap_or(DEFAULT_HOLD_TIME) as u64),
+            repeat: options.repeat.unwrap_or(false),
+            function,
+        }
+    }
+}
+
+/// A press the callbacks of a hotkey took, until the key is released.
+struct HeldHotkey {
+    press: u64,
+    /// Which callbacks were in scope when the key was pressed.
+    in_scope: Vec<bool>,
+    repeat_count: u32,
+}
+
+/// A registration of a key combination the platform hasn't confirmed yet.
+struct PendingRegistration {
+    /// Tells the outcome of this registration from that of an earlier one given up on.
+    registration: u64,
+    /// Called with the outcome, by the promises of the hotkeys and sequences using the key
+    /// combination.
+    waiting: Vec<HotkeyDone>,
+}
+
+struct HotkeyEntry {
+    hotkey: Hotkey,
+    callbacks: Vec<HotkeyCallback>,
+    /// How many sequences the key combination is a step of, which keep it registered too.
+    sequences: usize,
+    held: Option<HeldHotkey>,
+    /// Set until the platform registered the key combination.
+    pending: Option<PendingRegistration>,
+}
+
+impl HotkeyEntry {
+    /// A key combination about to be registered, which is `registration`.
+    fn new(hotkey: Hotkey, callbacks: Vec<HotkeyCallback>, sequences: usize, registration: u64) -> Self {
+        Self {
+            hotkey,
+            callbacks,
+            sequences,
+            held: None,
+            pending: Some(PendingRegistration {
+                registration,
+                waiting: Vec::new(),
+            }),
+        }
+    }
+
+    /// Calls `done` once the platform registered the key combination, or right away with
+    /// `HOTKEYS` still locked when it did already.
+    fn when_registered(&mut self, done: HotkeyDone) {
+        match &mut self.pending {
+            Some(pending) => pending.waiting.push(done),
+            None => done(Ok(())),
+        }
+    }
+
+    /// Calls the callbacks in scope of the held key that want to know about `phase`.
+    fn call(&self, held: &HeldHotkey, phase: HotkeyPhase, repeat: bool) {
+        for (callback, in_scope) in self.callbacks.iter().zip(&held.in_scope) {
+            if *in_scope && callback.trigger == phase && (!repeat || callback.repeat) {
+                let event = HotkeyEvent::new(self.hotkey.id, phase, held.repeat_count);
+                callback.function.call(event, ThreadsafeFunctionCallMode::NonBlocking);
+            }
+        }
+    }
+}
+
+#[derive(Default)]
+struct Sequences {
+    matcher: HotkeySequenceMatcher,
+    callbacks: HashMap<u32, HotkeyFunction>,
+}
+
+impl Sequences {
+    fn call(&self, ids: &[u32]) {
+        for id in ids {
+            if let Some(callback) = self.callbacks.get(id) {
+                callback.call(HotkeyEvent::new(*id, HotkeyPhase::Press, 0), ThreadsafeFunctionCallMode::NonBlocking);
+            }
+        }
+    }
+}
+
+/// A sequence waiting for its key combinations to be registered.
+struct SequenceRegistration {
+    deferred: Option<HotkeyDeferred<u32>>,
+    remaining: usize,
+    error: Option<HotkeyError>,
+}
+
+lazy_static! {
+    static ref HOTKEYS: Mutex<HashMap<u32, HotkeyEntry>> = Mutex::new(HashMap::new());
+    static ref SEQUENCES: Mutex<Sequences> = Mutex::new(Sequences::default());
+}
+
+static NEXT_SEQUENCE_ID: AtomicU32 = AtomicU32::new(1);
+static NEXT_PRESS: AtomicU64 = AtomicU64::new(1);
+static NEXT_REGISTRATION: AtomicU64 = AtomicU64::new(1);
+
+/// Calls the callbacks of the hotkey `id` for what happened to its key. A press is taken by the
+/// callbacks whose scope matches the foreground window and whose modifiers are held on their
+/// side, which then see its repeats and release. Returns false when none do, in which case the keystroke should go through to that
+/// window. Steps of hotkey sequences only call the callbacks when they don't continue a sequence,
+/// none can start one as the registration refuses that.
+pub fn trigger_hotkey(id: u32, action: HotkeyAction) -> bool {
+    if action == HotkeyAction::Press && trigger_sequences(id) {
+        return true;
+    }
+
+    let mut hotkeys = HOTKEYS.lock().unwrap();
+    let entry = match hotkeys.get_mut(&id) {
+        Some(entry) => entry,
+        None => return true,
+    };
+
+    match action {
+        HotkeyAction::Press => {}
+        HotkeyAction::Repeat => {
+            if let Some(mut held) = entry.held.take() {
+                held.repeat_count += 1;
+                entry.call(&held, HotkeyPhase::Press, true);
+                entry.held = Some(held);
+            }
+            return true;
+        }
+        HotkeyAction::Release => {
+            if let Some(held) = entry.held.take() {
+                entry.call(&held, HotkeyPhase::Release, false);
+            }
+            return true;
+        }
+    }
+
+    let window = match entry.callbacks.iter().any(|it| it.scope.is_some()) {
+        true => Some(platform::foreground_window()),
+        false => None,
+    };
+
+    let held_sides = match entry.callbacks.iter().any(|it| !it.sides.is_empty()) {
+        true => platform::held_sides(),
+        false => Vec::new(),
+    };
+
+    let in_scope: Vec<bool> = entry
+        .callbacks
+        .iter()
+        .map(|callback| {
+            let in_window = match (&callback.scope, &window) {
+                (Some(scope), Some(window)) => scope.matches(window),
+                _ => true,
+            };
+            in_window && callback.sides.iter().all(|it| held_sides.contains(it))
+        })
+        .collect();
+
+    if !in_scope.contains(&true) {
+        return false;
+    }
+
+    let held = HeldHotkey {
+        press: NEXT_PRESS.fetch_add(1, Ordering::Relaxed),
+        in_scope,
+        repeat_count: 0,
+    };
+    entry.call(&held, HotkeyPhase::Press, false);
+
+    for (index, callback) in entry.callbacks.iter().enumerate() {
+        if held.in_scope[index] && callback.trigger == HotkeyPhase::Hold {
+            wait_for_hold(id, held.press, index, callback.hold_time);
+        }
+    }
+
+    entry.held = Some(held);
+    true
+}
+
+/// Calls the `index`th callback of the hotkey `id` once `hold_time` has passed, unless the key
+/// has been released since `press`.
+fn wait_for_hold(id: u32, press: u64, index: usize, hold_time: Duration) {
+    std::thread::spawn(move || {
+        std::thread::sleep(hold_time);
+
+        let hotkeys = HOTKEYS.lock().unwrap();
+        let entry = match hotkeys.get(&id) {
+            Some(entry) => entry,
+            None => return,
+        };
+
+        if let (Some(held), Some(callback)) = (&entry.held, entry.callbacks.get(index)) {
+            if held.press == press {
+                let event = HotkeyEvent::new(id, HotkeyPhase::Hold, held.repeat_count);
+                callback.function.call(event, ThreadsafeFunctionCallMode::NonBlocking);
+            }
+        }
+    });
+}
+
+/// Feeds the hotkey `id` to the sequences and calls those it completes. Returns whether it was
+/// taken as a step. A sequence that is the beginning of a longer one fires once the longer one
+/// times out, or another key combination interrupts it.
+fn trigger_sequences(id: u32) -> bool {
+    let mut sequences = SEQUENCES.lock().unwrap();
+    if sequences.matcher.is_empty() {
+        return false;
+    }
+
+    let input = sequences.matcher.input(id, Instant::now());
+    let interrupted = sequences.matcher.take_interrupted();
+    sequences.call(&interrupted);
+
+    match input {
+        SequenceInput::Matched(ids) => sequences.call(&ids),
+        SequenceInput::Pending => {
+            if let Some(deadline) = sequences.matcher.deadline() {
+                std::thread::spawn(move || {
+                    std::thread::sleep(deadline.saturating_duration_since(Instant::now()));
+
+                    let mut sequences = SEQUENCES.lock().unwrap();
+                    let ids = sequences.matcher.expire(Instant::now());
+                    sequences.call(&ids);
+                });
+            }
+        }
+        SequenceInput::Unmatched => return false,
+    }
+
+    true
+}
+
+/// Forgets the sequence `id`, unregistering its key combinations unless hotkeys or other
+/// sequences still use them. Returns false for unknown ids.
+fn remove_sequence(id: u32) -> bool {
+    let sequence = {
+        let mut sequences = SEQUENCES.lock().unwrap();
+        sequences.callbacks.remove(&id);
+        sequences.matcher.remove(id)
+    };
+    let sequence = match sequence {
+        Some(sequence) => sequence,
+        None => return false,
+    };
+
+    let mut hotkeys = HOTKEYS.lock().unwrap();
+    let mut removed = Vec::new();
+    for chord in distinct(sequence.chords()) {
+        if let Some(entry) = hotkeys.get_mut(&chord) {
+            entry.sequences = entry.sequences.saturating_sub(1);
+            if entry.sequences == 0 && entry.callbacks.is_empty() {
+                removed.extend(hotkeys.remove(&chord));
+                platform::unregister(chord);
+            }
+        }
+    }
+    drop(hotkeys);
+
+    abandon_registrations(removed);
+    true
+}
+
+fn distinct(chords: &[u32]) -> Vec<u32> {
+    let mut distinct = Vec::new();
+    for chord in chords {
+        if !distinct.contains(chord) {
+            distinct.push(*chord);
+        }
+    }
+    distinct
+}
+
+#[napi]
+impl Keyboard {
+    /// Calls `callback` with a `HotkeyEvent` whenever the key combination is pressed, or
+    /// released or held down as `options` say. The combination is given either as `mods` and
+    /// `key` or as an accelerator string such as "Ctrl+Alt+F5", see `parseAccelerator`. The
+    /// promise resolves with the hotkey's id, or rejects with an error whose `code` is a
+    /// `HotkeyErrorCode` when the combination can't be registered. The callback can be scoped
+    /// to windows with `options`, registering the combination again adds callbacks for other
+    /// windows, phases or sides. A modifier of one side, like `Modifiers.LeftControl` or "LCtrl",
+    /// registers the combination for either side and calls the callback only while the modifier
+    /// is held on that side; otherwise the keystroke goes through as if out of scope. A key
+    /// combination that begins a hotkey sequence is refused with the code `Conflict`. Should the
+    /// hotkey stop working later on, the callback is called once more with `HotkeyPhase.Lost`.
+    #[napi(
+        ts_args_type = "mods: Array<Modifiers> | string, key: Key | ((event: HotkeyEvent) => void), callback?: ((event: HotkeyEvent) => void) | HotkeyOptions, options?: HotkeyOptions",
+        ts_return_type = "Promise<number>"
+    )]
+    pub fn register_hotkey(
+        env: Env,
+        mods: Either<Vec<Modifiers>, String>,
+        key: Either<Key, JsFunction>,
+        callback: Option<Either<JsFunction, HotkeyOptions>>,
+        options: Option<HotkeyOptions>,
+    ) -> Result<JsObject> {
+        let (mods, key, callback, options) = match (mods, key, callback, options) {
+            (Either::A(mods), Either::A(key), Some(Either::A(callback)), options) => (mods, key, callback, options),
+            (Either::B(accelerator), Either::B(callback), options @ (None | Some(Either::B(_))), None) => {
+                let accelerator = parse_accelerator_inner(&accelerator).map_err(|e| Error::new(Status::InvalidArg, e))?;
+                let options = match options {
+                    Some(Either::B(options)) => Some(options),
+                    _ => None,
+                };
+                (accelerator.mods, accelerator.key, callback, options)
+            }
+            _ => return Err(Error::new(Status::InvalidArg, "Expected modifiers, a key and a callback, or an accelerator and a callback".to_string())),
+        };
+        // The platforms register hotkeys for either side of a modifier, the callbacks check the
+        // side when the key is pressed.
+        let sides: Vec<Modifiers> = mods.iter().filter(|it| it.is_sided()).copied().collect();
+        let mut either_side = Vec::new();
+        for modifier in mods {
+            if !either_side.contains(&modifier.either_side()) {
+                either_side.push(modifier.either_side());
+            }
+        }
+        let callback = HotkeyCallback::new(hotkey_function(callback)?, options.unwrap_or_default(), sides);
+
+        let (deferred, promise) = env.create_deferred::<u32, HotkeyResolver<u32>>()?;
+        let hotkey = Hotkey::new(either_side, key);
+        let id = hotkey.id;
+
+        let done: HotkeyDone = Box::new(move |result| settle(deferred, result.map(|_| id)));
+
+        let mut hotkeys = HOTKEYS.lock().unwrap();
+        if SEQUENCES.lock().unwrap().matcher.starts_with(id) {
+            done(Err(HotkeyError::new(
+                HotkeyErrorCode::Conflict,
+                format!("{:?} with {:?} begins a hotkey sequence", key, hotkey.mods),
+            )));
+        } else if let Some(entry) = hotkeys.get_mut(&id) {
+            // Settles only once the platform registered it, when another registration of the
+            // key combination is still pending.
+            entry.callbacks.push(callback);
+            entry.when_registered(done);
+        } else {
+            let registration = NEXT_REGISTRATION.fetch_add(1, Ordering::Relaxed);
+            let mut entry = HotkeyEntry::new(hotkey.clone(), vec![callback], 0, registration);
+            entry.when_registered(done);
+            hotkeys.insert(id, entry);
+            drop(hotkeys);
+            register_entry(hotkey, registration);
+        }
+
+        Ok(promise)
+    }
+
+    /// Unregisters the hotkey `id` with all its callbacks. Throws an error with the code
+    /// `NotRegistered` for unknown ids.
+    #[napi]
+    pub fn unregister_hotkey(env: Env, id: u32) -> Result<()> {
+        let mut hotkeys = HOTKEYS.lock().unwrap();
+        match hotkeys.get_mut(&id) {
+            Some(entry) if !entry.callbacks.is_empty() => {
+                entry.callbacks.clear();
+                entry.held = None;
+                if entry.sequences == 0 {
+                    let removed = hotkeys.remove(&id);
+                    platform::unregister(id);
+                    drop(hotkeys);
+                    abandon_registrations(removed.into_iter().collect());
+                }
+                Ok(())
+            }
+            _ => Err(HotkeyError::new(HotkeyErrorCode::NotRegistered, format!("No hotkey with id {} is registered", id)).into_napi(&env)),
+        }
+    }
+
+    /// Calls `callback` whenever the key combinations `steps`, accelerator strings such as
+    /// "Ctrl+K", are pressed one after another, each within `timeoutMs` of the previous one. A
+    /// key combination that doesn't continue the sequence cancels it, and reaches the foreground
+    /// window as usual unless it starts another sequence or is a hotkey. The promise resolves
+    /// with the sequence's id, or rejects like `registerHotkey`, and with the code `Conflict` when
+    /// the first step is a hotkey. Steps can't ask for a modifier of one side like "LCtrl". Like
+    /// hotkeys, the callback is called with `HotkeyPhase.Lost` when a step stops working.
+    #[napi(ts_args_type = "steps: Array<string>, timeoutMs: number, callback: (event: HotkeyEvent) => void", ts_return_type = "Promise<number>")]
+    pub fn register_hotkey_sequence(env: Env, steps: Vec<String>, timeout_ms: u32, callback: JsFunction) -> Result<JsObject> {
+        let chords = steps
+            .iter()
+            .map(|step| match parse_accelerator_inner(step) {
+                Ok(accelerator) if accelerator.mods.iter().any(|it| it.is_sided()) => {
+                    Err(format!("The step \"{}\" of the hotkey sequence asks for a left or right modifier, which steps don't tell apart", step))
+                }
+                Ok(accelerator) => Ok(Hotkey::new(accelerator.mods, accelerator.key)),
+                Err(e) => Err(e),
+            })
+            .collect::<std::result::Result<Vec<Hotkey>, String>>()
+            .map_err(|e| Error::new(Status::InvalidArg, e))?;
+        let sequence = HotkeySequence::new(chords.iter().map(|it| it.id).collect(), Duration::from_millis(timeout_ms as u64))
+            .map_err(|e| Error::new(Status::InvalidArg, e))?;
+
+        let tsfn = hotkey_function(callback)?;
+
+        let (deferred, promise) = env.create_deferred::<u32, HotkeyResolver<u32>>()?;
+        let id = NEXT_SEQUENCE_ID.fetch_add(1, Ordering::Relaxed);
+
+        let mut hotkeys = HOTKEYS.lock().unwrap();
+        if hotkeys.get(&chords[0].id).is_some_and(|entry| !entry.callbacks.is_empty()) {
+            settle(deferred, Err(HotkeyError::new(
+                HotkeyErrorCode::Conflict,
+                format!("The first step \"{}\" of the hotkey sequence is a hotkey", steps[0]),
+            )));
+            return Ok(promise);
+        }
+
+        // The key combinations to register, and those whose registration is still pending, which
+        // include the former.
+        let mut unregistered = Vec::new();
+        let mut pending = Vec::new();
+        let mut counted = Vec::new();
+        for chord in chords {
+            let chord_id = chord.id;
+            if counted.contains(&chord_id) {
+                continue;
+            }
+            counted.push(chord_id);
+
+            match hotkeys.get_mut(&chord_id) {
+                Some(entry) => entry.sequences += 1,
+                None => {
+                    let registration = NEXT_REGISTRATION.fetch_add(1, Ordering::Relaxed);
+                    hotkeys.insert(chord_id, HotkeyEntry::new(chord.clone(), Vec::new(), 1, registration));
+                    unregistered.push((chord, registration));
+                }
+            }
+            if hotkeys[&chord_id].pending.is_some() {
+                pending.push(chord_id);
+            }
+        }
+
+        let mut sequences = SEQUENCES.lock().unwrap();
+        sequences.matcher.add(id, sequence);
+        sequences.callbacks.insert(id, tsfn);
+        drop(sequences);
+
+        if pending.is_empty() {
+            settle(deferred, Ok(id));
+            return Ok(promise);
+        }
+
+        let registration = Arc::new(Mutex::new(SequenceRegistration {
+            deferred: Some(deferred),
+            remaining: pending.len(),
+            error: None,
+        }));
+
+        for chord in pending {
+            let registration = registration.clone();
+
+            hotkeys.get_mut(&chord).unwrap().when_registered(Box::new(move |result| {
+                let finished = {
+                    let mut registration = registration.lock().unwrap();
+                    if let Err(e) = result {
+                        registration.error.get_or_insert(e);
+                    }
+
+                    registration.remaining -= 1;
+                    match registration.remaining {
+                        0 => registration.deferred.take().map(|deferred| (deferred, registration.error.take())),
+                        _ => None,
+                    }
+                };
+
+                match finished {
+                    Some((deferred, Some(e))) => {
+                        remove_sequence(id);
+                        settle(deferred, Err(e));
+                    }
+                    Some((deferred, None)) => settle(deferred, Ok(id)),
+                    None => {}
+                }
+            }));
+        }
+        drop(hotkeys);
+
+        for (chord, registration) in unregistered {
+            register_entry(chord, registration);
+        }
+
+        Ok(promise)
+    }
+
+    /// Unregisters the hotkey sequence `id`. Throws an error with the code `NotRegistered` for
+    /// unknown ids.
+    #[napi]
+    pub fn unregister_hotkey_sequence(env: Env, id: u32) -> Result<()> {
+        match remove_sequence(id) {
+            true => Ok(()),
+            false => Err(HotkeyError::new(HotkeyErrorCode::NotRegistered, format!("No hotkey sequence with id {} is registered", id)).into_napi(&env)),
+        }
+    }
+
+    /// Unregisters every hotkey and hotkey sequence and stops the thread waiting for them, which
+    /// lets the process exit.
+    #[napi(ts_return_type = "Promise<void>")]
+    pub fn unregister_all_hotkeys(env: Env) -> Result<JsObject> {
+        let (deferred, promise) = env.create_deferred::<(), HotkeyResolver<()>>()?;
+
+        let removed: Vec<HotkeyEntry> = HOTKEYS.lock().unwrap().drain().map(|(_, entry)| entry).collect();
+        *SEQUENCES.lock().unwrap() = Sequences::default();
+        platform::shutdown(deferred);
+        abandon_registrations(removed);
+
+        Ok(promise)
+    }
+
+    /// The hotkeys registered right now.
+    #[napi]
+    pub fn get_hotkeys() -> Vec<RegisteredHotkey> {
+        HOTKEYS
+            .lock()
+            .unwrap()
+            .values()
+            .filter(|entry| !entry.callbacks.is_empty())
+            .map(|entry| RegisteredHotkey {
+                id: entry.hotkey.id,
+                mods: entry.hotkey.mods.clone(),
+                key: entry.hotkey.key,
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,328 @@
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// Key combinations to press one after another, like Ctrl+K then Ctrl+C. The chords are hotkey
+/// ids, see `Hotkey::generate_hash`.
+#[derive(Debug, Clone)]
+pub struct HotkeySequence {
+    chords: Vec<u32>,
+    timeout: Duration,
+}
+
+impl HotkeySequence {
+    /// `timeout` is how long the next chord may take before the sequence is abandoned.
+    pub fn new(chords: Vec<u32>, timeout: Duration) -> Result<Self, String> {
+        if chords.is_empty() {
+            return Err("A hotkey sequence needs at least one key combination".to_string());
+        }
+
+        Ok(Self { chords, timeout })
+    }
+
+    pub fn chords(&self) -> &[u32] {
+        &self.chords
+    }
+}
+
+/// What a chord did to the sequences.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SequenceInput {
+    /// It completed the sequences with these ids.
+    Matched(Vec<u32>),
+    /// It started or continued a sequence, more chords have to follow.
+    Pending,
+    /// It is no part of any sequence at this point. Any progress is forgotten.
+    Unmatched,
+}
+
+/// Follows the chords pressed through the registered sequences. A chord that doesn't continue
+/// the sequence in progress cancels it and may start another one. When a complete sequence is
+/// also the beginning of a longer one, it is held back until the longer one times out, see
+/// `deadline` and `expire`, or another chord interrupts it, see `take_interrupted`.
+#[derive(Debug, Default)]
+pub struct HotkeySequenceMatcher {
+    sequences: HashMap<u32, HotkeySequence>,
+    progress: Vec<u32>,
+    last: Option<Instant>,
+    interrupted: Vec<u32>,
+}
+
+impl HotkeySequenceMatcher {
+    pub fn add(&mut self, id: u32, sequence: HotkeySequence) {
+        self.sequences.insert(id, sequence);
+        self.reset();
+    }
+
+    pub fn remove(&mut self, id: u32) -> Option<HotkeySequence> {
+        self.reset();
+        self.sequences.remove(&id)
+    }
+
+    /// Whether a sequence begins with `chord`.
+    pub fn starts_with(&self, chord: u32) -> bool {
+        self.sequences.values().any(|sequence| sequence.chords[0] == chord)
+    }
+
+    /// The ids of the sequences `chord` is a step of.
+    pub fn containing(&self, chord: u32) -> Vec<u32> {
+        self.sequences
+            .iter()
+            .filter(|(_, sequence)| sequence.chords.contains(&chord))
+            .map(|(id, _)| *id)
+            .collect()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sequences.is_empty()
+    }
+
+    pub fn reset(&mut self) {
+        self.progress.clear();
+        self.last = None;
+    }
+
+    pub fn input(&mut self, chord: u32, now: Instant) -> SequenceInput {
+        let held_back = self.complete();
+        self.interrupted.clear();
+
+        if !self.progress.is_empty() {
+            self.progress.push(chord);
+            if let Some(result) = self.advance(now) {
+                return result;
+            }
+
+            // The sequences held back for the longer one are complete all the same.
+            self.interrupted = held_back;
+        }
+
+        self.progress = vec![chord];
+        self.last = None;
+        match self.advance(now) {
+            Some(result) => result,
+            None => {
+                self.reset();
+                SequenceInput::Unmatched
+            }
+        }
+    }
+
+    /// When the sequences completed by the chords so far fire unless another chord comes, if
+    /// there are any.
+    pub fn deadline(&self) -> Option<Instant> {
+        let last = self.last?;
+        if !self.sequences.values().any(|it| it.chords == self.progress) {
+            return None;
+        }
+
+        self.sequences
+            .values()
+            .filter(|it| it.chords.len() > self.progress.len() && it.chords.starts_with(&self.progress))
+            .map(|it| last + it.timeout)
+            .max()
+    }
+
+    /// The ids of the sequences that were held back for a longer one until the last chord,
+    /// which didn't continue it. They fire before whatever the chord did.
+    pub fn take_interrupted(&mut self) -> Vec<u32> {
+        std::mem::take(&mut self.interrupted)
+    }
+
+    /// Gives up waiting for a longer sequence once the deadline passed, returning the ids of the
+    /// sequences the chords so far complete.
+    pub fn expire(&mut self, now: Instant) -> Vec<u32> {
+        match self.deadline() {
+            Some(deadline) if now >= deadline => {
+                let ids = self.complete();
+                self.reset();
+                ids
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    /// Looks for sequences beginning with the chords so far, each pressed in time. `None` when
+    /// there are none.
+    fn advance(&mut self, now: Instant) -> Option<SequenceInput> {
+        let in_time = |sequence: &HotkeySequence| self.last.is_none_or(|last| now.duration_since(last) <= sequence.timeout);
+        let candidates: Vec<&HotkeySequence> = self
+            .sequences
+            .values()
+            .filter(|it| it.chords.starts_with(&self.progress) && in_time(it))
+            .collect();
+
+        if candidates.is_empty() {
+            return None;
+        }
+
+        let longer = candidates.iter().any(|it| it.chords.len() > self.progress.len());
+        let complete = self.complete();
+
+        if complete.is_empty() || longer {
+            self.last = Some(now);
+            Some(SequenceInput::Pending)
+        } else {
+            self.reset();
+            Some(SequenceInput::Matched(complete))
+        }
+    }
+
+    fn complete(&self) -> Vec<u32> {
+        let mut ids: Vec<u32> = self
+            .sequences
+            .iter()
+            .filter(|(_, it)| it.chords == self.progress)
+            .map(|(id, _)| *id)
+            .collect();
+        ids.sort_unstable();
+        ids
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const K: u32 = 1;
+    const C: u32 = 2;
+    const U: u32 = 3;
+    const X: u32 = 4;
+
+    fn matcher(sequences: &[&[u32]]) -> HotkeySequenceMatcher {
+        let mut matcher = HotkeySequenceMatcher::default();
+        for (i, chords) in sequences.iter().enumerate() {
+            matcher.add(i as u32 + 1, HotkeySequence::new(chords.to_vec(), Duration::from_millis(1000)).unwrap());
+        }
+        matcher
+    }
+
+    fn after(start: Instant, millis: u64) -> Instant {
+        start + Duration::from_millis(millis)
+    }
+
+    #[test]
+    fn matches_a_sequence() {
+        let mut matcher = matcher(&[&[K, C]]);
+        let start = Instant::now();
+
+        assert_eq!(matcher.input(K, start), SequenceInput::Pending);
+        assert_eq!(matcher.input(C, after(start, 100)), SequenceInput::Matched(vec![1]));
+        assert_eq!(matcher.input(C, after(start, 200)), SequenceInput::Unmatched);
+    }
+
+    #[test]
+    fn rejects_an_empty_sequence() {
+        assert!(HotkeySequence::new(vec![], Duration::from_millis(1000)).is_err());
+    }
+
+    #[test]
+    fn times_out_between_chords() {
+        let mut matcher = matcher(&[&[K, C]]);
+        let start = Instant::now();
+
+        assert_eq!(matcher.input(K, start), SequenceInput::Pending);
+        assert_eq!(matcher.input(C, after(start, 1500)), SequenceInput::Unmatched);
+
+        assert_eq!(matcher.input(K, after(start, 2000)), SequenceInput::Pending);
+        assert_eq!(matcher.input(K, after(start, 2900)), SequenceInput::Pending);
+        assert_eq!(matcher.input(C, after(start, 3800)), SequenceInput::Matched(vec![1]));
+    }
+
+    #[test]
+    fn cancels_on_another_chord() {
+        let mut matcher = matcher(&[&[K, C]]);
+        let start = Instant::now();
+
+        assert_eq!(matcher.input(K, start), SequenceInput::Pending);
+        assert_eq!(matcher.input(X, after(start, 100)), SequenceInput::Unmatched);
+        assert_eq!(matcher.input(C, after(start, 200)), SequenceInput::Unmatched);
+    }
+
+    #[test]
+    fn restarts_with_a_chord_that_cancels() {
+        let mut matcher = matcher(&[&[K, K, C]]);
+        let start = Instant::now();
+
+        assert_eq!(matcher.input(K, start), SequenceInput::Pending);
+        assert_eq!(matcher.input(K, after(start, 100)), SequenceInput::Pending);
+        assert_eq!(matcher.input(K, after(start, 200)), SequenceInput::Pending);
+        assert_eq!(matcher.input(K, after(start, 300)), SequenceInput::Pending);
+        assert_eq!(matcher.input(C, after(start, 400)), SequenceInput::Matched(vec![1]));
+    }
+
+    #[test]
+    fn follows_sequences_sharing_a_prefix() {
+        let mut matcher = matcher(&[&[K, C], &[K, U]]);
+        let start = Instant::now();
+
+        assert_eq!(matcher.input(K, start), SequenceInput::Pending);
+        assert_eq!(matcher.input(U, after(start, 100)), SequenceInput::Matched(vec![2]));
+        assert_eq!(matcher.input(K, after(start, 200)), SequenceInput::Pending);
+        assert_eq!(matcher.input(C, after(start, 300)), SequenceInput::Matched(vec![1]));
+    }
+
+    #[test]
+    fn holds_back_a_sequence_prefixing_a_longer_one() {
+        let mut matcher = matcher(&[&[K, C], &[K, C, U]]);
+        let start = Instant::now();
+
+        assert_eq!(matcher.input(K, start), SequenceInput::Pending);
+        assert_eq!(matcher.input(C, after(start, 100)), SequenceInput::Pending);
+        assert_eq!(matcher.deadline(), Some(after(start, 1100)));
+        assert_eq!(matcher.expire(after(start, 500)), Vec::<u32>::new());
+        assert_eq!(matcher.input(U, after(start, 600)), SequenceInput::Matched(vec![2]));
+        assert_eq!(matcher.take_interrupted(), Vec::<u32>::new());
+
+        assert_eq!(matcher.input(K, after(start, 2000)), SequenceInput::Pending);
+        assert_eq!(matcher.deadline(), None);
+        assert_eq!(matcher.input(C, after(start, 2100)), SequenceInput::Pending);
+        assert_eq!(matcher.expire(after(start, 3100)), vec![1]);
+        assert_eq!(matcher.deadline(), None);
+    }
+
+    #[test]
+    fn fires_a_held_back_sequence_when_another_chord_interrupts() {
+        let mut matcher = matcher(&[&[K, C], &[K, C, U], &[X, U]]);
+        let start = Instant::now();
+
+        assert_eq!(matcher.input(K, start), SequenceInput::Pending);
+        assert_eq!(matcher.input(C, after(start, 100)), SequenceInput::Pending);
+        assert_eq!(matcher.input(X, after(start, 200)), SequenceInput::Pending);
+        assert_eq!(matcher.take_interrupted(), vec![1]);
+        assert_eq!(matcher.take_interrupted(), Vec::<u32>::new());
+        assert_eq!(matcher.input(U, after(start, 300)), SequenceInput::Matched(vec![3]));
+
+        assert_eq!(matcher.input(K, after(start, 400)), SequenceInput::Pending);
+        assert_eq!(matcher.input(C, after(start, 500)), SequenceInput::Pending);
+        assert_eq!(matcher.input(C, after(start, 600)), SequenceInput::Unmatched);
+        assert_eq!(matcher.take_interrupted(), vec![1]);
+    }
+
+    #[test]
+    fn matches_every_sequence_with_the_same_chords() {
+        let mut matcher = matcher(&[&[K, C], &[K, C]]);
+        let start = Instant::now();
+
+        assert_eq!(matcher.input(K, start), SequenceInput::Pending);
+        assert_eq!(matcher.input(C, after(start, 100)), SequenceInput::Matched(vec![1, 2]));
+    }
+
+    #[test]
+    fn knows_which_chords_begin_a_sequence() {
+        let matcher = matcher(&[&[K, C], &[C, U]]);
+
+        assert!(matcher.starts_with(K));
+        assert!(matcher.starts_with(C));
+        assert!(!matcher.starts_with(U));
+    }
+
+    #[test]
+    fn finds_the_sequences_containing_a_chord() {
+        let matcher = matcher(&[&[K, C], &[C, U], &[K, K]]);
+
+        let mut ids = matcher.containing(C);
+        ids.sort();
+        assert_eq!(ids, vec![1, 2]);
+        assert_eq!(matcher.containing(U), vec![2]);
+        assert_eq!(matcher.containing(7), Vec::<u32>::new());
+    }
+}
@@ -0,0 +1,328 @@
+use napi_derive::napi;
+
+/// The characters ending an abbreviation unless `endChars` says otherwise, as in AutoHotkey.
+pub const DEFAULT_END_CHARS: &str = "-()[]{}':;\"/\\,.?!\n \t";
+
+#[napi(object)]
+#[derive(Debug, Clone, Default)]
+pub struct HotstringOptions {
+    /// The characters that complete the abbreviation, and one of which has to precede it unless
+    /// it starts the input. Defaults to ``-()[]{}':;"/\,.?!``, space, tab and Enter.
+    pub end_chars: Option<String>,
+    /// Whether the abbreviation has to be typed in the same case. Otherwise the replacement
+    /// follows the case it was typed in: all capitals, a capital first letter, or as given.
+    /// Defaults to false.
+    pub case_sensitive: Option<bool>,
+    /// Whether to replace the abbreviation as soon as its last character is typed, without
+    /// waiting for an end character. Defaults to false.
+    pub immediate: Option<bool>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Hotstring {
+    abbreviation: Vec<char>,
+    replacement: String,
+    end_chars: Vec<char>,
+    case_sensitive: bool,
+    immediate: bool,
+}
+
+impl Hotstring {
+    pub fn new(abbreviation: &str, replacement: &str, options: HotstringOptions) -> Result<Self, String> {
+        if abbreviation.is_empty() {
+            return Err("The abbreviation of a hotstring can't be empty".to_string());
+        }
+
+        Ok(Self {
+            abbreviation: abbreviation.chars().collect(),
+            replacement: replacement.to_string(),
+            end_chars: options.end_chars.as_deref().unwrap_or(DEFAULT_END_CHARS).chars().collect(),
+            case_sensitive: options.case_sensitive.unwrap_or(false),
+            immediate: options.immediate.unwrap_or(false),
+        })
+    }
+
+    /// Checks whether `typed` ends with this hotstring, followed by an end character unless it
+    /// is immediate. `truncated` tells that `typed` lost its beginning, which then doesn't start
+    /// a word.
+    fn matches(&self, typed: &[char], truncated: bool) -> Option<(usize, String)> {
+        let (end, end_char) = match (self.immediate, typed.last()) {
+            (true, _) => (typed.len(), None),
+            (false, Some(c)) if self.end_chars.contains(c) => (typed.len() - 1, Some(*c)),
+            _ => return None,
+        };
+
+        let start = end.checked_sub(self.abbreviation.len())?;
+        let candidate = &typed[start..end];
+
+        let is_same = if self.case_sensitive {
+            candidate == self.abbreviation.as_slice()
+        } else {
+            candidate.iter().zip(&self.abbreviation).all(|(a, b)| a.to_lowercase().eq(b.to_lowercase()))
+        };
+        if !is_same {
+            return None;
+        }
+
+        // Only whole words, "abtw" shouldn't expand "btw".
+        let word_start = match start {
+            0 => !truncated,
+            _ => self.end_chars.contains(&typed[start - 1]),
+        };
+        if !word_start {
+            return None;
+        }
+
+        let mut text = if self.case_sensitive {
+            self.replacement.clone()
+        } else {
+            conform_case(&self.replacement, candidate)
+        };
+        text.extend(end_char);
+
+        Some((end - start + end_char.map_or(0, |_| 1), text))
+    }
+}
+
+/// Types the replacement in capitals when the abbreviation was typed in capitals, or with a
+/// capital first letter when only that one was.
+fn conform_case(replacement: &str, typed: &[char]) -> String {
+    let letters: Vec<&char> = typed.iter().filter(|it| it.is_alphabetic()).collect();
+
+    match letters.as_slice() {
+        [_, _, ..] if letters.iter().all(|it| it.is_uppercase()) => replacement.to_uppercase(),
+        [first, ..] if first.is_uppercase() => {
+            let mut chars = replacement.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect(),
+                None => String::new(),
+            }
+        }
+        _ => replacement.to_string(),
+    }
+}
+
+/// What a key press means to hotstrings.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HotstringInput {
+    Char(char),
+    /// Backspace, which takes back the last character.
+    Backspace,
+    /// A key that moves the caret or otherwise breaks up the typed text, like arrows, Escape or
+    /// shortcuts, after which the text before no longer counts.
+    Reset,
+}
+
+impl HotstringInput {
+    /// The input for a character a key press produced, mapping Backspace and other control
+    /// characters.
+    pub fn from_char(c: char) -> Self {
+        match c {
+            '\r' | '\n' => HotstringInput::Char('\n'),
+            '\t' => HotstringInput::Char('\t'),
+            '\u{8}' => HotstringInput::Backspace,
+            c if c.is_control() => HotstringInput::Reset,
+            c => HotstringInput::Char(c),
+        }
+    }
+}
+
+/// A completed hotstring: the number of characters to erase with Backspace, including the end
+/// character, and the text to type in their place, ending with that end character again.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HotstringMatch {
+    pub id: u32,
+    pub erase: usize,
+    pub text: String,
+}
+
+/// Follows the typed text and reports the hotstrings completed by it. Hotstrings registered
+/// first take precedence.
+#[derive(Debug, Default)]
+pub struct HotstringMatcher {
+    hotstrings: Vec<(u32, Hotstring)>,
+    /// The last characters typed, as many as the longest abbreviation needs.
+    typed: Vec<char>,
+    /// Whether earlier characters were dropped from `typed`.
+    truncated: bool,
+}
+
+impl HotstringMatcher {
+    pub fn add(&mut self, id: u32, hotstring: Hotstring) {
+        self.hotstrings.push((id, hotstring));
+    }
+
+    pub fn remove(&mut self, id: u32) {
+        self.hotstrings.retain(|(it, _)| *it != id);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.hotstrings.is_empty()
+    }
+
+    /// Forgets the typed text, e.g. after typing a replacement.
+    pub fn reset(&mut self) {
+        self.typed.clear();
+        self.truncated = false;
+    }
+
+    pub fn input(&mut self, input: HotstringInput) -> Option<HotstringMatch> {
+        match input {
+            HotstringInput::Char(c) => {
+                self.typed.push(c);
+
+                // The abbreviation with the character before it and an end character.
+                let capacity = self.hotstrings.iter().map(|(_, it)| it.abbreviation.len() + 2).max().unwrap_or(0);
+                if self.typed.len() > capacity {
+                    self.typed.drain(..self.typed.len() - capacity);
+                    self.truncated = true;
+                }
+            }
+            HotstringInput::Backspace => {
+                self.typed.pop();
+                return None;
+            }
+            HotstringInput::Reset => {
+                self.reset();
+                return None;
+            }
+        }
+
+        let found = self.hotstrings.iter().find_map(|(id, hotstring)| {
+            hotstring
+                .matches(&self.typed, self.truncated)
+                .map(|(erase, text)| HotstringMatch { id: *id, erase, text })
+        });
+
+        if found.is_some() {
+            self.reset();
+        }
+
+        found
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matcher(hotstrings: &[(&str, &str, HotstringOptions)]) -> HotstringMatcher {
+        let mut matcher = HotstringMatcher::default();
+        for (i, (abbreviation, replacement, options)) in hotstrings.iter().enumerate() {
+            matcher.add(i as u32 + 1, Hotstring::new(abbreviation, replacement, options.clone()).unwrap());
+        }
+        matcher
+    }
+
+    fn type_text(matcher: &mut HotstringMatcher, text: &str) -> Vec<HotstringMatch> {
+        text.chars().filter_map(|c| matcher.input(HotstringInput::from_char(c))).collect()
+    }
+
+    fn expansion(erase: usize, text: &str) -> HotstringMatch {
+        HotstringMatch { id: 1, erase, text: text.to_string() }
+    }
+
+    #[test]
+    fn expands_after_an_end_character() {
+        let mut matcher = matcher(&[("btw", "by the way", HotstringOptions::default())]);
+
+        assert_eq!(type_text(&mut matcher, "btw"), vec![]);
+        assert_eq!(type_text(&mut matcher, " "), vec![expansion(4, "by the way ")]);
+        assert_eq!(type_text(&mut matcher, "btw."), vec![expansion(4, "by the way.")]);
+        assert_eq!(type_text(&mut matcher, "btw\r"), vec![expansion(4, "by the way\n")]);
+    }
+
+    #[test]
+    fn expands_whole_words_only() {
+        let mut matcher = matcher(&[("btw", "by the way", HotstringOptions::default())]);
+
+        assert_eq!(type_text(&mut matcher, "abtw btwx "), vec![]);
+        assert_eq!(type_text(&mut matcher, "(btw)"), vec![expansion(4, "by the way)")]);
+    }
+
+    #[test]
+    fn expands_immediately() {
+        let options = HotstringOptions { immediate: Some(true), ..Default::default() };
+        let mut matcher = matcher(&[("@@", "someone@example.com", options)]);
+
+        assert_eq!(type_text(&mut matcher, "mail @@"), vec![expansion(2, "someone@example.com")]);
+    }
+
+    #[test]
+    fn follows_the_typed_case() {
+        let mut matcher = matcher(&[("btw", "by the way", HotstringOptions::default())]);
+
+        assert_eq!(type_text(&mut matcher, "Btw "), vec![expansion(4, "By the way ")]);
+        assert_eq!(type_text(&mut matcher, "BTW "), vec![expansion(4, "BY THE WAY ")]);
+        assert_eq!(type_text(&mut matcher, "bTw "), vec![expansion(4, "by the way ")]);
+    }
+
+    #[test]
+    fn matches_the_case_when_case_sensitive() {
+        let options = HotstringOptions { case_sensitive: Some(true), ..Default::default() };
+        let mut matcher = matcher(&[("NYC", "New York City", options)]);
+
+        assert_eq!(type_text(&mut matcher, "nyc Nyc "), vec![]);
+        assert_eq!(type_text(&mut matcher, "NYC "), vec![expansion(4, "New York City ")]);
+    }
+
+    #[test]
+    fn uses_custom_end_characters() {
+        let options = HotstringOptions { end_chars: Some(";".to_string()), ..Default::default() };
+        let mut matcher = matcher(&[("sig", "Best regards", options)]);
+
+        assert_eq!(type_text(&mut matcher, "sig "), vec![]);
+        assert_eq!(type_text(&mut matcher, ";sig;"), vec![expansion(4, "Best regards;")]);
+    }
+
+    #[test]
+    fn follows_corrections_and_resets() {
+        let mut matcher = matcher(&[("btw", "by the way", HotstringOptions::default())]);
+
+        assert_eq!(type_text(&mut matcher, "bty\u{8}w "), vec![expansion(4, "by the way ")]);
+
+        type_text(&mut matcher, "bt");
+        matcher.input(HotstringInput::Reset);
+        assert_eq!(type_text(&mut matcher, "w "), vec![]);
+    }
+
+    #[test]
+    fn prefers_the_first_registered() {
+        let mut matcher = matcher(&[
+            ("btw", "by the way", HotstringOptions::default()),
+            ("btw", "between", HotstringOptions::default()),
+        ]);
+
+        assert_eq!(type_text(&mut matcher, "btw "), vec![expansion(4, "by the way ")]);
+
+        matcher.remove(1);
+        assert_eq!(type_text(&mut matcher, "btw ").first().map(|it| it.text.as_str()), Some("between "));
+    }
+
+    #[test]
+    fn matches_long_abbreviations() {
+        let abbreviation = "x".repeat(150);
+        let mut matcher = matcher(&[(&abbreviation, "many", HotstringOptions::default())]);
+
+        assert_eq!(type_text(&mut matcher, &format!("{} ", abbreviation)), vec![expansion(151, "many ")]);
+    }
+
+    #[test]
+    fn expands_whole_words_only_after_long_text() {
+        let mut matcher = matcher(&[("btw", "by the way", HotstringOptions::default())]);
+
+        assert_eq!(type_text(&mut matcher, &format!("{}btw ", "a".repeat(300))), vec![]);
+
+        // A match at the start of what is left of the text only starts a word when nothing was
+        // dropped before it.
+        let hotstring = Hotstring::new("btw", "by the way", HotstringOptions::default()).unwrap();
+        let typed: Vec<char> = "btw ".chars().collect();
+        assert!(hotstring.matches(&typed, false).is_some());
+        assert_eq!(hotstring.matches(&typed, true), None);
+    }
+
+    #[test]
+    fn rejects_an_empty_abbreviation() {
+        assert!(Hotstring::new("", "nothing", HotstringOptions::default()).is_err());
+    }
+}
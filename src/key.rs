@@ -0,0 +1,266 @@
+use std::fmt::Display;
+use napi_derive::napi;
+
+/// What the backends need to know about a key.
+#[derive(Debug)]
+pub struct KeyInfo {
+    pub key: Key,
+    /// The names `parseAccelerator` and `Keyboard.send` accept, case-insensitively. The first
+    /// one is what `formatAccelerator` and `Display` write.
+    pub names: &'static [&'static str],
+    /// The Windows virtual-key code.
+    pub vk: u16,
+    /// The PC/AT (set 1) scan code, with the 0xE0 or 0xE1 prefix of extended keys in the high
+    /// byte.
+    pub scan_code: u16,
+    /// The X11 keysym the key produces without modifiers.
+    pub keysym: u32,
+    /// The Linux input event code, which the X server's keycode is 8 more than. 0 when the key
+    /// has none.
+    pub evdev: u16,
+}
+
+/// Declares `Key` and the table of `KeyInfo`, in the same order so that a key's value is its
+/// index in the table.
+macro_rules! keys {
+    ($($key:ident => [$($name:literal),+], $vk:literal, $scan_code:literal, $keysym:literal, $evdev:literal;)+) => {
+        /// A key, independent of the platform and the keyboard layout. The printable keys are
+        /// named after the US layout. The values are not Windows virtual-key codes, so pass
+        /// `Key.ArrowLeft` rather than a number like 37.
+        #[napi]
+        #[derive(Debug, PartialEq)]
+        pub enum Key {
+            None,
+            $($key,)+
+        }
+
+        const KEYS: &[KeyInfo] = &[
+            KeyInfo { key: Key::None, names: &["None"], vk: 0, scan_code: 0, keysym: 0, evdev: 0 },
+            $(KeyInfo { key: Key::$key, names: &[$($name),+], vk: $vk, scan_code: $scan_code, keysym: $keysym, evdev: $evdev },)+
+        ];
+    };
+}
+
+keys! {
+    Back => ["Back", "Backspace", "BS"], 0x08, 0x000e, 0xff08, 14;
+    Tab => ["Tab"], 0x09, 0x000f, 0xff09, 15;
+    LineFeed => ["LineFeed"], 0x0a, 0x0000, 0xff0a, 0;
+    Clear => ["Clear"], 0x0c, 0x004c, 0xff0b, 355;
+    Enter => ["Enter", "Return"], 0x0d, 0x001c, 0xff0d, 28;
+    Shift => ["Shift"], 0x10, 0x002a, 0xffe1, 42;
+    Control => ["Control", "Ctrl"], 0x11, 0x001d, 0xffe3, 29;
+    Alt => ["Alt"], 0x12, 0x0038, 0xffe9, 56;
+    Pause => ["Pause"], 0x13, 0xe11d, 0xff13, 119;
+    CapsLock => ["CapsLock"], 0x14, 0x003a, 0xffe5, 58;
+    Esc => ["Esc", "Escape"], 0x1b, 0x0001, 0xff1b, 1;
+    Space => ["Space"], 0x20, 0x0039, 0x0020, 57;
+    PageUp => ["PageUp", "PgUp"], 0x21, 0xe049, 0xff55, 104;
+    PageDown => ["PageDown", "PgDn"], 0x22, 0xe051, 0xff56, 109;
+    End => ["End"], 0x23, 0xe04f, 0xff57, 107;
+    Home => ["Home"], 0x24, 0xe047, 0xff50, 102;
+    ArrowLeft => ["ArrowLeft", "Left"], 0x25, 0xe04b, 0xff51, 105;
+    ArrowUp => ["ArrowUp", "Up"], 0x26, 0xe048, 0xff52, 103;
+    ArrowRight => ["ArrowRight", "Right"], 0x27, 0xe04d, 0xff53, 106;
+    ArrowDown => ["ArrowDown", "Down"], 0x28, 0xe050, 0xff54, 108;
+    PrintScreen => ["PrintScreen", "PrtSc"], 0x2c, 0xe037, 0xff61, 99;
+    Insert => ["Insert", "Ins"], 0x2d, 0xe052, 0xff63, 110;
+    Delete => ["Delete", "Del"], 0x2e, 0xe053, 0xffff, 111;
+    D0 => ["0", "D0"], 0x30, 0x000b, 0x0030, 11;
+    D1 => ["1", "D1"], 0x31, 0x0002, 0x0031, 2;
+    D2 => ["2", "D2"], 0x32, 0x0003, 0x0032, 3;
+    D3 => ["3", "D3"], 0x33, 0x0004, 0x0033, 4;
+    D4 => ["4", "D4"], 0x34, 0x0005, 0x0034, 5;
+    D5 => ["5", "D5"], 0x35, 0x0006, 0x0035, 6;
+    D6 => ["6", "D6"], 0x36, 0x0007, 0x0036, 7;
+    D7 => ["7", "D7"], 0x37, 0x0008, 0x0037, 8;
+    D8 => ["8", "D8"], 0x38, 0x0009, 0x0038, 9;
+    D9 => ["9", "D9"], 0x39, 0x000a, 0x0039, 10;
+    A => ["A"], 0x41, 0x001e, 0x0061, 30;
+    B => ["B"], 0x42, 0x0030, 0x0062, 48;
+    C => ["C"], 0x43, 0x002e, 0x0063, 46;
+    D => ["D"], 0x44, 0x0020, 0x0064, 32;
+    E => ["E"], 0x45, 0x0012, 0x0065, 18;
+    F => ["F"], 0x46, 0x0021, 0x0066, 33;
+    G => ["G"], 0x47, 0x0022, 0x0067, 34;
+    H => ["H"], 0x48, 0x0023, 0x0068, 35;
+    I => ["I"], 0x49, 0x0017, 0x0069, 23;
+    J => ["J"], 0x4a, 0x0024, 0x006a, 36;
+    K => ["K"], 0x4b, 0x0025, 0x006b, 37;
+    L => ["L"], 0x4c, 0x0026, 0x006c, 38;
+    M => ["M"], 0x4d, 0x0032, 0x006d, 50;
+    N => ["N"], 0x4e, 0x0031, 0x006e, 49;
+    O => ["O"], 0x4f, 0x0018, 0x006f, 24;
+    P => ["P"], 0x50, 0x0019, 0x0070, 25;
+    Q => ["Q"], 0x51, 0x0010, 0x0071, 16;
+    R => ["R"], 0x52, 0x0013, 0x0072, 19;
+    S => ["S"], 0x53, 0x001f, 0x0073, 31;
+    T => ["T"], 0x54, 0x0014, 0x0074, 20;
+    U => ["U"], 0x55, 0x0016, 0x0075, 22;
+    V => ["V"], 0x56, 0x002f, 0x0076, 47;
+    W => ["W"], 0x57, 0x0011, 0x0077, 17;
+    X => ["X"], 0x58, 0x002d, 0x0078, 45;
+    Y => ["Y"], 0x59, 0x0015, 0x0079, 21;
+    Z => ["Z"], 0x5a, 0x002c, 0x007a, 44;
+    LeftWin => ["LeftWin", "LWin", "Win"], 0x5b, 0xe05b, 0xffeb, 125;
+    RightWin => ["RightWin", "RWin"], 0x5c, 0xe05c, 0xffec, 126;
+    Apps => ["Apps", "Menu", "AppsKey"], 0x5d, 0xe05d, 0xff67, 127;
+    Sleep => ["Sleep"], 0x5f, 0xe05f, 0x1008ff2f, 142;
+    NumPad0 => ["NumPad0", "Numpad0"], 0x60, 0x0052, 0xffb0, 82;
+    NumPad1 => ["NumPad1"], 0x61, 0x004f, 0xffb1, 79;
+    NumPad2 => ["NumPad2"], 0x62, 0x0050, 0xffb2, 80;
+    NumPad3 => ["NumPad3"], 0x63, 0x0051, 0xffb3, 81;
+    NumPad4 => ["NumPad4"], 0x64, 0x004b, 0xffb4, 75;
+    NumPad5 => ["NumPad5"], 0x65, 0x004c, 0xffb5, 76;
+    NumPad6 => ["NumPad6"], 0x66, 0x004d, 0xffb6, 77;
+    NumPad7 => ["NumPad7"], 0x67, 0x0047, 0xffb7, 71;
+    NumPad8 => ["NumPad8"], 0x68, 0x0048, 0xffb8, 72;
+    NumPad9 => ["NumPad9"], 0x69, 0x0049, 0xffb9, 73;
+    Multiply => ["Multiply", "NumPadMult"], 0x6a, 0x0037, 0xffaa, 55;
+    Add => ["Add", "NumPadAdd"], 0x6b, 0x004e, 0xffab, 78;
+    Separator => ["Separator"], 0x6c, 0x007e, 0xffac, 121;
+    Subtract => ["Subtract", "NumPadSub"], 0x6d, 0x004a, 0xffad, 74;
+    Decimal => ["Decimal", "NumPadDot"], 0x6e, 0x0053, 0xffae, 83;
+    Divide => ["Divide", "NumPadDiv"], 0x6f, 0xe035, 0xffaf, 98;
+    F1 => ["F1"], 0x70, 0x003b, 0xffbe, 59;
+    F2 => ["F2"], 0x71, 0x003c, 0xffbf, 60;
+    F3 => ["F3"], 0x72, 0x003d, 0xffc0, 61;
+    F4 => ["F4"], 0x73, 0x003e, 0xffc1, 62;
+    F5 => ["F5"], 0x74, 0x003f, 0xffc2, 63;
+    F6 => ["F6"], 0x75, 0x0040, 0xffc3, 64;
+    F7 => ["F7"], 0x76, 0x0041, 0xffc4, 65;
+    F8 => ["F8"], 0x77, 0x0042, 0xffc5, 66;
+    F9 => ["F9"], 0x78, 0x0043, 0xffc6, 67;
+    F10 => ["F10"], 0x79, 0x0044, 0xffc7, 68;
+    F11 => ["F11"], 0x7a, 0x0057, 0xffc8, 87;
+    F12 => ["F12"], 0x7b, 0x0058, 0xffc9, 88;
+    F13 => ["F13"], 0x7c, 0x0064, 0xffca, 183;
+    F14 => ["F14"], 0x7d, 0x0065, 0xffcb, 184;
+    F15 => ["F15"], 0x7e, 0x0066, 0xffcc, 185;
+    F16 => ["F16"], 0x7f, 0x0067, 0xffcd, 186;
+    F17 => ["F17"], 0x80, 0x0068, 0xffce, 187;
+    F18 => ["F18"], 0x81, 0x0069, 0xffcf, 188;
+    F19 => ["F19"], 0x82, 0x006a, 0xffd0, 189;
+    F20 => ["F20"], 0x83, 0x006b, 0xffd1, 190;
+    F21 => ["F21"], 0x84, 0x006c, 0xffd2, 191;
+    F22 => ["F22"], 0x85, 0x006d, 0xffd3, 192;
+    F23 => ["F23"], 0x86, 0x006e, 0xffd4, 193;
+    F24 => ["F24"], 0x87, 0x0076, 0xffd5, 194;
+    NumLock => ["NumLock"], 0x90, 0x0045, 0xff7f, 69;
+    ScrollLock => ["ScrollLock"], 0x91, 0x0046, 0xff14, 70;
+    LeftShift => ["LeftShift", "LShift"], 0xa0, 0x002a, 0xffe1, 42;
+    RightShift => ["RightShift", "RShift"], 0xa1, 0x0036, 0xffe2, 54;
+    LeftControl => ["LeftControl", "LCtrl", "LControl"], 0xa2, 0x001d, 0xffe3, 29;
+    RightControl => ["RightControl", "RCtrl", "RControl"], 0xa3, 0xe01d, 0xffe4, 97;
+    LeftAlt => ["LeftAlt", "LAlt"], 0xa4, 0x0038, 0xffe9, 56;
+    RightAlt => ["RightAlt", "RAlt"], 0xa5, 0xe038, 0xffea, 100;
+    BrowserBack => ["BrowserBack", "Browser_Back"], 0xa6, 0xe06a, 0x1008ff26, 158;
+    BrowserForward => ["BrowserForward", "Browser_Forward"], 0xa7, 0xe069, 0x1008ff27, 159;
+    BrowserRefresh => ["BrowserRefresh", "Browser_Refresh"], 0xa8, 0xe067, 0x1008ff29, 173;
+    BrowserStop => ["BrowserStop", "Browser_Stop"], 0xa9, 0xe068, 0x1008ff28, 128;
+    BrowserSearch => ["BrowserSearch", "Browser_Search"], 0xaa, 0xe065, 0x1008ff1b, 217;
+    BrowserFavorites => ["BrowserFavorites", "Browser_Favorites"], 0xab, 0xe066, 0x1008ff30, 364;
+    BrowserHome => ["BrowserHome", "Browser_Home"], 0xac, 0xe032, 0x1008ff18, 172;
+    VolumeMute => ["VolumeMute", "Volume_Mute"], 0xad, 0xe020, 0x1008ff12, 113;
+    VolumeDown => ["VolumeDown", "Volume_Down"], 0xae, 0xe02e, 0x1008ff11, 114;
+    VolumeUp => ["VolumeUp", "Volume_Up"], 0xaf, 0xe030, 0x1008ff13, 115;
+    MediaNext => ["MediaNext", "Media_Next"], 0xb0, 0xe019, 0x1008ff17, 163;
+    MediaPrev => ["MediaPrev", "Media_Prev"], 0xb1, 0xe010, 0x1008ff16, 165;
+    MediaStop => ["MediaStop", "Media_Stop"], 0xb2, 0xe024, 0x1008ff15, 166;
+    MediaPlayPause => ["MediaPlayPause", "Media_Play_Pause"], 0xb3, 0xe022, 0x1008ff14, 164;
+    LaunchMail => ["LaunchMail", "Launch_Mail"], 0xb4, 0xe06c, 0x1008ff19, 155;
+    LaunchMedia => ["LaunchMedia", "Launch_Media"], 0xb5, 0xe06d, 0x1008ff32, 226;
+    LaunchApp1 => ["LaunchApp1", "Launch_App1"], 0xb6, 0xe06b, 0x1008ff33, 157;
+    LaunchApp2 => ["LaunchApp2", "Launch_App2"], 0xb7, 0xe021, 0x1008ff1d, 140;
+    Semicolon => ["Semicolon", ";"], 0xba, 0x0027, 0x003b, 39;
+    Equal => ["Equal", "="], 0xbb, 0x000d, 0x003d, 13;
+    Comma => ["Comma", ","], 0xbc, 0x0033, 0x002c, 51;
+    Minus => ["Minus", "-"], 0xbd, 0x000c, 0x002d, 12;
+    Period => ["Period", "."], 0xbe, 0x0034, 0x002e, 52;
+    Slash => ["Slash", "/"], 0xbf, 0x0035, 0x002f, 53;
+    Backquote => ["Backquote", "`"], 0xc0, 0x0029, 0x0060, 41;
+    BracketLeft => ["BracketLeft", "["], 0xdb, 0x001a, 0x005b, 26;
+    Backslash => ["Backslash", "\\"], 0xdc, 0x002b, 0x005c, 43;
+    BracketRight => ["BracketRight", "]"], 0xdd, 0x001b, 0x005d, 27;
+    Quote => ["Quote", "'"], 0xde, 0x0028, 0x0027, 40;
+    IntlBackslash => ["IntlBackslash"], 0xe2, 0x0056, 0x003c, 86;
+}
+
+impl Display for Key {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", key_name(*self))
+    }
+}
+
+pub fn key_info(key: Key) -> &'static KeyInfo {
+    &KEYS[key as usize]
+}
+
+/// Every key but `None`.
+pub fn all_keys() -> impl Iterator<Item = Key> {
+    KEYS[1..].iter().map(|it| it.key)
+}
+
+pub fn key_from_name(name: &str) -> Option<Key> {
+    KEYS[1..]
+        .iter()
+        .find(|it| it.names.iter().any(|it| it.eq_ignore_ascii_case(name)))
+        .map(|it| it.key)
+}
+
+pub fn key_name(key: Key) -> &'static str {
+    key_info(key).names[0]
+}
+
+/// The key with the virtual-key code `vk`.
+pub fn key_from_vk(vk: u32) -> Option<Key> {
+    KEYS[1..].iter().find(|it| it.vk as u32 == vk).map(|it| it.key)
+}
+
+/// The key producing `keysym`. Keys sharing a keysym, like `Shift` and `LeftShift`, resolve to
+/// the one naming the side.
+pub fn key_from_keysym(keysym: u32) -> Option<Key> {
+    KEYS[1..].iter().rfind(|it| it.keysym == keysym).map(|it| it.key)
+}
+
+/// The key with the Linux input event code `evdev`, preferring the one naming the side like
+/// `key_from_keysym`.
+pub fn key_from_evdev(evdev: u16) -> Option<Key> {
+    match evdev {
+        0 => None,
+        evdev => KEYS[1..].iter().rfind(|it| it.evdev == evdev).map(|it| it.key),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn values_are_table_indices() {
+        for (index, info) in KEYS.iter().enumerate() {
+            assert_eq!(info.key as usize, index, "{:?}", info.key);
+        }
+    }
+
+    #[test]
+    fn names_and_codes_are_unique() {
+        for (index, info) in KEYS.iter().enumerate() {
+            for other in &KEYS[index + 1..] {
+                assert!(!info.names.iter().any(|name| other.names.iter().any(|it| it.eq_ignore_ascii_case(name))), "{:?} and {:?}", info.key, other.key);
+                assert_ne!(info.vk, other.vk, "{:?} and {:?}", info.key, other.key);
+            }
+        }
+    }
+
+    #[test]
+    fn converts_keys() {
+        assert_eq!(key_from_name("volume_mute"), Some(Key::VolumeMute));
+        assert_eq!(key_from_name(";"), Some(Key::Semicolon));
+        assert_eq!(key_from_name("None"), None);
+        assert_eq!(Key::D0.to_string(), "0");
+        assert_eq!(key_from_vk(0x10), Some(Key::Shift));
+        assert_eq!(key_from_vk(0xa0), Some(Key::LeftShift));
+        assert_eq!(key_from_keysym(0xffe1), Some(Key::LeftShift));
+        assert_eq!(key_from_keysym(0x1008ff13), Some(Key::VolumeUp));
+    }
+}
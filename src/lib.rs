@@ -1,9 +1,24 @@
 #![deny(clippy::all)]
 
+pub mod accelerator;
+pub mod barcode;
+pub mod delay;
+pub mod desktop;
+pub mod draw;
 pub mod geometry;
+pub mod hotkey;
+pub mod hotkey_sequence;
+pub mod hotstring;
+pub mod key;
+pub mod modifiers;
+pub mod mouse_path;
 pub mod screen;
+pub mod send;
 pub mod utils;
 
+#[cfg(target_os = "linux")]
+pub mod linux;
+
 #[cfg(target_os = "windows")]
 pub mod win;
 
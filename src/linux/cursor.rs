@@ -0,0 +1,182 @@
+use std::sync::Mutex;
+use lazy_static::lazy_static;
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+use x11rb::connection::Connection;
+use x11rb::protocol::xfixes::{Barrier, BarrierDirections, ConnectionExt as _};
+use x11rb::protocol::xproto::{ConnectionExt as _, MOTION_NOTIFY_EVENT};
+use x11rb::wrapper::ConnectionExt as _;
+use crate::geometry::Rect;
+use crate::utils::handle_result;
+use super::mouse::Mouse;
+use super::x11::{display, fake_input, Display};
+
+#[napi]
+#[derive(Debug, PartialEq)]
+pub enum CursorShape {
+    /// No cursor is shown.
+    Hidden,
+    Arrow,
+    /// The text cursor.
+    IBeam,
+    /// The busy cursor (an hourglass or spinner).
+    Wait,
+    /// An arrow with a small busy indicator, shown while an application starts or works in the
+    /// background.
+    Progress,
+    /// The pointing hand over links.
+    Hand,
+    Crosshair,
+    /// Any other cursor, e.g. resize arrows or custom cursors.
+    Other,
+}
+
+lazy_static! {
+    static ref CONFINEMENT: Mutex<Vec<Barrier>> = Mutex::new(Vec::new());
+}
+
+#[napi]
+impl Mouse {
+    /// Keeps the cursor inside `rect` until `unconfine` is called, moving it there first if it
+    /// is outside. Synthetic moves to absolute positions can still leave the area.
+    ///
+    /// The cursor is held back by pointer barriers instead of a pointer grab, so clicks and key
+    /// presses keep going to the applications under it.
+    #[napi]
+    pub async fn confine(rect: Rect) -> Result<()> {
+        let task = tokio::spawn(async move {
+            confine_inner(rect)
+        });
+
+        handle_result(task).await
+    }
+
+    /// Lets the cursor leave the area given to `confine` again.
+    #[napi]
+    pub async fn unconfine() -> Result<()> {
+        let task = tokio::spawn(async move {
+            unconfine_inner()
+        });
+
+        handle_result(task).await
+    }
+
+    #[napi]
+    pub async fn get_cursor_shape() -> Result<CursorShape> {
+        let task = tokio::spawn(async move {
+            get_cursor_shape_inner()
+        });
+
+        handle_result(task).await
+    }
+}
+
+/// XFixes requests are refused until the client announced the version it speaks; pointer
+/// barriers need version 5.
+fn query_xfixes_version(display: &Display) -> std::result::Result<(), String> {
+    let version = display.connection
+        .xfixes_query_version(5, 0)
+        .map_err(|e| e.into())
+        .and_then(|cookie| cookie.reply());
+
+    match version {
+        Ok(_) => Ok(()),
+        Err(e) => Err(format!("Failed to query the XFIXES extension: {:?}", e)),
+    }
+}
+
+/// Surrounds `rect` with four pointer barriers. Unlike a pointer grab, barriers leave the
+/// input going to the applications under the cursor.
+fn confine_inner(rect: Rect) -> std::result::Result<(), String> {
+    let display = display()?;
+    query_xfixes_version(display)?;
+    unconfine_inner()?;
+
+    let reply = match display.connection.query_pointer(display.root) {
+        Ok(cookie) => cookie.reply(),
+        Err(e) => return Err(format!("Failed to query pointer: {:?}", e)),
+    };
+    match reply {
+        Ok(reply) => {
+            let x = (reply.root_x as i32).clamp(rect.left, (rect.right - 1).max(rect.left));
+            let y = (reply.root_y as i32).clamp(rect.top, (rect.bottom - 1).max(rect.top));
+            if x != reply.root_x as i32 || y != reply.root_y as i32 {
+                fake_input(MOTION_NOTIFY_EVENT, 0, x, y)?;
+            }
+        }
+        Err(e) => return Err(format!("Failed to query pointer: {:?}", e)),
+    }
+
+    let clamp = |value: i32| value.clamp(0, u16::MAX as i32) as u16;
+    let (left, top, right, bottom) = (clamp(rect.left), clamp(rect.top), clamp(rect.right), clamp(rect.bottom));
+    let edges = [
+        (left, top, left, bottom),
+        (right, top, right, bottom),
+        (left, top, right, top),
+        (left, bottom, right, bottom),
+    ];
+
+    let mut barriers = CONFINEMENT.lock().unwrap();
+    for (x1, y1, x2, y2) in edges {
+        let barrier = match display.connection.generate_id() {
+            Ok(id) => id,
+            Err(e) => return Err(format!("Failed to create a pointer barrier: {:?}", e)),
+        };
+
+        let created = display.connection
+            .xfixes_create_pointer_barrier(barrier, display.root, x1, y1, x2, y2, BarrierDirections::from(0u32), &[])
+            .map_err(|e| e.into())
+            .and_then(|cookie| cookie.check());
+        if let Err(e) = created {
+            return Err(format!("Failed to create a pointer barrier: {:?}", e));
+        }
+
+        barriers.push(barrier);
+    }
+
+    Ok(())
+}
+
+fn unconfine_inner() -> std::result::Result<(), String> {
+    let display = display()?;
+
+    for barrier in CONFINEMENT.lock().unwrap().drain(..) {
+        let _ = display.connection.xfixes_delete_pointer_barrier(barrier);
+    }
+
+    match display.connection.sync() {
+        Ok(_) => Ok(()),
+        Err(e) => Err(format!("Failed to remove the pointer barriers: {:?}", e)),
+    }
+}
+
+/// Recognizes the cursor by the name the cursor theme gave it. Applications that draw their own
+/// cursor images report `Other`.
+fn get_cursor_shape_inner() -> std::result::Result<CursorShape, String> {
+    let display = display()?;
+    query_xfixes_version(display)?;
+
+    let reply = display.connection
+        .xfixes_get_cursor_image_and_name()
+        .map_err(|e| e.into())
+        .and_then(|cookie| cookie.reply());
+    let cursor = match reply {
+        Ok(reply) => reply,
+        Err(e) => return Err(format!("Failed to get the cursor: {:?}", e)),
+    };
+
+    // The pixels are ARGB, a hidden cursor is an image that is transparent everywhere.
+    if cursor.cursor_image.iter().all(|pixel| pixel >> 24 == 0) {
+        return Ok(CursorShape::Hidden);
+    }
+
+    Ok(match String::from_utf8_lossy(&cursor.name).as_ref() {
+        "left_ptr" | "default" | "arrow" | "top_left_arrow" => CursorShape::Arrow,
+        "xterm" | "text" | "ibeam" => CursorShape::IBeam,
+        "watch" | "wait" => CursorShape::Wait,
+        "left_ptr_watch" | "progress" | "half-busy" => CursorShape::Progress,
+        "hand1" | "hand2" | "pointer" | "pointing_hand" => CursorShape::Hand,
+        "crosshair" | "cross" | "tcross" => CursorShape::Crosshair,
+        _ => CursorShape::Other,
+    })
+}
@@ -0,0 +1,306 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use lazy_static::lazy_static;
+use x11rb::connection::Connection;
+use x11rb::errors::ReplyError;
+use x11rb::protocol::xproto::{Allow, Atom, AtomEnum, ClientMessageEvent, ConnectionExt as _, CreateWindowAux, EventMask, GetPropertyReply, GrabMode, Keycode, ModMask, Window, WindowClass};
+use x11rb::protocol::{ErrorKind, Event};
+use x11rb::rust_connection::RustConnection;
+use x11rb::COPY_DEPTH_FROM_PARENT;
+use crate::hotkey::{settle, trigger_hotkey, ForegroundWindow, Hotkey, HotkeyAction, HotkeyDeferred, HotkeyDone, HotkeyError, HotkeyErrorCode};
+use crate::modifiers::SIDED_MODIFIERS;
+use super::keyboard::{key_keycode, Keymap, Modifiers};
+use super::x11::display;
+
+/// The modifiers hotkeys care about: Shift, Control, Mod1, Mod4 and Mod5. Caps Lock (Lock) and
+/// Num Lock (Mod2) are ignored by grabbing every combination with them too.
+const HOTKEY_MODIFIERS: u16 = 0x01 | 0x04 | 0x08 | 0x40 | 0x80;
+const LOCK_MODIFIERS: [u16; 4] = [0, 0x02, 0x10, 0x02 | 0x10];
+
+/// The connection grabbing the hotkeys, whose events a thread of its own waits for. A
+/// `ClientMessage` to `wake` ends that thread.
+struct HotkeyConnection {
+    connection: Arc<RustConnection>,
+    root: Window,
+    wake: Window,
+}
+
+lazy_static! {
+    static ref HOTKEY_CONNECTION: Mutex<Option<HotkeyConnection>> = Mutex::new(None);
+    /// The id of each grabbed keycode and modifier mask.
+    static ref GRABS: Mutex<HashMap<(Keycode, u16), u32>> = Mutex::new(HashMap::new());
+}
+
+pub fn register(hotkey: Hotkey, done: HotkeyDone) {
+    done(grab(&hotkey));
+}
+
+pub fn unregister(id: u32) {
+    let grab = GRABS.lock().unwrap().iter().find(|(_, it)| **it == id).map(|(grab, _)| *grab);
+    if let (Some(grab), Some(hotkey_connection)) = (grab, HOTKEY_CONNECTION.lock().unwrap().as_ref()) {
+        ungrab(hotkey_connection, grab);
+        let _ = hotkey_connection.connection.flush();
+    }
+}
+
+pub fn shutdown(deferred: HotkeyDeferred<()>) {
+    if let Some(hotkey_connection) = HOTKEY_CONNECTION.lock().unwrap().take() {
+        let grabs: Vec<(Keycode, u16)> = GRABS.lock().unwrap().keys().copied().collect();
+        for grab in grabs {
+            ungrab(&hotkey_connection, grab);
+        }
+
+        let event = ClientMessageEvent::new(32, hotkey_connection.wake, AtomEnum::NONE, [0u32; 5]);
+        let _ = hotkey_connection.connection.send_event(false, hotkey_connection.wake, EventMask::NO_EVENT, event);
+        let _ = hotkey_connection.connection.flush();
+    }
+
+    settle(deferred, Ok(()));
+}
+
+fn grab(hotkey: &Hotkey) -> std::result::Result<(), HotkeyError> {
+    let failed = |e: String| HotkeyError::new(HotkeyErrorCode::Failed, e);
+
+    let display = display().map_err(failed)?;
+    let keymap = Keymap::load(display).map_err(failed)?;
+    let keycode = match key_keycode(&keymap, hotkey.key) {
+        Some(keycode) => keycode,
+        None => return Err(HotkeyError::new(HotkeyErrorCode::Unsupported, format!("The keyboard layout has no key for {:?}", hotkey.key))),
+    };
+    let mods = modifier_mask(&hotkey.mods)?;
+
+    let mut hotkey_connection = HOTKEY_CONNECTION.lock().unwrap();
+    if hotkey_connection.is_none() {
+        *hotkey_connection = Some(connect().map_err(failed)?);
+    }
+    let hotkey_connection = hotkey_connection.as_ref().unwrap();
+    let connection = &hotkey_connection.connection;
+
+    for (i, lock) in LOCK_MODIFIERS.iter().enumerate() {
+        let grabbed = connection
+            .grab_key(false, hotkey_connection.root, ModMask::from(mods | lock), keycode, GrabMode::ASYNC, GrabMode::SYNC)
+            .map_err(ReplyError::from)
+            .and_then(|cookie| cookie.check());
+
+        if let Err(e) = grabbed {
+            for lock in &LOCK_MODIFIERS[..i] {
+                let _ = connection.ungrab_key(keycode, hotkey_connection.root, ModMask::from(mods | lock));
+            }
+            let _ = connection.flush();
+
+            return Err(match e {
+                ReplyError::X11Error(error) if error.error_kind == ErrorKind::Access => HotkeyError::new(
+                    HotkeyErrorCode::AlreadyRegistered,
+                    format!("Another application already grabbed {:?} with {:?}", hotkey.key, hotkey.mods),
+                ),
+                e => failed(format!("Failed to grab the key: {:?}", e)),
+            });
+        }
+    }
+
+    GRABS.lock().unwrap().insert((keycode, mods), hotkey.id);
+
+    Ok(())
+}
+
+fn ungrab(hotkey_connection: &HotkeyConnection, (keycode, mods): (Keycode, u16)) {
+    GRABS.lock().unwrap().remove(&(keycode, mods));
+
+    for lock in LOCK_MODIFIERS {
+        let _ = hotkey_connection.connection.ungrab_key(keycode, hotkey_connection.root, ModMask::from(mods | lock));
+    }
+}
+
+/// The modifiers of one side whose key is held down according to `QueryKeymap`, as key events
+/// only carry the modifier mask. None when the X server can't be asked.
+pub fn held_sides() -> Vec<Modifiers> {
+    let held = || -> std::result::Result<Vec<Modifiers>, String> {
+        let display = display()?;
+        let keymap = Keymap::load(display)?;
+        let keys = display
+            .connection
+            .query_keymap()
+            .map_err(|e| e.to_string())?
+            .reply()
+            .map_err(|e| e.to_string())?
+            .keys;
+
+        Ok(SIDED_MODIFIERS
+            .iter()
+            .filter(|(_, _, key)| key_keycode(&keymap, *key).is_some_and(|it| keys[it as usize / 8] & (1 << (it % 8)) != 0))
+            .map(|(sided, _, _)| *sided)
+            .collect())
+    };
+
+    held().unwrap_or_default()
+}
+
+/// The X modifier mask of hotkey modifiers, assuming the usual mapping of Mod1 to Alt, Mod4 to
+/// Super and Mod5 to AltGr. The other modifiers have no bit of their own and are refused rather
+/// than left out.
+fn modifier_mask(mods: &[Modifiers]) -> std::result::Result<u16, HotkeyError> {
+    let mut mask = 0;
+
+    for modifier in mods {
+        mask |= match modifier {
+            Modifiers::Shift => 0x01,
+            Modifiers::Control => 0x04,
+            Modifiers::Alt => 0x08,
+            Modifiers::Super | Modifiers::Meta => 0x40,
+            Modifiers::AltGraph => 0x80,
+            _ => return Err(HotkeyError::new(
+                HotkeyErrorCode::Unsupported,
+                format!("Hotkeys on X11 can't use the {:?} modifier", modifier),
+            )),
+        };
+    }
+
+    Ok(mask)
+}
+
+/// Opens the connection for hotkeys and starts the thread calling their callbacks. Key repeat
+/// is recognized by a release followed by a press of the same key at the same time. Keys are
+/// grabbed synchronously, so the keyboard stays frozen until a press is either taken, or
+/// replayed to the focused window when it is out of the scope of every callback.
+fn connect() -> std::result::Result<HotkeyConnection, String> {
+    let (connection, screen) = match x11rb::connect(None) {
+        Ok(result) => result,
+        Err(e) => return Err(format!("Failed to connect to the X server: {:?}", e)),
+    };
+    let connection = Arc::new(connection);
+    let root = connection.setup().roots[screen].root;
+
+    let wake = match connection.generate_id() {
+        Ok(id) => id,
+        Err(e) => return Err(format!("Failed to create a window: {:?}", e)),
+    };
+    let created = connection
+        .create_window(COPY_DEPTH_FROM_PARENT, wake, root, 0, 0, 1, 1, 0, WindowClass::INPUT_ONLY, 0, &CreateWindowAux::new())
+        .map_err(ReplyError::from)
+        .and_then(|cookie| cookie.check());
+    if let Err(e) = created {
+        return Err(format!("Failed to create a window: {:?}", e));
+    }
+
+    let events = connection.clone();
+    std::thread::spawn(move || {
+        // The keycodes and ids of the hotkeys held down, whose modifiers may be released first.
+        let mut held: Vec<(Keycode, u32)> = Vec::new();
+        let mut next = None;
+
+        loop {
+            let event = match next.take() {
+                Some(event) => Ok(event),
+                None => events.wait_for_event(),
+            };
+
+            match event {
+                Ok(Event::KeyPress(event)) => {
+                    let taken = match held.iter().find(|(keycode, _)| *keycode == event.detail) {
+                        Some((_, id)) => trigger_hotkey(*id, HotkeyAction::Repeat),
+                        None => {
+                            let mods = u16::from(event.state) & HOTKEY_MODIFIERS;
+                            let id = GRABS.lock().unwrap().get(&(event.detail, mods)).copied();
+                            match id {
+                                Some(id) if trigger_hotkey(id, HotkeyAction::Press) => {
+                                    held.push((event.detail, id));
+                                    true
+                                }
+                                Some(_) => false,
+                                None => true,
+                            }
+                        }
+                    };
+
+                    let mode = if taken { Allow::ASYNC_KEYBOARD } else { Allow::REPLAY_KEYBOARD };
+                    let _ = events.allow_events(mode, event.time);
+                    let _ = events.flush();
+                }
+                Ok(Event::KeyRelease(event)) => {
+                    next = events.poll_for_event().ok().flatten();
+
+                    let repeats = matches!(&next, Some(Event::KeyPress(press)) if press.detail == event.detail && press.time == event.time);
+                    if !repeats {
+                        if let Some(index) = held.iter().position(|(keycode, _)| *keycode == event.detail) {
+                            trigger_hotkey(held.remove(index).1, HotkeyAction::Release);
+                        }
+                    }
+                }
+                Ok(Event::ClientMessage(event)) if event.window == wake => break,
+                Ok(_) => {}
+                Err(_) => break,
+            }
+        }
+
+        let _ = events.destroy_window(wake);
+        let _ = events.flush();
+    });
+
+    Ok(HotkeyConnection {
+        connection,
+        root,
+        wake,
+    })
+}
+
+/// The active window according to the window manager's `_NET_ACTIVE_WINDOW`, with its process
+/// found through `_NET_WM_PID`.
+pub fn foreground_window() -> ForegroundWindow {
+    display()
+        .ok()
+        .and_then(|display| active_window(&display.connection, display.root))
+        .unwrap_or_default()
+}
+
+fn active_window(connection: &RustConnection, root: Window) -> Option<ForegroundWindow> {
+    let atom = |name: &[u8]| -> Option<Atom> {
+        Some(connection.intern_atom(false, name).ok()?.reply().ok()?.atom)
+    };
+    let property = |window: Window, property: Atom, type_: Atom| -> Option<GetPropertyReply> {
+        connection.get_property(false, window, property, type_, 0, u32::MAX).ok()?.reply().ok()
+    };
+
+    let window = property(root, atom(b"_NET_ACTIVE_WINDOW")?, AtomEnum::WINDOW.into())?.value32()?.next()?;
+    if window == x11rb::NONE {
+        return None;
+    }
+
+    let title = atom(b"_NET_WM_NAME")
+        .zip(atom(b"UTF8_STRING"))
+        .and_then(|(name, utf8)| property(window, name, utf8))
+        .filter(|it| !it.value.is_empty())
+        .or_else(|| property(window, AtomEnum::WM_NAME.into(), AtomEnum::ANY.into()))
+        .map(|it| String::from_utf8_lossy(&it.value).into_owned())
+        .unwrap_or_default();
+
+    // WM_CLASS holds the instance and the class name, each terminated by a null byte.
+    let class_names = property(window, AtomEnum::WM_CLASS.into(), AtomEnum::STRING.into())
+        .map(|it| {
+            it.value
+                .split(|byte| *byte == 0)
+                .filter(|name| !name.is_empty())
+                .map(|name| String::from_utf8_lossy(name).into_owned())
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let process = atom(b"_NET_WM_PID")
+        .and_then(|pid| property(window, pid, AtomEnum::CARDINAL.into()))
+        .and_then(|it| it.value32()?.next())
+        .and_then(process_name)
+        .unwrap_or_default();
+
+    Some(ForegroundWindow {
+        title,
+        class_names,
+        process,
+    })
+}
+
+/// The file name of the executable of process `pid`, or its command name if that isn't readable.
+fn process_name(pid: u32) -> Option<String> {
+    match std::fs::read_link(format!("/proc/{}/exe", pid)) {
+        Ok(exe) => exe.file_name().map(|it| it.to_string_lossy().into_owned()),
+        Err(_) => std::fs::read_to_string(format!("/proc/{}/comm", pid)).ok().map(|it| it.trim_end().to_string()),
+    }
+}
@@ -0,0 +1,169 @@
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::{Condvar, Mutex};
+use std::time::{Duration, Instant};
+use lazy_static::lazy_static;
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+use x11rb::protocol::xproto::{KEY_PRESS_EVENT, KEY_RELEASE_EVENT};
+use crate::hotstring::{Hotstring, HotstringInput, HotstringMatch, HotstringMatcher, HotstringOptions};
+use super::keyboard::{is_modifier_keysym, key_event, keysym_char, typing_inner, Key, Keyboard, Keymap, Modifiers};
+use super::keyboard_listener::{start_interception, stop_interception_if_unused, KeyboardEvent, KeyboardEventKind};
+use super::x11::journal_key_events;
+
+lazy_static! {
+    static ref HOTSTRINGS: Mutex<HotstringMatcher> = Mutex::new(HotstringMatcher::default());
+    /// The (type, keycode) of the key events recorded while `EXPANDING` is set, and the signal
+    /// that another one arrived.
+    static ref RECORDED_WHILE_EXPANDING: (Mutex<Vec<(u8, u8)>>, Condvar) = (Mutex::new(Vec::new()), Condvar::new());
+}
+
+static NEXT_HOTSTRING_ID: AtomicU32 = AtomicU32::new(1);
+
+/// Set while a replacement is typed, until XRecord has delivered every key event typing it
+/// sent. XRecord reads them on a connection of its own, so they can still be on their way after
+/// the server has processed them, and it can't tell synthetic key events from real ones. Key
+/// events are ignored meanwhile.
+static EXPANDING: AtomicBool = AtomicBool::new(false);
+
+/// The longest `expand` waits for its key events to be recorded, in case some never are.
+const EXPANDING_TIMEOUT: Duration = Duration::from_secs(1);
+
+#[napi]
+impl Keyboard {
+    /// Replaces `abbreviation` whenever it is typed as a word of its own, erasing it with
+    /// Backspace and typing `replacement` instead. Returns an id for `unregisterHotstring`.
+    #[napi]
+    pub fn register_hotstring(abbreviation: String, replacement: String, options: Option<HotstringOptions>) -> Result<u32> {
+        let hotstring = Hotstring::new(&abbreviation, &replacement, options.unwrap_or_default())
+            .map_err(|e| Error::new(Status::InvalidArg, e))?;
+
+        let id = NEXT_HOTSTRING_ID.fetch_add(1, Ordering::Relaxed);
+        HOTSTRINGS.lock().unwrap().add(id, hotstring);
+
+        if let Err(e) = start_interception() {
+            HOTSTRINGS.lock().unwrap().remove(id);
+            return Err(Error::new(Status::GenericFailure, e));
+        }
+
+        Ok(id)
+    }
+
+    #[napi]
+    pub fn unregister_hotstring(id: u32) {
+        HOTSTRINGS.lock().unwrap().remove(id);
+        stop_interception_if_unused();
+    }
+}
+
+pub fn has_hotstrings() -> bool {
+    !HOTSTRINGS.lock().unwrap().is_empty()
+}
+
+/// Feeds a recorded key press to the hotstrings, and types the replacement of the one it
+/// completes on a thread of its own.
+pub fn hotstring_key_event(event: &KeyboardEvent, keymap: &Keymap) {
+    if EXPANDING.load(Ordering::Acquire) {
+        let type_ = if event.kind == KeyboardEventKind::Down { KEY_PRESS_EVENT } else { KEY_RELEASE_EVENT };
+        let (recorded, arrived) = &*RECORDED_WHILE_EXPANDING;
+        recorded.lock().unwrap().push((type_, event.scan_code as u8));
+        arrived.notify_all();
+        return;
+    }
+
+    if event.kind != KeyboardEventKind::Down {
+        return;
+    }
+
+    let input = match key_input(event, keymap) {
+        Some(input) => input,
+        None => return,
+    };
+
+    let found = {
+        let mut hotstrings = HOTSTRINGS.lock().unwrap();
+        if hotstrings.is_empty() {
+            return;
+        }
+        hotstrings.input(input)
+    };
+
+    if let Some(found) = found {
+        EXPANDING.store(true, Ordering::Release);
+        std::thread::spawn(move || {
+            let (_, sent) = journal_key_events(|| expand(&found));
+            wait_until_recorded(&sent);
+
+            HOTSTRINGS.lock().unwrap().reset();
+            RECORDED_WHILE_EXPANDING.0.lock().unwrap().clear();
+            EXPANDING.store(false, Ordering::Release);
+        });
+    }
+}
+
+/// What a key press means to hotstrings, or `None` for modifier keys, which don't break up
+/// the typed text.
+fn key_input(event: &KeyboardEvent, keymap: &Keymap) -> Option<HotstringInput> {
+    let keycode = event.scan_code as u8;
+    if is_modifier_keysym(event.code) {
+        return None;
+    }
+
+    let mods = &event.modifiers;
+    if mods.contains(&Modifiers::Control) || mods.contains(&Modifiers::Alt) || mods.contains(&Modifiers::Meta) {
+        return Some(HotstringInput::Reset);
+    }
+
+    let shift = mods.contains(&Modifiers::Shift);
+    let keysym = keymap.typed_keysym(keycode, shift, mods.contains(&Modifiers::AltGraph));
+    let c = match keysym_char(keysym) {
+        Some(c) => c,
+        None => return Some(HotstringInput::Reset),
+    };
+
+    // Caps Lock inverts the case Shift gives letters.
+    let c = match (c.is_alphabetic(), shift != mods.contains(&Modifiers::CapsLock)) {
+        (true, true) => single_char(c.to_uppercase()).unwrap_or(c),
+        (true, false) => single_char(c.to_lowercase()).unwrap_or(c),
+        (false, _) => c,
+    };
+
+    Some(HotstringInput::from_char(c))
+}
+
+fn single_char(mut chars: impl Iterator<Item = char>) -> Option<char> {
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => Some(c),
+        _ => None,
+    }
+}
+
+fn expand(found: &HotstringMatch) -> std::result::Result<(), String> {
+    for _ in 0..found.erase {
+        key_event(Key::Back, true)?;
+        key_event(Key::Back, false)?;
+    }
+    typing_inner(&found.text)
+}
+
+/// Waits until `sent` came back through XRecord, in order but possibly interleaved with keys
+/// the user pressed meanwhile.
+fn wait_until_recorded(sent: &[(u8, u8)]) {
+    let deadline = Instant::now() + EXPANDING_TIMEOUT;
+    let (recorded, arrived) = &*RECORDED_WHILE_EXPANDING;
+    let mut recorded = recorded.lock().unwrap();
+
+    loop {
+        let mut expected = sent.iter().peekable();
+        for event in recorded.iter() {
+            if expected.peek() == Some(&event) {
+                expected.next();
+            }
+        }
+
+        let now = Instant::now();
+        if expected.peek().is_none() || now >= deadline {
+            return;
+        }
+        recorded = arrived.wait_timeout(recorded, deadline - now).unwrap().0;
+    }
+}
@@ -0,0 +1,554 @@
+use std::sync::Mutex;
+use lazy_static::lazy_static;
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+use x11rb::connection::Connection;
+use x11rb::protocol::xproto::{ConnectionExt as _, KeyButMask, Keycode, Keysym, KEY_PRESS_EVENT, KEY_RELEASE_EVENT};
+use x11rb::wrapper::ConnectionExt as _;
+use crate::delay::{typing_steps, TypingOptions};
+use crate::key::{key_from_evdev, key_from_keysym, key_info};
+use crate::send::{parse_send_sequence_inner, SendEventKind};
+use crate::utils::{handle_input, handle_result};
+use super::x11::{display, fake_input, Display};
+
+/// Keyboard LEDs in the `led_mask` of the X server, in the order Xorg assigns them.
+const LED_CAPS_LOCK: u32 = 1 << 0;
+const LED_NUM_LOCK: u32 = 1 << 1;
+const LED_SCROLL_LOCK: u32 = 1 << 2;
+
+pub use crate::key::Key;
+pub use crate::modifiers::Modifiers;
+
+lazy_static! {
+    /// Spare keycodes some `Remapping` gave a keysym to, which other calls typing at the same
+    /// time must not take as spare.
+    static ref REMAPPED_KEYCODES: Mutex<Vec<Keycode>> = Mutex::new(Vec::new());
+    /// Keys pressed with `key_event` through a spare keycode, which keeps its keysym until they
+    /// are released.
+    static ref HELD_REMAPPINGS: Mutex<Vec<(Key, Remapping)>> = Mutex::new(Vec::new());
+}
+
+#[napi]
+pub struct Keyboard {
+
+}
+
+#[napi]
+impl Keyboard {
+    #[napi]
+    pub async fn press(key: Key) -> Result<()> {
+        let task = tokio::spawn(async move {
+            key_event(key, true)
+        });
+
+        handle_input(task).await
+    }
+
+    #[napi]
+    pub async fn release(key: Key) -> Result<()> {
+        let task = tokio::spawn(async move {
+            key_event(key, false)
+        });
+
+        handle_input(task).await
+    }
+
+    #[napi]
+    pub async fn click(key: Key) -> Result<()> {
+        let task = tokio::spawn(async move {
+            key_event(key, true)?;
+            key_event(key, false)
+        });
+
+        handle_input(task).await
+    }
+
+    /// Types `text` character by character, with the pauses `options` asks for. Characters
+    /// missing from the keyboard layout are typed through unused keycodes, which are given the
+    /// needed keysym until the text is typed.
+    #[napi]
+    pub async fn typing(text: String, options: Option<TypingOptions>) -> Result<()> {
+        let task = tokio::spawn(async move {
+            for step in typing_steps(&text, &options.unwrap_or_default()) {
+                if step.hold.is_zero() {
+                    typing_inner(&step.text)?;
+                } else {
+                    let display = display()?;
+                    let mut keymap = Keymap::load(display)?;
+                    let mut remapping = Remapping::default();
+                    for c in step.text.chars() {
+                        let keycodes = char_keycodes(display, &mut keymap, &mut remapping, c)?;
+                        char_event(keycodes, true)?;
+                        tokio::time::sleep(step.hold).await;
+                        char_event(keycodes, false)?;
+                    }
+                }
+
+                tokio::time::sleep(step.pause).await;
+            }
+
+            Ok(())
+        });
+
+        handle_input(task).await
+    }
+
+    /// Sends a key sequence in AutoHotkey's Send syntax, e.g. "Hello{Enter}^a{Tab 3}", see
+    /// `parseSendSequence`.
+    #[napi]
+    pub async fn send(sequence: String) -> Result<()> {
+        let task = tokio::spawn(async move {
+            for event in parse_send_sequence_inner(&sequence)? {
+                match (event.kind, event.key, event.text) {
+                    (SendEventKind::Press, Some(key), _) => key_event(key, true)?,
+                    (SendEventKind::Release, Some(key), _) => key_event(key, false)?,
+                    (SendEventKind::Text, _, Some(text)) => typing_inner(&text)?,
+                    _ => {}
+                }
+            }
+
+            Ok(())
+        });
+
+        handle_input(task).await
+    }
+
+    /// The modifiers held down right now, plus `CapsLock`, `NumLock` and `ScrollLock` when those
+    /// are toggled on.
+    #[napi]
+    pub async fn get_modifiers() -> Result<Vec<Modifiers>> {
+        let task = tokio::spawn(async move {
+            get_modifiers_inner()
+        });
+
+        handle_result(task).await
+    }
+}
+
+/// Reads held modifiers from the pointer's modifier mask and the lock keys from the keyboard
+/// LEDs.
+fn get_modifiers_inner() -> std::result::Result<Vec<Modifiers>, String> {
+    let display = display()?;
+    let connection = &display.connection;
+
+    let pointer = match connection.query_pointer(display.root) {
+        Ok(cookie) => cookie.reply(),
+        Err(e) => return Err(format!("Failed to query pointer: {:?}", e)),
+    };
+    let mask = match pointer {
+        Ok(reply) => reply.mask,
+        Err(e) => return Err(format!("Failed to query pointer: {:?}", e)),
+    };
+
+    let control = match connection.get_keyboard_control() {
+        Ok(cookie) => cookie.reply(),
+        Err(e) => return Err(format!("Failed to query keyboard control: {:?}", e)),
+    };
+    let leds = match control {
+        Ok(reply) => reply.led_mask,
+        Err(e) => return Err(format!("Failed to query keyboard control: {:?}", e)),
+    };
+
+    let mut mods = held_modifiers(mask);
+    if leds & LED_CAPS_LOCK != 0 {
+        mods.push(Modifiers::CapsLock);
+    }
+    if leds & LED_NUM_LOCK != 0 {
+        mods.push(Modifiers::NumLock);
+    }
+    if leds & LED_SCROLL_LOCK != 0 {
+        mods.push(Modifiers::ScrollLock);
+    }
+
+    Ok(mods)
+}
+
+/// The modifiers held according to the modifier mask of an event or pointer query, assuming
+/// the usual mapping of Mod1 to Alt, Mod4 to Super and Mod5 to AltGr.
+pub fn held_modifiers(mask: KeyButMask) -> Vec<Modifiers> {
+    let mut mods = Vec::new();
+    if mask.contains(KeyButMask::SHIFT) {
+        mods.push(Modifiers::Shift);
+    }
+    if mask.contains(KeyButMask::CONTROL) {
+        mods.push(Modifiers::Control);
+    }
+    if mask.contains(KeyButMask::MOD1) {
+        mods.push(Modifiers::Alt);
+    }
+    if mask.contains(KeyButMask::MOD4) {
+        mods.push(Modifiers::Meta);
+    }
+    if mask.contains(KeyButMask::MOD5) {
+        mods.push(Modifiers::AltGraph);
+    }
+
+    mods
+}
+
+/// The keysyms of every keycode, as returned by `GetKeyboardMapping`.
+pub struct Keymap {
+    min_keycode: Keycode,
+    keysyms_per_keycode: usize,
+    keysyms: Vec<Keysym>,
+}
+
+impl Keymap {
+    pub fn new(min_keycode: Keycode, keysyms_per_keycode: usize, keysyms: Vec<Keysym>) -> Self {
+        Self {
+            min_keycode,
+            keysyms_per_keycode: keysyms_per_keycode.max(1),
+            keysyms,
+        }
+    }
+
+    pub fn load(display: &Display) -> std::result::Result<Self, String> {
+        let setup = display.connection.setup();
+        let min_keycode = setup.min_keycode;
+        let count = setup.max_keycode - min_keycode + 1;
+
+        let reply = display.connection
+            .get_keyboard_mapping(min_keycode, count)
+            .map_err(|e| e.into())
+            .and_then(|cookie| cookie.reply());
+
+        match reply {
+            Ok(reply) => Ok(Self::new(min_keycode, reply.keysyms_per_keycode as usize, reply.keysyms)),
+            Err(e) => Err(format!("Failed to get the keyboard mapping: {:?}", e)),
+        }
+    }
+
+    fn keycodes(&self) -> impl Iterator<Item = (Keycode, &[Keysym])> {
+        self.keysyms
+            .chunks(self.keysyms_per_keycode)
+            .enumerate()
+            .map(|(i, keysyms)| (self.min_keycode + i as Keycode, keysyms))
+    }
+
+    /// The keycode producing `keysym` in the first group, and whether Shift is needed for it.
+    fn find(&self, keysym: Keysym) -> Option<(Keycode, bool)> {
+        (0..self.keysyms_per_keycode.min(2)).find_map(|column| {
+            self.keycodes()
+                .find(|(_, keysyms)| keysyms[column] == keysym)
+                .map(|(keycode, _)| (keycode, column == 1))
+        })
+    }
+
+    /// The keysym `keycode` produces without modifiers.
+    pub fn keysym(&self, keycode: Keycode) -> Keysym {
+        let index = keycode.saturating_sub(self.min_keycode) as usize * self.keysyms_per_keycode;
+        self.keysyms.get(index).copied().unwrap_or(NO_SYMBOL)
+    }
+
+    /// The keysym `keycode` types with Shift and AltGr held or not, following the core
+    /// protocol's rule that a missing shifted keysym repeats the unshifted one.
+    pub fn typed_keysym(&self, keycode: Keycode, shift: bool, alt_graph: bool) -> Keysym {
+        let index = keycode.saturating_sub(self.min_keycode) as usize * self.keysyms_per_keycode;
+        let column = |column: usize| if column < self.keysyms_per_keycode {
+            self.keysyms.get(index + column).copied().unwrap_or(NO_SYMBOL)
+        } else {
+            NO_SYMBOL
+        };
+
+        // AltGr selects the third level, which Xorg puts in columns 4 and 5.
+        let group = if alt_graph { 4 } else { 0 };
+        match (shift, column(group + 1)) {
+            (true, NO_SYMBOL) | (false, _) => column(group),
+            (true, keysym) => keysym,
+        }
+    }
+
+    fn set(&mut self, keycode: Keycode, keysym: Keysym) {
+        let start = (keycode - self.min_keycode) as usize * self.keysyms_per_keycode;
+        self.keysyms[start..start + self.keysyms_per_keycode].fill(keysym);
+    }
+}
+
+/// Presses or releases `key`. A key missing from the keyboard layout is pressed through a spare
+/// keycode, which gets its keysym back once the key is released.
+pub fn key_event(key: Key, down: bool) -> std::result::Result<(), String> {
+    let keysym = key_info(key).keysym;
+    if keysym == NO_SYMBOL {
+        return Ok(());
+    }
+
+    let display = display()?;
+    let mut keymap = Keymap::load(display)?;
+    if let Some(keycode) = key_keycode(&keymap, key) {
+        return fake_input(if down { KEY_PRESS_EVENT } else { KEY_RELEASE_EVENT }, keycode, 0, 0);
+    }
+
+    let mut held = HELD_REMAPPINGS.lock().unwrap();
+    let index = held.iter().position(|(held_key, _)| *held_key == key);
+    match (down, index) {
+        (true, Some(index)) => fake_input(KEY_PRESS_EVENT, held[index].1.keycodes[0], 0, 0),
+        (true, None) => {
+            let mut remapping = Remapping::default();
+            let keycode = remapping.keycode(display, &mut keymap, keysym)?;
+            held.push((key, remapping));
+            fake_input(KEY_PRESS_EVENT, keycode, 0, 0)
+        }
+        (false, Some(index)) => {
+            let (_, remapping) = held.remove(index);
+            fake_input(KEY_RELEASE_EVENT, remapping.keycodes[0], 0, 0)
+        }
+        (false, None) => Ok(()),
+    }
+}
+
+pub fn typing_inner(text: &str) -> std::result::Result<(), String> {
+    let display = display()?;
+    let mut keymap = Keymap::load(display)?;
+    let mut remapping = Remapping::default();
+
+    for c in text.chars() {
+        let keycodes = char_keycodes(display, &mut keymap, &mut remapping, c)?;
+        char_event(keycodes, true)?;
+        char_event(keycodes, false)?;
+    }
+
+    Ok(())
+}
+
+/// The keycode typing `c`, and the Shift keycode to hold for it if it needs one.
+fn char_keycodes(
+    display: &Display,
+    keymap: &mut Keymap,
+    remapping: &mut Remapping,
+    c: char,
+) -> std::result::Result<(Keycode, Option<Keycode>), String> {
+    let keysym = char_keysym(c);
+    let shift = keymap.find(XK_SHIFT_L).map(|(keycode, _)| keycode);
+
+    Ok(match (keymap.find(keysym), shift) {
+        (Some((keycode, false)), _) => (keycode, None),
+        (Some((keycode, true)), Some(shift)) => (keycode, Some(shift)),
+        _ => (remapping.keycode(display, keymap, keysym)?, None),
+    })
+}
+
+/// Presses Shift, if needed, and the key of a character, or releases them in reverse.
+fn char_event((keycode, shift): (Keycode, Option<Keycode>), down: bool) -> std::result::Result<(), String> {
+    if down {
+        if let Some(shift) = shift {
+            fake_input(KEY_PRESS_EVENT, shift, 0, 0)?;
+        }
+        fake_input(KEY_PRESS_EVENT, keycode, 0, 0)
+    } else {
+        fake_input(KEY_RELEASE_EVENT, keycode, 0, 0)?;
+        match shift {
+            Some(shift) => fake_input(KEY_RELEASE_EVENT, shift, 0, 0),
+            None => Ok(()),
+        }
+    }
+}
+
+/// The spare keycodes one call gave a keysym to, oldest first. Dropping it takes their keysyms
+/// away again, as xdotool does, so that typing leaves the keyboard mapping as it found it. The
+/// key events sent before are queued ahead of the `MappingNotify`, so clients still look them
+/// up with the keysym they were typed with.
+#[derive(Default)]
+pub struct Remapping {
+    keycodes: Vec<Keycode>,
+    keysyms_per_keycode: u8,
+}
+
+impl Remapping {
+    /// Gives `keysym` to a keycode without any, in every column so that modifiers don't matter.
+    /// When no keycode is free, the one this remapping changed longest ago is reused, as its key
+    /// has been released by then.
+    fn keycode(&mut self, display: &Display, keymap: &mut Keymap, keysym: Keysym) -> std::result::Result<Keycode, String> {
+        let mut remapped = REMAPPED_KEYCODES.lock().unwrap();
+
+        let spare = keymap
+            .keycodes()
+            .find(|(keycode, keysyms)| keysyms.iter().all(|it| *it == NO_SYMBOL) && !remapped.contains(keycode))
+            .map(|(keycode, _)| keycode);
+
+        let keycode = match spare {
+            Some(keycode) => {
+                remapped.push(keycode);
+                keycode
+            }
+            None if !self.keycodes.is_empty() => self.keycodes.remove(0),
+            None => return Err(format!("No free keycode to type keysym {:#x}", keysym)),
+        };
+        self.keycodes.push(keycode);
+        self.keysyms_per_keycode = keymap.keysyms_per_keycode as u8;
+
+        let keysyms = vec![keysym; keymap.keysyms_per_keycode];
+        let changed = display.connection
+            .change_keyboard_mapping(1, keycode, self.keysyms_per_keycode, &keysyms)
+            .map_err(|e| e.into())
+            .and_then(|_| display.connection.sync());
+        if let Err(e) = changed {
+            return Err(format!("Failed to change the keyboard mapping: {:?}", e));
+        }
+
+        keymap.set(keycode, keysym);
+
+        Ok(keycode)
+    }
+}
+
+impl Drop for Remapping {
+    fn drop(&mut self) {
+        if self.keycodes.is_empty() {
+            return;
+        }
+
+        if let Ok(display) = display() {
+            let keysyms = vec![NO_SYMBOL; self.keysyms_per_keycode as usize];
+            for keycode in &self.keycodes {
+                let _ = display.connection.change_keyboard_mapping(1, *keycode, self.keysyms_per_keycode, &keysyms);
+            }
+            let _ = display.connection.sync();
+        }
+
+        REMAPPED_KEYCODES.lock().unwrap().retain(|keycode| !self.keycodes.contains(keycode));
+    }
+}
+
+const NO_SYMBOL: Keysym = 0;
+const XK_SHIFT_L: Keysym = 0xffe1;
+/// How much the evdev driver adds to Linux input event codes to make X keycodes.
+const EVDEV_KEYCODE_OFFSET: Keycode = 8;
+
+/// The keysym of a character. Latin-1 characters are their own keysym, everything else uses the
+/// Unicode keysym range. Control characters map to the keys that type them.
+fn char_keysym(c: char) -> Keysym {
+    match c {
+        '\n' | '\r' => 0xff0d,
+        '\t' => 0xff09,
+        '\u{8}' => 0xff08,
+        '\u{1b}' => 0xff1b,
+        '\u{20}'..='\u{7e}' | '\u{a0}'..='\u{ff}' => c as Keysym,
+        _ => 0x0100_0000 | c as Keysym,
+    }
+}
+
+/// The character a keysym types, the reverse of `char_keysym`.
+pub fn keysym_char(keysym: Keysym) -> Option<char> {
+    match keysym {
+        0xff0d | 0xff8d => Some('\n'),
+        0xff09 => Some('\t'),
+        0xff08 => Some('\u{8}'),
+        0xff1b => Some('\u{1b}'),
+        0xffb0..=0xffb9 => char::from_u32(keysym - 0xffb0 + '0' as Keysym),
+        0x20..=0x7e | 0xa0..=0xff => char::from_u32(keysym),
+        0x0100_0000..=0x0110_ffff => char::from_u32(keysym - 0x0100_0000),
+        _ => None,
+    }
+}
+
+/// Whether `keysym` belongs to a modifier or lock key, from `Shift_L` to `Hyper_R`, the ISO
+/// level and group shifts, and `Num_Lock`.
+pub fn is_modifier_keysym(keysym: Keysym) -> bool {
+    matches!(keysym, 0xffe1..=0xffee | 0xfe01..=0xfe13 | 0xff7f)
+}
+
+/// The keycode of `key`: the one producing its keysym without Shift in the keyboard layout, or
+/// else the one at its position on a US keyboard, so that e.g. `Semicolon` still names a key on
+/// layouts without a `;` key. `None` when the keyboard has neither.
+pub fn key_keycode(keymap: &Keymap, key: Key) -> Option<Keycode> {
+    let info = key_info(key);
+    if info.keysym == NO_SYMBOL {
+        return None;
+    }
+
+    match keymap.find(info.keysym) {
+        Some((keycode, false)) => Some(keycode),
+        _ => evdev_keycode(info.evdev).filter(|keycode| keymap.keysym(*keycode) != NO_SYMBOL),
+    }
+}
+
+/// The key of `keycode`, by the keysym it produces or else by its position.
+pub fn keycode_key(keymap: &Keymap, keycode: Keycode) -> Option<Key> {
+    let keysym = keymap.keysym(keycode);
+    if keysym == NO_SYMBOL {
+        return None;
+    }
+
+    key_from_keysym(keysym).or_else(|| key_from_evdev(keycode.checked_sub(EVDEV_KEYCODE_OFFSET)? as u16))
+}
+
+/// The X keycode of a Linux input event code, if it fits in one.
+fn evdev_keycode(evdev: u16) -> Option<Keycode> {
+    match evdev {
+        0 => None,
+        evdev => Keycode::try_from(evdev + EVDEV_KEYCODE_OFFSET as u16).ok(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use x11rb::protocol::xproto::EventMask;
+    use x11rb::protocol::Event;
+    use super::*;
+    use crate::linux::test_client::TestClient;
+
+    /// Not on any common layout.
+    const KATAKANA_TSU: char = 'ツ';
+
+    #[test]
+    #[ignore = "needs an X server, run under xvfb-run"]
+    fn types_characters_of_the_layout() {
+        let client = TestClient::new(EventMask::KEY_PRESS | EventMask::KEY_RELEASE);
+        let keymap = Keymap::load(display().unwrap()).unwrap();
+
+        typing_inner("aB").unwrap();
+
+        let typed: Vec<Keysym> = client
+            .events()
+            .into_iter()
+            .filter_map(|event| match event {
+                Event::KeyPress(event) => Some(keymap.typed_keysym(event.detail, event.state.contains(KeyButMask::SHIFT), false)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(typed, vec![char_keysym('a'), XK_SHIFT_L, char_keysym('B')]);
+    }
+
+    #[test]
+    #[ignore = "needs an X server, run under xvfb-run"]
+    fn lends_a_spare_keycode_to_missing_keysyms() {
+        let _client = TestClient::new(EventMask::NO_EVENT);
+        let display = display().unwrap();
+        let mut keymap = Keymap::load(display).unwrap();
+        let keysym = char_keysym(KATAKANA_TSU);
+        assert_eq!(keymap.find(keysym), None);
+
+        let mut remapping = Remapping::default();
+        let keycode = remapping.keycode(display, &mut keymap, keysym).unwrap();
+        assert_eq!(Keymap::load(display).unwrap().find(keysym), Some((keycode, false)));
+        assert_eq!(*REMAPPED_KEYCODES.lock().unwrap(), vec![keycode]);
+
+        drop(remapping);
+        assert_eq!(Keymap::load(display).unwrap().keysym(keycode), NO_SYMBOL);
+        assert!(REMAPPED_KEYCODES.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    #[ignore = "needs an X server, run under xvfb-run"]
+    fn types_characters_missing_from_the_layout() {
+        let client = TestClient::new(EventMask::KEY_PRESS | EventMask::KEY_RELEASE);
+
+        typing_inner(&KATAKANA_TSU.to_string()).unwrap();
+
+        // The key events come between the mapping notifications lending the keycode out and
+        // taking it back, so the client can look them up in the mapping of their time.
+        let events: Vec<(&str, Keycode)> = client
+            .events()
+            .into_iter()
+            .filter_map(|event| match event {
+                Event::MappingNotify(event) => Some(("mapping", event.first_keycode)),
+                Event::KeyPress(event) => Some(("press", event.detail)),
+                Event::KeyRelease(event) => Some(("release", event.detail)),
+                _ => None,
+            })
+            .collect();
+        let keycode = events.first().map(|it| it.1).unwrap();
+        assert_eq!(events, vec![("mapping", keycode), ("press", keycode), ("release", keycode), ("mapping", keycode)]);
+        assert_eq!(Keymap::load(display().unwrap()).unwrap().keysym(keycode), NO_SYMBOL);
+    }
+}
@@ -0,0 +1,290 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+use lazy_static::lazy_static;
+use napi::bindgen_prelude::*;
+use napi::threadsafe_function::{ErrorStrategy, ThreadsafeFunction, ThreadsafeFunctionCallMode};
+use napi_derive::napi;
+use x11rb::protocol::record;
+use x11rb::protocol::xproto::{KeyButMask, Mapping, KEY_PRESS_EVENT, KEY_RELEASE_EVENT, MAPPING_NOTIFY_EVENT};
+use super::hotstring::{has_hotstrings, hotstring_key_event};
+use super::keyboard::{held_modifiers, keycode_key, Key, Keyboard, Keymap, Modifiers};
+use super::x11::{display, start_recording, stop_recording};
+
+#[napi]
+#[derive(Debug, PartialEq)]
+pub enum KeyboardEventKind {
+    Down,
+    Up,
+}
+
+#[napi(object)]
+#[derive(Debug, Clone)]
+pub struct KeyboardEvent {
+    pub kind: KeyboardEventKind,
+    /// The key, when `Key` has a variant for it.
+    pub key: Option<Key>,
+    /// The layout-dependent key code: the virtual-key code on Windows, the keysym on X11.
+    pub code: u32,
+    /// The layout-independent key code: the scan code on Windows, the keycode on X11.
+    pub scan_code: u32,
+    /// The modifiers held when the event happened, not counting the key itself.
+    pub modifiers: Vec<Modifiers>,
+    /// Milliseconds since the Unix epoch.
+    pub timestamp: f64,
+}
+
+lazy_static! {
+    static ref KEYBOARD_LISTENERS: Mutex<HashMap<u32, ThreadsafeFunction<KeyboardEvent, ErrorStrategy::Fatal>>> = Mutex::new(HashMap::new());
+    static ref KEYBOARD_RECORD_CONTEXT: Mutex<Option<record::Context>> = Mutex::new(None);
+}
+
+static NEXT_LISTENER_ID: AtomicU32 = AtomicU32::new(1);
+
+#[napi]
+impl Keyboard {
+    /// Calls `callback` with every key press and release, including synthetic ones and those of
+    /// registered hotkeys. Returns an id for `unlisten`.
+    #[napi(ts_args_type = "callback: (event: KeyboardEvent) => void")]
+    pub fn listen(callback: JsFunction) -> Result<u32> {
+        let tsfn: ThreadsafeFunction<KeyboardEvent, ErrorStrategy::Fatal> = callback.create_threadsafe_function(0, |ctx| {
+            Ok(vec![ctx.value])
+        })?;
+
+        let id = NEXT_LISTENER_ID.fetch_add(1, Ordering::Relaxed);
+        KEYBOARD_LISTENERS.lock().unwrap().insert(id, tsfn);
+
+        if let Err(e) = start_interception() {
+            KEYBOARD_LISTENERS.lock().unwrap().remove(&id);
+            return Err(Error::new(Status::GenericFailure, e));
+        }
+
+        Ok(id)
+    }
+
+    #[napi]
+    pub fn unlisten(id: u32) {
+        KEYBOARD_LISTENERS.lock().unwrap().remove(&id);
+        stop_interception_if_unused();
+    }
+}
+
+/// Starts recording key events unless it already is, for listeners and hotstrings alike.
+pub fn start_interception() -> std::result::Result<(), String> {
+    let mut context = KEYBOARD_RECORD_CONTEXT.lock().unwrap();
+    if context.is_some() {
+        return Ok(());
+    }
+
+    // `MappingNotify` is recorded too, to follow layout switches and remapped keycodes.
+    let keymap = Mutex::new(Keymap::load(display()?)?);
+    *context = Some(start_recording(KEY_PRESS_EVENT, KEY_RELEASE_EVENT, Some(MAPPING_NOTIFY_EVENT), move |data| {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|it| it.as_secs_f64() * 1000.0)
+            .unwrap_or(0.0);
+
+        let mut keymap = keymap.lock().unwrap();
+        let reload = || display().and_then(Keymap::load).ok();
+        for event in parse_record_data(data, &mut keymap, reload, timestamp) {
+            hotstring_key_event(&event, &keymap);
+
+            let listeners = KEYBOARD_LISTENERS.lock().unwrap();
+            for listener in listeners.values() {
+                listener.call(event.clone(), ThreadsafeFunctionCallMode::NonBlocking);
+            }
+        }
+    })?);
+
+    Ok(())
+}
+
+/// Stops recording key events once no listener and no hotstring is left.
+pub fn stop_interception_if_unused() {
+    if !KEYBOARD_LISTENERS.lock().unwrap().is_empty() || has_hotstrings() {
+        return;
+    }
+
+    if let Some(context) = KEYBOARD_RECORD_CONTEXT.lock().unwrap().take() {
+        stop_recording(context);
+    }
+}
+
+/// Turns the raw 32-byte core events intercepted by XRecord into keyboard events. Keys are
+/// named after the keysym they produce without modifiers, or after their position when that
+/// keysym names no key.
+///
+/// A `MappingNotify` for the keyboard or the modifiers replaces `keymap` with `reload` before the
+/// next key event, or at the end of the batch. The server sends one to every client, so a whole
+/// run of them costs a single reload.
+fn parse_record_data(
+    data: &[u8],
+    keymap: &mut Keymap,
+    reload: impl Fn() -> Option<Keymap>,
+    timestamp: f64,
+) -> Vec<KeyboardEvent> {
+    let mut events = Vec::new();
+    let mut stale = false;
+
+    for event in data.chunks_exact(32) {
+        let kind = match event[0] & 0x7F {
+            KEY_PRESS_EVENT => KeyboardEventKind::Down,
+            KEY_RELEASE_EVENT => KeyboardEventKind::Up,
+            MAPPING_NOTIFY_EVENT => {
+                let request = Mapping::from(event[4]);
+                stale |= request == Mapping::KEYBOARD || request == Mapping::MODIFIER;
+                continue;
+            }
+            _ => continue,
+        };
+
+        if std::mem::take(&mut stale) {
+            if let Some(reloaded) = reload() {
+                *keymap = reloaded;
+            }
+        }
+
+        let keycode = event[1];
+        let state = u16::from_ne_bytes([event[28], event[29]]);
+        let keysym = keymap.keysym(keycode);
+
+        let mask = KeyButMask::from(state);
+        let mut modifiers = held_modifiers(mask);
+        if mask.contains(KeyButMask::LOCK) {
+            modifiers.push(Modifiers::CapsLock);
+        }
+        if mask.contains(KeyButMask::MOD2) {
+            modifiers.push(Modifiers::NumLock);
+        }
+
+        events.push(KeyboardEvent {
+            kind,
+            key: keycode_key(keymap, keycode),
+            code: keysym,
+            scan_code: keycode as u32,
+            modifiers,
+            timestamp,
+        });
+    }
+
+    if stale {
+        if let Some(reloaded) = reload() {
+            *keymap = reloaded;
+        }
+    }
+
+    events
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SHIFT_L: u32 = 0xffe1;
+
+    /// Keycode 10 types "1" and "!", 38 "a" and "A", 50 is the left Shift.
+    fn keymap(a: u32) -> Keymap {
+        let mut keysyms = vec![0; 2 * 248];
+        let mut set = |keycode: usize, unshifted: u32, shifted: u32| {
+            keysyms[(keycode - 8) * 2] = unshifted;
+            keysyms[(keycode - 8) * 2 + 1] = shifted;
+        };
+        set(10, '1' as u32, '!' as u32);
+        set(38, a, a - 0x20);
+        set(50, SHIFT_L, SHIFT_L);
+
+        Keymap::new(8, 2, keysyms)
+    }
+
+    /// A core event record: the type, the detail (also at byte 4, where `MappingNotify` has its
+    /// request) and the state at byte 28.
+    fn record(kind: u8, detail: u8, state: u16) -> [u8; 32] {
+        let mut event = [0u8; 32];
+        event[0] = kind;
+        event[1] = detail;
+        event[4] = detail;
+        event[28..30].copy_from_slice(&state.to_ne_bytes());
+        event
+    }
+
+    /// The kind, key, code, scan code and modifiers of an event.
+    type Parsed = (KeyboardEventKind, Option<Key>, u32, u32, Vec<Modifiers>);
+
+    fn parse(records: &[[u8; 32]], keymap: &mut Keymap) -> Vec<Parsed> {
+        parse_record_data(&records.concat(), keymap, || Some(self::keymap('q' as u32)), 12.5)
+            .into_iter()
+            .map(|it| {
+                assert_eq!(it.timestamp, 12.5);
+                (it.kind, it.key, it.code, it.scan_code, it.modifiers)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn parses_key_presses_and_releases() {
+        let records = [
+            record(KEY_PRESS_EVENT, 38, 0),
+            record(KEY_RELEASE_EVENT | 0x80, 38, 0),
+            record(KEY_PRESS_EVENT, 50, 0),
+            record(KEY_PRESS_EVENT, 10, 0x0001 | 0x0004),
+        ];
+
+        assert_eq!(parse(&records, &mut keymap('a' as u32)), vec![
+            (KeyboardEventKind::Down, Some(Key::A), 'a' as u32, 38, vec![]),
+            (KeyboardEventKind::Up, Some(Key::A), 'a' as u32, 38, vec![]),
+            (KeyboardEventKind::Down, Some(Key::LeftShift), SHIFT_L, 50, vec![]),
+            (KeyboardEventKind::Down, Some(Key::D1), '1' as u32, 10, vec![Modifiers::Shift, Modifiers::Control]),
+        ]);
+    }
+
+    #[test]
+    fn reads_the_modifiers_and_locks() {
+        // Shift, Lock, Control, Mod1 (Alt), Mod2 (Num Lock), Mod4 (Super) and Mod5 (AltGr).
+        let records = [record(KEY_PRESS_EVENT, 38, 0x01 | 0x02 | 0x04 | 0x08 | 0x10 | 0x40 | 0x80)];
+
+        let events = parse(&records, &mut keymap('a' as u32));
+        assert_eq!(events[0].4, vec![
+            Modifiers::Shift,
+            Modifiers::Control,
+            Modifiers::Alt,
+            Modifiers::Meta,
+            Modifiers::AltGraph,
+            Modifiers::CapsLock,
+            Modifiers::NumLock,
+        ]);
+    }
+
+    #[test]
+    fn skips_other_events() {
+        // MotionNotify, ButtonPress, and a truncated key press.
+        let mut data = [record(6, 0, 0), record(4, 1, 0), record(KEY_PRESS_EVENT, 38, 0)].concat();
+        data.truncate(32 * 2 + 16);
+
+        assert!(parse_record_data(&data, &mut keymap('a' as u32), || None, 0.0).is_empty());
+    }
+
+    #[test]
+    fn reloads_the_keymap_on_mapping_notify() {
+        let mut keymap = keymap('a' as u32);
+
+        // A pointer mapping change doesn't concern keys.
+        let pointer = [record(MAPPING_NOTIFY_EVENT, 2, 0), record(KEY_PRESS_EVENT, 38, 0)];
+        assert_eq!(parse(&pointer, &mut keymap)[0].1, Some(Key::A));
+
+        // One notification per client, and the first key after them is named after the new layout.
+        let keyboard = [
+            record(KEY_PRESS_EVENT, 38, 0),
+            record(MAPPING_NOTIFY_EVENT, 1, 0),
+            record(MAPPING_NOTIFY_EVENT, 1, 0),
+            record(KEY_RELEASE_EVENT, 38, 0),
+        ];
+        let events = parse(&keyboard, &mut keymap);
+        assert_eq!((events[0].1, events[1].1), (Some(Key::A), Some(Key::Q)));
+
+        // A notification ending a batch still applies to the next one.
+        let mut keymap = self::keymap('a' as u32);
+        parse(&[record(MAPPING_NOTIFY_EVENT, 0, 0)], &mut keymap);
+        assert_eq!(keymap.keysym(38), 'q' as u32);
+    }
+}
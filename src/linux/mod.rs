@@ -0,0 +1,11 @@
+pub mod cursor;
+pub mod hotkey;
+pub mod hotstring;
+pub mod keyboard;
+pub mod keyboard_listener;
+pub mod mouse;
+pub mod mouse_listener;
+pub mod screen;
+#[cfg(test)]
+mod test_client;
+mod x11;
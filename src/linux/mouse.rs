@@ -0,0 +1,453 @@
+use std::sync::Mutex;
+use lazy_static::lazy_static;
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+use x11rb::protocol::xinput::ConnectionExt as _;
+use x11rb::protocol::xproto::{ConnectionExt as _, BUTTON_PRESS_EVENT, BUTTON_RELEASE_EVENT, MOTION_NOTIFY_EVENT};
+use crate::geometry::Point;
+use crate::mouse_path::{generate_mouse_path, relative_mouse_steps, MoveOptions};
+use crate::utils::{handle_input, handle_result};
+use super::x11::{display, fake_input};
+
+#[napi]
+#[derive(Debug)]
+pub enum MouseButton {
+    Left,
+    Right,
+    Middle,
+    /// The "back" side button.
+    X1,
+    /// The "forward" side button.
+    X2,
+}
+
+#[napi]
+#[derive(Debug)]
+pub enum ScrollAxis {
+    Vertical,
+    Horizontal,
+}
+
+#[napi(object)]
+pub struct DragOptions {
+    /// The button held during the drag, `Left` by default.
+    pub button: Option<MouseButton>,
+    /// Milliseconds to wait after pressing and before releasing the button, 50 by default.
+    pub hold: Option<u32>,
+    /// How to move between the two points, as in `moveSmooth`.
+    pub movement: Option<MoveOptions>,
+}
+
+/// Wheel delta of one notch, matching Windows' `WHEEL_DELTA`.
+const WHEEL_DELTA: i32 = 120;
+
+const DEFAULT_DRAG_HOLD: u32 = 50;
+
+/// X11 has no system-wide double-click time; toolkits default to 400ms (GTK) or less.
+const DOUBLE_CLICK_TIME: u32 = 400;
+
+lazy_static! {
+    /// Vertical and horizontal deltas too small to make a whole notch yet.
+    static ref SCROLL_REMAINDER: Mutex<[i32; 2]> = Mutex::new([0, 0]);
+}
+
+#[napi]
+pub struct Mouse {
+
+}
+
+#[napi]
+impl Mouse {
+    #[napi(js_name = "move")]
+    pub async fn mouse_move(x: i32, y: i32) -> Result<()> {
+        let task = tokio::spawn(async move {
+            mouse_move_inner(x, y)
+        });
+
+        handle_input(task).await
+    }
+
+    /// Moves to (`x`, `y`) through intermediate points instead of jumping there, see
+    /// `generateMousePath`.
+    #[napi]
+    pub async fn move_smooth(x: i32, y: i32, options: Option<MoveOptions>) -> Result<()> {
+        let task = tokio::spawn(async move {
+            move_smooth_inner(x, y, options.unwrap_or_default()).await
+        });
+
+        handle_input(task).await
+    }
+
+    /// Moves by (`dx`, `dy`) pixels as relative motion, which games that capture the mouse read
+    /// instead of the cursor position.
+    #[napi]
+    pub async fn move_relative(dx: i32, dy: i32) -> Result<()> {
+        let task = tokio::spawn(async move {
+            move_relative_inner(dx, dy)
+        });
+
+        handle_input(task).await
+    }
+
+    /// Moves by (`dx`, `dy`) in small relative steps along a path like `moveSmooth`'s.
+    #[napi]
+    pub async fn move_relative_smooth(dx: i32, dy: i32, options: Option<MoveOptions>) -> Result<()> {
+        let task = tokio::spawn(async move {
+            let options = options.unwrap_or_default();
+            let interval = options.interval();
+
+            let start = tokio::time::Instant::now();
+            for (i, (step_x, step_y)) in relative_mouse_steps(dx, dy, Some(options)).into_iter().enumerate() {
+                if step_x != 0 || step_y != 0 {
+                    move_relative_inner(step_x, step_y)?;
+                }
+                tokio::time::sleep_until(start + interval * (i as u32 + 1)).await;
+            }
+
+            Ok(())
+        });
+
+        handle_input(task).await
+    }
+
+    #[napi]
+    pub async fn press(button: MouseButton) -> Result<()> {
+        let task = tokio::spawn(async move {
+            fake_input(BUTTON_PRESS_EVENT, button_number(button), 0, 0)
+        });
+
+        handle_input(task).await
+    }
+
+    #[napi]
+    pub async fn release(button: MouseButton) -> Result<()> {
+        let task = tokio::spawn(async move {
+            fake_input(BUTTON_RELEASE_EVENT, button_number(button), 0, 0)
+        });
+
+        handle_input(task).await
+    }
+
+    #[napi]
+    pub async fn click(button: MouseButton, x: i32, y: i32) -> Result<()> {
+        let task = tokio::spawn(async move {
+            let button = button_number(button);
+
+            mouse_move_inner(x, y)?;
+            fake_input(BUTTON_PRESS_EVENT, button, 0, 0)?;
+            fake_input(BUTTON_RELEASE_EVENT, button, 0, 0)
+        });
+
+        handle_input(task).await
+    }
+
+    /// Turns the wheel by whole notches. Positive values scroll down (or right), like the deltas
+    /// of DOM wheel events.
+    #[napi]
+    pub async fn scroll(notches: i32, axis: Option<ScrollAxis>) -> Result<()> {
+        let task = tokio::spawn(async move {
+            scroll_inner(notches, axis.unwrap_or(ScrollAxis::Vertical))
+        });
+
+        handle_input(task).await
+    }
+
+    /// Turns the wheel by `delta`, where 120 is one notch. X11 only knows whole notches, so
+    /// smaller deltas add up until they make one.
+    #[napi]
+    pub async fn scroll_delta(delta: i32, axis: Option<ScrollAxis>) -> Result<()> {
+        let task = tokio::spawn(async move {
+            let axis = axis.unwrap_or(ScrollAxis::Vertical);
+            let notches = {
+                let mut remainder = SCROLL_REMAINDER.lock().unwrap();
+                let remainder = &mut remainder[axis as usize];
+                *remainder = remainder.saturating_add(delta);
+
+                let notches = *remainder / WHEEL_DELTA;
+                *remainder %= WHEEL_DELTA;
+                notches
+            };
+
+            scroll_inner(notches, axis)
+        });
+
+        handle_input(task).await
+    }
+
+    /// Presses `button` at `from`, moves to `to` along a smooth path and releases it there.
+    #[napi]
+    pub async fn drag(from: Point, to: Point, options: Option<DragOptions>) -> Result<()> {
+        let task = tokio::spawn(async move {
+            let (button, hold, movement) = match options {
+                Some(options) => (options.button, options.hold, options.movement),
+                None => (None, None, None),
+            };
+            let button = button_number(button.unwrap_or(MouseButton::Left));
+            let hold = std::time::Duration::from_millis(hold.unwrap_or(DEFAULT_DRAG_HOLD) as u64);
+
+            mouse_move_inner(from.x, from.y)?;
+            fake_input(BUTTON_PRESS_EVENT, button, 0, 0)?;
+            tokio::time::sleep(hold).await;
+
+            let moved = move_smooth_inner(to.x, to.y, movement.unwrap_or_default()).await;
+            if moved.is_ok() {
+                tokio::time::sleep(hold).await;
+            }
+
+            // Never leave the button stuck down.
+            fake_input(BUTTON_RELEASE_EVENT, button, 0, 0)?;
+            moved
+        });
+
+        handle_input(task).await
+    }
+
+    #[napi]
+    pub async fn double_click(button: MouseButton, x: i32, y: i32) -> Result<()> {
+        let task = tokio::spawn(async move {
+            multi_click_inner(button, x, y, 2, None).await
+        });
+
+        handle_input(task).await
+    }
+
+    /// Clicks `count` times in a row at (`x`, `y`), `interval` ms apart. The interval defaults to
+    /// a fifth of the double-click time and is kept below it, so that applications see a single
+    /// double (triple, ...) click.
+    #[napi]
+    pub async fn multi_click(button: MouseButton, x: i32, y: i32, count: u32, interval: Option<u32>) -> Result<()> {
+        let task = tokio::spawn(async move {
+            multi_click_inner(button, x, y, count, interval).await
+        });
+
+        handle_input(task).await
+    }
+
+    /// The longest time between two clicks making a double click, in milliseconds.
+    #[napi]
+    pub async fn get_double_click_time() -> Result<u32> {
+        Ok(DOUBLE_CLICK_TIME)
+    }
+
+    #[napi]
+    pub async fn get_position() -> Result<Point> {
+        let task = tokio::spawn(async move {
+            get_mouse_position_inner()
+        });
+
+        handle_result(task).await
+    }
+
+    /// Whether `button` is held down right now, by the user or by `press`.
+    #[napi]
+    pub async fn is_pressed(button: MouseButton) -> Result<bool> {
+        let task = tokio::spawn(async move {
+            is_pressed_inner(button)
+        });
+
+        handle_result(task).await
+    }
+}
+
+/// X11 core pointer button numbers.
+fn button_number(button: MouseButton) -> u8 {
+    match button {
+        MouseButton::Left => 1,
+        MouseButton::Middle => 2,
+        MouseButton::Right => 3,
+        MouseButton::X1 => 8,
+        MouseButton::X2 => 9,
+    }
+}
+
+/// X11 scrolls by clicking buttons 4 (up), 5 (down), 6 (left) and 7 (right), once per notch.
+fn scroll_inner(notches: i32, axis: ScrollAxis) -> std::result::Result<(), String> {
+    let button = match (axis, notches < 0) {
+        (ScrollAxis::Vertical, true) => 4,
+        (ScrollAxis::Vertical, false) => 5,
+        (ScrollAxis::Horizontal, true) => 6,
+        (ScrollAxis::Horizontal, false) => 7,
+    };
+
+    for _ in 0..notches.unsigned_abs() {
+        fake_input(BUTTON_PRESS_EVENT, button, 0, 0)?;
+        fake_input(BUTTON_RELEASE_EVENT, button, 0, 0)?;
+    }
+
+    Ok(())
+}
+
+fn get_mouse_position_inner() -> std::result::Result<Point, String> {
+    let display = display()?;
+
+    let reply = match display.connection.query_pointer(display.root) {
+        Ok(cookie) => cookie.reply(),
+        Err(e) => return Err(format!("Failed to query pointer: {:?}", e)),
+    };
+
+    match reply {
+        Ok(reply) => Ok(Point::new(reply.root_x as i32, reply.root_y as i32)),
+        Err(e) => Err(format!("Failed to query pointer: {:?}", e)),
+    }
+}
+
+/// Reads the button state through XInput 2, as the core protocol only reports buttons 1 to 5.
+fn is_pressed_inner(button: MouseButton) -> std::result::Result<bool, String> {
+    let display = display()?;
+    let connection = &display.connection;
+
+    // XInput 2 requests are refused until the client announced the version it speaks.
+    let version = connection
+        .xinput_xi_query_version(2, 0)
+        .map_err(|e| e.into())
+        .and_then(|cookie| cookie.reply());
+    if let Err(e) = version {
+        return Err(format!("Failed to query the XInput extension: {:?}", e));
+    }
+
+    let pointer = connection
+        .xinput_xi_get_client_pointer(x11rb::NONE)
+        .map_err(|e| e.into())
+        .and_then(|cookie| cookie.reply());
+    let device = match pointer {
+        Ok(reply) => reply.deviceid,
+        Err(e) => return Err(format!("Failed to query pointer: {:?}", e)),
+    };
+
+    let state = connection
+        .xinput_xi_query_pointer(display.root, device)
+        .map_err(|e| e.into())
+        .and_then(|cookie| cookie.reply());
+    let buttons = match state {
+        Ok(reply) => reply.buttons,
+        Err(e) => return Err(format!("Failed to query pointer: {:?}", e)),
+    };
+
+    let number = button_number(button) as usize;
+    Ok(buttons.get(number / 32).is_some_and(|word| word & (1 << (number % 32)) != 0))
+}
+
+async fn move_smooth_inner(x: i32, y: i32, options: MoveOptions) -> std::result::Result<(), String> {
+    let interval = options.interval();
+    let path = generate_mouse_path(get_mouse_position_inner()?, Point::new(x, y), Some(options));
+
+    let start = tokio::time::Instant::now();
+    for (i, point) in path.iter().enumerate() {
+        mouse_move_inner(point.x, point.y)?;
+        tokio::time::sleep_until(start + interval * (i as u32 + 1)).await;
+    }
+
+    Ok(())
+}
+
+async fn multi_click_inner(
+    button: MouseButton,
+    x: i32,
+    y: i32,
+    count: u32,
+    interval: Option<u32>,
+) -> std::result::Result<(), String> {
+    let button = button_number(button);
+    let interval = interval
+        .unwrap_or(DOUBLE_CLICK_TIME / 5)
+        .min(DOUBLE_CLICK_TIME.saturating_sub(1));
+
+    mouse_move_inner(x, y)?;
+    for i in 0..count {
+        if i > 0 {
+            tokio::time::sleep(std::time::Duration::from_millis(interval as u64)).await;
+        }
+
+        fake_input(BUTTON_PRESS_EVENT, button, 0, 0)?;
+        fake_input(BUTTON_RELEASE_EVENT, button, 0, 0)?;
+    }
+
+    Ok(())
+}
+
+fn mouse_move_inner(x: i32, y: i32) -> std::result::Result<(), String> {
+    fake_input(MOTION_NOTIFY_EVENT, 0, x, y)
+}
+
+/// Moves the pointer relative to where it is, as XTest motion with `detail` set.
+fn move_relative_inner(dx: i32, dy: i32) -> std::result::Result<(), String> {
+    fake_input(MOTION_NOTIFY_EVENT, 1, dx, dy)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+    use x11rb::protocol::xproto::EventMask;
+    use x11rb::protocol::Event;
+    use super::*;
+    use crate::linux::test_client::TestClient;
+
+    fn position() -> (i32, i32) {
+        let point = get_mouse_position_inner().unwrap();
+        (point.x, point.y)
+    }
+
+    /// The button presses and releases `client` received, with where they happened.
+    fn buttons(client: &TestClient) -> Vec<(&'static str, u8, i16, i16)> {
+        client
+            .events()
+            .into_iter()
+            .filter_map(|event| match event {
+                Event::ButtonPress(event) => Some(("press", event.detail, event.event_x, event.event_y)),
+                Event::ButtonRelease(event) => Some(("release", event.detail, event.event_x, event.event_y)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    #[ignore = "needs an X server, run under xvfb-run"]
+    fn reads_back_the_pointer_position() {
+        let client = TestClient::new(EventMask::NO_EVENT);
+
+        mouse_move_inner(120, 45).unwrap();
+        assert_eq!(position(), (120, 45));
+
+        mouse_move_inner(-50, client.height + 50).unwrap();
+        assert_eq!(position(), (0, client.height - 1));
+        mouse_move_inner(client.width + 50, -50).unwrap();
+        assert_eq!(position(), (client.width - 1, 0));
+    }
+
+    #[test]
+    #[ignore = "needs an X server, run under xvfb-run"]
+    fn clicks_reach_the_window_under_the_pointer() {
+        let client = TestClient::new(EventMask::BUTTON_PRESS | EventMask::BUTTON_RELEASE);
+
+        mouse_move_inner(30, 40).unwrap();
+        fake_input(BUTTON_PRESS_EVENT, button_number(MouseButton::Right), 0, 0).unwrap();
+        fake_input(BUTTON_RELEASE_EVENT, button_number(MouseButton::Right), 0, 0).unwrap();
+        assert_eq!(buttons(&client), vec![("press", 3, 30, 40), ("release", 3, 30, 40)]);
+
+        scroll_inner(-2, ScrollAxis::Vertical).unwrap();
+        scroll_inner(1, ScrollAxis::Horizontal).unwrap();
+        let wheel: Vec<u8> = buttons(&client).into_iter().filter(|it| it.0 == "press").map(|it| it.1).collect();
+        assert_eq!(wheel, vec![4, 4, 7]);
+    }
+
+    #[test]
+    #[ignore = "needs an X server, run under xvfb-run"]
+    fn moves_relative_to_the_pointer() {
+        let client = TestClient::new(EventMask::POINTER_MOTION);
+        mouse_move_inner(100, 100).unwrap();
+        client.events();
+
+        // Steps below the acceleration threshold, which the server would otherwise multiply.
+        for _ in 0..8 {
+            move_relative_inner(2, -1).unwrap();
+            std::thread::sleep(Duration::from_millis(20));
+        }
+        assert_eq!(position(), (116, 92));
+
+        let last = client.events().into_iter().rev().find_map(|event| match event {
+            Event::MotionNotify(event) => Some((event.event_x, event.event_y)),
+            _ => None,
+        });
+        assert_eq!(last, Some((116, 92)));
+    }
+}
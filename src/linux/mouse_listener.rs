@@ -0,0 +1,197 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+use lazy_static::lazy_static;
+use napi::bindgen_prelude::*;
+use napi::threadsafe_function::{ErrorStrategy, ThreadsafeFunction, ThreadsafeFunctionCallMode};
+use napi_derive::napi;
+use x11rb::protocol::record;
+use x11rb::protocol::xproto::{BUTTON_PRESS_EVENT, BUTTON_RELEASE_EVENT, MOTION_NOTIFY_EVENT};
+use super::mouse::{Mouse, MouseButton, ScrollAxis};
+use super::x11::{start_recording, stop_recording};
+
+/// Wheel delta of one notch, matching Windows' `WHEEL_DELTA`.
+const WHEEL_DELTA: i32 = 120;
+
+#[napi]
+#[derive(Debug, PartialEq)]
+pub enum MouseEventKind {
+    Move,
+    Press,
+    Release,
+    Wheel,
+}
+
+#[napi(object)]
+#[derive(Debug, Clone)]
+pub struct MouseEvent {
+    pub kind: MouseEventKind,
+    /// Pointer position on the virtual desktop when the event happened.
+    pub x: i32,
+    pub y: i32,
+    /// The button pressed or released, for `Press` and `Release`.
+    pub button: Option<MouseButton>,
+    /// For `Wheel`, 120 per notch, positive when scrolling down (or right).
+    pub delta: Option<i32>,
+    pub axis: Option<ScrollAxis>,
+    /// Milliseconds since the Unix epoch.
+    pub timestamp: f64,
+}
+
+lazy_static! {
+    static ref MOUSE_LISTENERS: Mutex<HashMap<u32, ThreadsafeFunction<MouseEvent, ErrorStrategy::Fatal>>> = Mutex::new(HashMap::new());
+    static ref MOUSE_RECORD_CONTEXT: Mutex<Option<record::Context>> = Mutex::new(None);
+}
+
+static NEXT_LISTENER_ID: AtomicU32 = AtomicU32::new(1);
+
+#[napi]
+impl Mouse {
+    /// Calls `callback` with every move, button press or release and wheel turn of the mouse,
+    /// including synthetic ones. Returns an id for `unlisten`.
+    #[napi(ts_args_type = "callback: (event: MouseEvent) => void")]
+    pub fn listen(callback: JsFunction) -> Result<u32> {
+        let tsfn: ThreadsafeFunction<MouseEvent, ErrorStrategy::Fatal> = callback.create_threadsafe_function(0, |ctx| {
+            Ok(vec![ctx.value])
+        })?;
+
+        let id = NEXT_LISTENER_ID.fetch_add(1, Ordering::Relaxed);
+        MOUSE_LISTENERS.lock().unwrap().insert(id, tsfn);
+
+        let mut context = MOUSE_RECORD_CONTEXT.lock().unwrap();
+        if context.is_none() {
+            let started = start_recording(BUTTON_PRESS_EVENT, MOTION_NOTIFY_EVENT, None, |data| {
+                let timestamp = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map(|it| it.as_secs_f64() * 1000.0)
+                    .unwrap_or(0.0);
+
+                let listeners = MOUSE_LISTENERS.lock().unwrap();
+                for event in parse_record_data(data, timestamp) {
+                    for listener in listeners.values() {
+                        listener.call(event.clone(), ThreadsafeFunctionCallMode::NonBlocking);
+                    }
+                }
+            });
+
+            match started {
+                Ok(started) => *context = Some(started),
+                Err(e) => {
+                    MOUSE_LISTENERS.lock().unwrap().remove(&id);
+                    return Err(Error::new(Status::GenericFailure, e));
+                }
+            }
+        }
+
+        Ok(id)
+    }
+
+    #[napi]
+    pub fn unlisten(id: u32) {
+        let is_empty = {
+            let mut listeners = MOUSE_LISTENERS.lock().unwrap();
+            listeners.remove(&id);
+            listeners.is_empty()
+        };
+
+        if is_empty {
+            if let Some(context) = MOUSE_RECORD_CONTEXT.lock().unwrap().take() {
+                stop_recording(context);
+            }
+        }
+    }
+}
+
+/// Turns the raw 32-byte core events intercepted by XRecord into mouse events. X11 reports wheel
+/// notches as presses and releases of buttons 4 to 7; only the presses become `Wheel` events.
+fn parse_record_data(data: &[u8], timestamp: f64) -> Vec<MouseEvent> {
+    data.chunks_exact(32)
+        .filter_map(|event| {
+            let kind = event[0] & 0x7F;
+            let detail = event[1];
+            let x = i16::from_ne_bytes([event[20], event[21]]) as i32;
+            let y = i16::from_ne_bytes([event[22], event[23]]) as i32;
+
+            let mut result = MouseEvent {
+                kind: MouseEventKind::Move,
+                x,
+                y,
+                button: None,
+                delta: None,
+                axis: None,
+                timestamp,
+            };
+
+            match (kind, detail) {
+                (MOTION_NOTIFY_EVENT, _) => {}
+                (BUTTON_PRESS_EVENT, 4..=7) => {
+                    result.kind = MouseEventKind::Wheel;
+                    result.delta = Some(if detail % 2 == 0 { -WHEEL_DELTA } else { WHEEL_DELTA });
+                    result.axis = Some(if detail <= 5 { ScrollAxis::Vertical } else { ScrollAxis::Horizontal });
+                }
+                (BUTTON_PRESS_EVENT | BUTTON_RELEASE_EVENT, _) => {
+                    result.kind = if kind == BUTTON_PRESS_EVENT { MouseEventKind::Press } else { MouseEventKind::Release };
+                    result.button = Some(match detail {
+                        1 => MouseButton::Left,
+                        2 => MouseButton::Middle,
+                        3 => MouseButton::Right,
+                        8 => MouseButton::X1,
+                        9 => MouseButton::X2,
+                        _ => return None,
+                    });
+                }
+                _ => return None,
+            }
+
+            Some(result)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc::channel;
+    use std::time::Duration;
+    use x11rb::protocol::xproto::EventMask;
+    use super::*;
+    use crate::linux::test_client::TestClient;
+    use crate::linux::x11::fake_input;
+
+    #[test]
+    #[ignore = "needs an X server, run under xvfb-run"]
+    fn records_synthetic_events() {
+        let _client = TestClient::new(EventMask::NO_EVENT);
+        // Away from where the recorded move goes, which the server would skip otherwise.
+        fake_input(MOTION_NOTIFY_EVENT, 0, 10, 10).unwrap();
+
+        let (sender, receiver) = channel();
+        let context = start_recording(BUTTON_PRESS_EVENT, MOTION_NOTIFY_EVENT, None, move |data| {
+            for event in parse_record_data(data, 0.0) {
+                let _ = sender.send(event);
+            }
+        })
+        .unwrap();
+        // The data connection enables the context on its own thread.
+        std::thread::sleep(Duration::from_millis(200));
+
+        fake_input(MOTION_NOTIFY_EVENT, 0, 50, 60).unwrap();
+        fake_input(BUTTON_PRESS_EVENT, 1, 0, 0).unwrap();
+        fake_input(BUTTON_RELEASE_EVENT, 1, 0, 0).unwrap();
+        fake_input(BUTTON_PRESS_EVENT, 5, 0, 0).unwrap();
+        fake_input(BUTTON_RELEASE_EVENT, 5, 0, 0).unwrap();
+
+        let mut events = Vec::new();
+        while let Ok(event) = receiver.recv_timeout(Duration::from_millis(500)) {
+            events.push(format!("{:?} {:?} {:?} ({}, {})", event.kind, event.button, event.delta, event.x, event.y));
+        }
+        stop_recording(context);
+
+        assert_eq!(events, vec![
+            "Move None None (50, 60)",
+            "Press Some(Left) None (50, 60)",
+            "Release Some(Left) None (50, 60)",
+            "Wheel None Some(120) (50, 60)",
+        ]);
+    }
+}
@@ -0,0 +1,52 @@
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+use x11rb::connection::Connection;
+use x11rb::protocol::randr::ConnectionExt as _;
+use crate::desktop::{Monitor, VirtualDesktop};
+use crate::geometry::Rect;
+use crate::utils::handle_result;
+use super::x11::display;
+
+/// X11 has no per-monitor scaling, so every monitor has a `scaleFactor` of 1.
+#[napi]
+pub async fn get_virtual_desktop() -> Result<VirtualDesktop> {
+    let task = tokio::spawn(async move {
+        get_virtual_desktop_inner()
+    });
+
+    handle_result(task).await
+}
+
+fn get_virtual_desktop_inner() -> std::result::Result<VirtualDesktop, String> {
+    let display = display()?;
+    let screen = &display.connection.setup().roots[display.screen];
+    let bounds = Rect::new(0, 0, screen.width_in_pixels as i32, screen.height_in_pixels as i32);
+
+    let reply = display
+        .connection
+        .randr_get_monitors(display.root, true)
+        .ok()
+        .and_then(|cookie| cookie.reply().ok());
+
+    let mut monitors: Vec<Monitor> = match reply {
+        Some(reply) => reply
+            .monitors
+            .iter()
+            .map(|monitor| {
+                let left = monitor.x as i32;
+                let top = monitor.y as i32;
+                let bounds = Rect::new(left, top, left + monitor.width as i32, top + monitor.height as i32);
+
+                Monitor::new(bounds, 1.0, monitor.primary)
+            })
+            .collect(),
+        None => Vec::new(),
+    };
+
+    // Without RandR, the root window is the only monitor.
+    if monitors.is_empty() {
+        monitors.push(Monitor::new(bounds.clone(), 1.0, true));
+    }
+
+    Ok(VirtualDesktop { bounds, monitors })
+}
@@ -0,0 +1,84 @@
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+use x11rb::connection::Connection;
+use x11rb::protocol::xproto::{ConnectionExt as _, CreateWindowAux, EventMask, InputFocus, Window, WindowClass};
+use x11rb::protocol::Event;
+use x11rb::rust_connection::RustConnection;
+use x11rb::wrapper::ConnectionExt as _;
+use x11rb::{COPY_DEPTH_FROM_PARENT, CURRENT_TIME};
+
+/// Tests against the X server share its pointer, keyboard and mapping, so they take turns.
+static X_SERVER: Mutex<()> = Mutex::new(());
+
+/// How long `events` waits for the next event before it considers them all delivered.
+const EVENT_QUIET_TIME: Duration = Duration::from_millis(300);
+
+/// An application on a connection of its own, with a focused window covering the screen that
+/// receives the input Sophia sends. These tests need an X server, e.g.
+/// `xvfb-run -a cargo test -- --ignored`.
+pub struct TestClient {
+    pub connection: RustConnection,
+    pub window: Window,
+    pub width: i32,
+    pub height: i32,
+    _turn: MutexGuard<'static, ()>,
+}
+
+impl TestClient {
+    pub fn new(event_mask: EventMask) -> Self {
+        let turn = X_SERVER.lock().unwrap_or_else(|e| e.into_inner());
+
+        let (connection, screen_num) = x11rb::connect(None).expect("Failed to connect to the X server");
+        let screen = &connection.setup().roots[screen_num];
+        let (root, width, height) = (screen.root, screen.width_in_pixels, screen.height_in_pixels);
+
+        let window = connection.generate_id().unwrap();
+        // Override-redirect, so that a window manager neither moves nor decorates it.
+        let aux = CreateWindowAux::new()
+            .override_redirect(1)
+            .event_mask(event_mask | EventMask::STRUCTURE_NOTIFY);
+        connection
+            .create_window(COPY_DEPTH_FROM_PARENT, window, root, 0, 0, width, height, 0, WindowClass::INPUT_OUTPUT, 0, &aux)
+            .unwrap();
+        connection.map_window(window).unwrap();
+        connection.flush().unwrap();
+
+        while !matches!(connection.wait_for_event().unwrap(), Event::MapNotify(_)) {}
+
+        connection.set_input_focus(InputFocus::POINTER_ROOT, window, CURRENT_TIME).unwrap();
+        connection.sync().unwrap();
+
+        Self {
+            connection,
+            window,
+            width: width as i32,
+            height: height as i32,
+            _turn: turn,
+        }
+    }
+
+    /// The events received so far, once none arrived for a while.
+    pub fn events(&self) -> Vec<Event> {
+        let mut events = Vec::new();
+        let mut last = Instant::now();
+
+        while last.elapsed() < EVENT_QUIET_TIME {
+            match self.connection.poll_for_event().unwrap() {
+                Some(event) => {
+                    events.push(event);
+                    last = Instant::now();
+                }
+                None => std::thread::sleep(Duration::from_millis(10)),
+            }
+        }
+
+        events
+    }
+}
+
+impl Drop for TestClient {
+    fn drop(&mut self) {
+        let _ = self.connection.destroy_window(self.window);
+        let _ = self.connection.sync();
+    }
+}
@@ -0,0 +1,167 @@
+use std::cell::RefCell;
+use lazy_static::lazy_static;
+use x11rb::connection::{Connection, RequestConnection};
+use x11rb::protocol::record::{self, ConnectionExt as _};
+use x11rb::protocol::xproto::{Window, KEY_PRESS_EVENT, KEY_RELEASE_EVENT};
+use x11rb::protocol::xtest::{self, ConnectionExt as _};
+use x11rb::rust_connection::RustConnection;
+use x11rb::wrapper::ConnectionExt as _;
+
+/// A connection to the X server named by `$DISPLAY`, shared by every input function.
+pub struct Display {
+    pub connection: RustConnection,
+    pub screen: usize,
+    pub root: Window,
+}
+
+/// XRecord category of intercepted protocol data coming from the server, i.e. events.
+const RECORD_FROM_SERVER: u8 = 0;
+
+lazy_static! {
+    static ref DISPLAY: std::result::Result<Display, String> = open_display();
+}
+
+thread_local! {
+    /// The (type, keycode) of the key events `fake_input` sent on this thread while
+    /// `journal_key_events` runs.
+    static KEY_EVENT_JOURNAL: RefCell<Option<Vec<(u8, u8)>>> = const { RefCell::new(None) };
+}
+
+fn open_display() -> std::result::Result<Display, String> {
+    let (connection, screen_num) = match x11rb::connect(None) {
+        Ok(result) => result,
+        Err(e) => return Err(format!("Failed to connect to the X server: {:?}", e)),
+    };
+
+    match connection.extension_information(xtest::X11_EXTENSION_NAME) {
+        Ok(Some(_)) => {}
+        Ok(None) => return Err("The X server does not support the XTEST extension".to_string()),
+        Err(e) => return Err(format!("Failed to query the XTEST extension: {:?}", e)),
+    }
+
+    let root = connection.setup().roots[screen_num].root;
+
+    Ok(Display {
+        connection,
+        screen: screen_num,
+        root,
+    })
+}
+
+pub fn display() -> std::result::Result<&'static Display, String> {
+    DISPLAY.as_ref().map_err(|e| e.clone())
+}
+
+/// Injects a synthetic input event and waits for the server to process it, so that state read
+/// back afterwards (e.g. the pointer position) already reflects it.
+pub fn fake_input(type_: u8, detail: u8, x: i32, y: i32) -> std::result::Result<(), String> {
+    let display = display()?;
+    let x = x.clamp(i16::MIN as i32, i16::MAX as i32) as i16;
+    let y = y.clamp(i16::MIN as i32, i16::MAX as i32) as i16;
+
+    if let Err(e) = display.connection.xtest_fake_input(type_, detail, x11rb::CURRENT_TIME, display.root, x, y, 0) {
+        return Err(format!("Failed to send input: {:?}", e));
+    }
+
+    if type_ == KEY_PRESS_EVENT || type_ == KEY_RELEASE_EVENT {
+        KEY_EVENT_JOURNAL.with(|journal| {
+            if let Some(journal) = journal.borrow_mut().as_mut() {
+                journal.push((type_, detail));
+            }
+        });
+    }
+
+    match display.connection.sync() {
+        Ok(_) => Ok(()),
+        Err(e) => Err(format!("Failed to send input: {:?}", e)),
+    }
+}
+
+/// Runs `f` and returns the (type, keycode) of every key event it sent with `fake_input`.
+pub fn journal_key_events<T>(f: impl FnOnce() -> T) -> (T, Vec<(u8, u8)>) {
+    KEY_EVENT_JOURNAL.with(|journal| *journal.borrow_mut() = Some(Vec::new()));
+    let result = f();
+    let sent = KEY_EVENT_JOURNAL.with(|journal| journal.borrow_mut().take()).unwrap_or_default();
+
+    (result, sent)
+}
+
+/// Starts intercepting the core device events `first..=last` of all clients with XRecord, plus
+/// the `delivered` events the server sends to clients, and calls `handler` with the raw 32-byte
+/// events of every batch. The events are read on a second connection, which XRecord keeps busy
+/// until the context is disabled by `stop_recording`.
+pub fn start_recording(
+    first: u8,
+    last: u8,
+    delivered: Option<u8>,
+    handler: impl Fn(&[u8]) + Send + 'static,
+) -> std::result::Result<record::Context, String> {
+    let display = display()?;
+    let connection = &display.connection;
+
+    match connection.extension_information(record::X11_EXTENSION_NAME) {
+        Ok(Some(_)) => {}
+        Ok(None) => return Err("The X server does not support the RECORD extension".to_string()),
+        Err(e) => return Err(format!("Failed to query the RECORD extension: {:?}", e)),
+    }
+
+    let context = match connection.generate_id() {
+        Ok(id) => id,
+        Err(e) => return Err(format!("Failed to create a record context: {:?}", e)),
+    };
+
+    let range = record::Range {
+        device_events: record::Range8 {
+            first,
+            last,
+        },
+        delivered_events: match delivered {
+            Some(event) => record::Range8 { first: event, last: event },
+            None => record::Range8::default(),
+        },
+        ..Default::default()
+    };
+
+    let created = connection
+        .record_create_context(context, 0, &[record::CS::ALL_CLIENTS.into()], &[range])
+        .map_err(|e| e.into())
+        .and_then(|cookie| cookie.check());
+    if let Err(e) = created {
+        return Err(format!("Failed to create a record context: {:?}", e));
+    }
+
+    let (data_connection, _) = match x11rb::connect(None) {
+        Ok(result) => result,
+        Err(e) => return Err(format!("Failed to connect to the X server: {:?}", e)),
+    };
+
+    std::thread::spawn(move || {
+        let replies = match data_connection.record_enable_context(context) {
+            Ok(replies) => replies,
+            Err(_) => return,
+        };
+
+        for reply in replies {
+            let reply = match reply {
+                Ok(reply) => reply,
+                Err(_) => break,
+            };
+
+            if reply.category != RECORD_FROM_SERVER {
+                continue;
+            }
+
+            handler(&reply.data);
+        }
+    });
+
+    Ok(context)
+}
+
+pub fn stop_recording(context: record::Context) {
+    if let Ok(display) = display() {
+        let _ = display.connection.record_disable_context(context);
+        let _ = display.connection.record_free_context(context);
+        let _ = display.connection.sync();
+    }
+}
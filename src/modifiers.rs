@@ -0,0 +1,57 @@
+use napi_derive::napi;
+use crate::key::Key;
+
+/// Modifier keys and lock states. The left and right ones only serve hotkeys, which then only
+/// fire while the modifier is held on that side.
+#[napi]
+#[derive(Debug, PartialEq)]
+pub enum Modifiers {
+    Alt = 0x01,
+    AltGraph = 0x2,
+    CapsLock = 0x4,
+    Control = 0x8,
+    Fn = 0x10,
+    FnLock = 0x20,
+    Meta = 0x40,
+    NumLock = 0x80,
+    ScrollLock = 0x100,
+    Shift = 0x200,
+    Symbol = 0x400,
+    SymbolLock = 0x800,
+    Hyper = 0x1000,
+    Super = 0x2000,
+    LeftShift = 0x4000,
+    RightShift = 0x8000,
+    LeftControl = 0x10000,
+    RightControl = 0x20000,
+    LeftAlt = 0x40000,
+    RightAlt = 0x80000,
+    LeftSuper = 0x100000,
+    RightSuper = 0x200000,
+}
+
+/// The modifiers of one side, with the modifier of either side and the key of theirs.
+pub const SIDED_MODIFIERS: &[(Modifiers, Modifiers, Key)] = &[
+    (Modifiers::LeftShift, Modifiers::Shift, Key::LeftShift),
+    (Modifiers::RightShift, Modifiers::Shift, Key::RightShift),
+    (Modifiers::LeftControl, Modifiers::Control, Key::LeftControl),
+    (Modifiers::RightControl, Modifiers::Control, Key::RightControl),
+    (Modifiers::LeftAlt, Modifiers::Alt, Key::LeftAlt),
+    (Modifiers::RightAlt, Modifiers::Alt, Key::RightAlt),
+    (Modifiers::LeftSuper, Modifiers::Super, Key::LeftWin),
+    (Modifiers::RightSuper, Modifiers::Super, Key::RightWin),
+];
+
+impl Modifiers {
+    /// `Shift` for `LeftShift` and `RightShift` and so on, the modifier itself for the others.
+    pub fn either_side(self) -> Modifiers {
+        SIDED_MODIFIERS
+            .iter()
+            .find(|(sided, _, _)| *sided == self)
+            .map_or(self, |(_, either, _)| *either)
+    }
+
+    pub fn is_sided(self) -> bool {
+        self.either_side() != self
+    }
+}
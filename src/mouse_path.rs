@@ -0,0 +1,340 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use napi_derive::napi;
+use crate::geometry::Point;
+
+const DEFAULT_DURATION: u32 = 300;
+const DEFAULT_RATE: u32 = 120;
+const MAX_STEPS: u64 = 10_000;
+
+const BEZIER_MAX_BEND: f64 = 0.25;
+
+const WIND_MOUSE_GRAVITY: f64 = 9.0;
+const WIND_MOUSE_WIND: f64 = 3.0;
+const WIND_MOUSE_MAX_STEP: f64 = 15.0;
+const WIND_MOUSE_TARGET_AREA: f64 = 12.0;
+const WIND_MOUSE_MAX_ITERATIONS: usize = 10_000;
+const OVERSHOOT_MIN_DISTANCE: f64 = 100.0;
+
+#[napi]
+#[derive(Debug)]
+pub enum MousePath {
+    /// A straight line at constant speed.
+    Linear,
+    /// A straight line that speeds up, then slows down before the target.
+    Eased,
+    /// A randomly bent cubic Bézier curve, eased like `Eased`.
+    Bezier,
+    /// A wandering path pulled towards the target (the WindMouse algorithm), overshooting it
+    /// slightly on long moves before settling.
+    WindMouse,
+}
+
+#[napi(object)]
+#[derive(Debug, Clone, Default)]
+pub struct MoveOptions {
+    /// Shape of the path, `Eased` by default.
+    pub path: Option<MousePath>,
+    /// Total time of the movement in milliseconds, 300 by default.
+    pub duration: Option<u32>,
+    /// Intermediate moves per second, 120 by default.
+    pub rate: Option<u32>,
+    /// Makes the random paths reproducible.
+    pub seed: Option<u32>,
+}
+
+impl MoveOptions {
+    /// Time between two consecutive points of the path generated with these options.
+    pub fn interval(&self) -> Duration {
+        let duration = self.duration.unwrap_or(DEFAULT_DURATION);
+        Duration::from_millis(duration as u64) / step_count(self)
+    }
+}
+
+/// Generates the points a smooth move from `from` to `to` goes through, without `from` and
+/// ending exactly on `to`. A move of `duration` ms at `rate` moves per second has
+/// `duration * rate / 1000` points (at least one, at most 10 000).
+#[napi]
+pub fn generate_mouse_path(from: Point, to: Point, options: Option<MoveOptions>) -> Vec<Point> {
+    let options = options.unwrap_or_default();
+    let steps = step_count(&options) as usize;
+    let mut rng = Rng::new(options.seed);
+
+    let start = (from.x as f64, from.y as f64);
+    let end = (to.x as f64, to.y as f64);
+
+    let mut points: Vec<Point> = match options.path.unwrap_or(MousePath::Eased) {
+        MousePath::Linear => (1..=steps)
+            .map(|i| to_point(lerp(start, end, i as f64 / steps as f64)))
+            .collect(),
+        MousePath::Eased => (1..=steps)
+            .map(|i| to_point(lerp(start, end, ease_in_out(i as f64 / steps as f64))))
+            .collect(),
+        MousePath::Bezier => bezier_path(start, end, steps, &mut rng),
+        MousePath::WindMouse => wind_mouse_path(start, end, steps, &mut rng),
+    };
+
+    if let Some(last) = points.last_mut() {
+        *last = to;
+    }
+
+    points
+}
+
+/// Splits a relative move by (`dx`, `dy`) into the per-step offsets along the path
+/// `generate_mouse_path` gives for it. The offsets add up to exactly (`dx`, `dy`).
+pub fn relative_mouse_steps(dx: i32, dy: i32, options: Option<MoveOptions>) -> Vec<(i32, i32)> {
+    let mut previous = Point::new(0, 0);
+
+    generate_mouse_path(Point::new(0, 0), Point::new(dx, dy), options)
+        .into_iter()
+        .map(|point| {
+            let step = (point.x - previous.x, point.y - previous.y);
+            previous = point;
+            step
+        })
+        .collect()
+}
+
+fn step_count(options: &MoveOptions) -> u32 {
+    let duration = options.duration.unwrap_or(DEFAULT_DURATION);
+    let rate = options.rate.unwrap_or(DEFAULT_RATE);
+
+    ((duration as u64 * rate as u64 + 500) / 1000).clamp(1, MAX_STEPS) as u32
+}
+
+fn lerp(from: (f64, f64), to: (f64, f64), t: f64) -> (f64, f64) {
+    (from.0 + (to.0 - from.0) * t, from.1 + (to.1 - from.1) * t)
+}
+
+fn ease_in_out(t: f64) -> f64 {
+    if t < 0.5 {
+        4.0 * t * t * t
+    } else {
+        1.0 - (2.0 - 2.0 * t).powi(3) / 2.0
+    }
+}
+
+fn to_point((x, y): (f64, f64)) -> Point {
+    Point::new(x.round() as i32, y.round() as i32)
+}
+
+fn bezier_path(start: (f64, f64), end: (f64, f64), steps: usize, rng: &mut Rng) -> Vec<Point> {
+    let dx = end.0 - start.0;
+    let dy = end.1 - start.1;
+
+    // Control points sit along the line, pushed sideways by up to a quarter of its length.
+    let mut control = |from: f64, to: f64| {
+        let along = lerp(start, end, rng.range(from, to));
+        let bend = rng.range(-BEZIER_MAX_BEND, BEZIER_MAX_BEND);
+        (along.0 - dy * bend, along.1 + dx * bend)
+    };
+    let first = control(0.2, 0.4);
+    let second = control(0.6, 0.8);
+
+    (1..=steps)
+        .map(|i| {
+            let t = ease_in_out(i as f64 / steps as f64);
+            let u = 1.0 - t;
+            let a = u * u * u;
+            let b = 3.0 * u * u * t;
+            let c = 3.0 * u * t * t;
+            let d = t * t * t;
+
+            to_point((
+                a * start.0 + b * first.0 + c * second.0 + d * end.0,
+                a * start.1 + b * first.1 + c * second.1 + d * end.1,
+            ))
+        })
+        .collect()
+}
+
+fn wind_mouse_path(start: (f64, f64), end: (f64, f64), steps: usize, rng: &mut Rng) -> Vec<Point> {
+    let dx = end.0 - start.0;
+    let dy = end.1 - start.1;
+    let distance = (dx * dx + dy * dy).sqrt();
+
+    let mut trail = vec![start];
+    if distance >= OVERSHOOT_MIN_DISTANCE {
+        let past = 1.0 + rng.range(0.03, 0.08);
+        let aside = rng.range(-0.03, 0.03);
+        let overshoot = (start.0 + dx * past - dy * aside, start.1 + dy * past + dx * aside);
+
+        wind_mouse(&mut trail, overshoot, rng);
+    }
+    wind_mouse(&mut trail, end, rng);
+
+    resample(&trail, steps)
+}
+
+/// Walks from the last point of `trail` to `end`, pushing every position on the way.
+///
+/// Each step adds a constant pull towards the target ("gravity") and a random, slowly varying
+/// push ("wind") to the velocity, which is capped to a random fraction of the maximum step.
+/// Near the target the wind dies down and the step shrinks, so the walk settles on it.
+fn wind_mouse(trail: &mut Vec<(f64, f64)>, end: (f64, f64), rng: &mut Rng) {
+    let sqrt3 = 3f64.sqrt();
+    let sqrt5 = 5f64.sqrt();
+
+    let (mut x, mut y) = *trail.last().unwrap();
+    let (mut velocity_x, mut velocity_y) = (0.0, 0.0);
+    let (mut wind_x, mut wind_y) = (0.0, 0.0);
+    let mut max_step = WIND_MOUSE_MAX_STEP;
+
+    for _ in 0..WIND_MOUSE_MAX_ITERATIONS {
+        let distance = ((end.0 - x).powi(2) + (end.1 - y).powi(2)).sqrt();
+        if distance < 1.0 {
+            break;
+        }
+
+        let wind = WIND_MOUSE_WIND.min(distance);
+        if distance >= WIND_MOUSE_TARGET_AREA {
+            wind_x = wind_x / sqrt3 + rng.range(-1.0, 1.0) * wind / sqrt5;
+            wind_y = wind_y / sqrt3 + rng.range(-1.0, 1.0) * wind / sqrt5;
+        } else {
+            wind_x /= sqrt3;
+            wind_y /= sqrt3;
+            max_step = if max_step < 3.0 { rng.range(3.0, 6.0) } else { max_step / sqrt5 };
+        }
+
+        velocity_x += wind_x + WIND_MOUSE_GRAVITY * (end.0 - x) / distance;
+        velocity_y += wind_y + WIND_MOUSE_GRAVITY * (end.1 - y) / distance;
+
+        let speed = (velocity_x * velocity_x + velocity_y * velocity_y).sqrt();
+        if speed > max_step {
+            let clipped = max_step / 2.0 + rng.range(0.0, max_step / 2.0);
+            velocity_x = velocity_x / speed * clipped;
+            velocity_y = velocity_y / speed * clipped;
+        }
+
+        x += velocity_x;
+        y += velocity_y;
+        trail.push((x, y));
+    }
+
+    trail.push(end);
+}
+
+/// Picks `steps` points spread along `trail` by an eased fraction of its length, so the
+/// resulting move has a fixed number of points but keeps the shape of the walk.
+fn resample(trail: &[(f64, f64)], steps: usize) -> Vec<Point> {
+    let mut lengths = Vec::with_capacity(trail.len());
+    let mut total = 0.0;
+    lengths.push(0.0);
+    for pair in trail.windows(2) {
+        total += ((pair[1].0 - pair[0].0).powi(2) + (pair[1].1 - pair[0].1).powi(2)).sqrt();
+        lengths.push(total);
+    }
+
+    let mut segment = 0;
+    (1..=steps)
+        .map(|i| {
+            let length = total * ease_in_out(i as f64 / steps as f64);
+            while segment + 2 < trail.len() && lengths[segment + 1] < length {
+                segment += 1;
+            }
+
+            let segment_length = lengths[segment + 1] - lengths[segment];
+            let t = if segment_length > 0.0 {
+                ((length - lengths[segment]) / segment_length).clamp(0.0, 1.0)
+            } else {
+                1.0
+            };
+
+            to_point(lerp(trail[segment], trail[segment + 1], t))
+        })
+        .collect()
+}
+
+/// A small xorshift generator; the paths only need to look random, not be unpredictable.
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: Option<u32>) -> Self {
+        let seed = match seed {
+            Some(seed) => seed as u64,
+            None => SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|it| it.as_nanos() as u64)
+                .unwrap_or(0),
+        };
+
+        // Spread the seed over all bits (SplitMix64), xorshift must not start from 0.
+        let mut z = seed.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        Self((z ^ (z >> 31)) | 1)
+    }
+
+    fn next(&mut self) -> f64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        (self.0 >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    pub fn range(&mut self, from: f64, to: f64) -> f64 {
+        from + (to - from) * self.next()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PATHS: [MousePath; 4] = [MousePath::Linear, MousePath::Eased, MousePath::Bezier, MousePath::WindMouse];
+
+    fn options(path: MousePath, duration: u32, rate: u32, seed: u32) -> Option<MoveOptions> {
+        Some(MoveOptions { path: Some(path), duration: Some(duration), rate: Some(rate), seed: Some(seed) })
+    }
+
+    fn coordinates(points: &[Point]) -> Vec<(i32, i32)> {
+        points.iter().map(|it| (it.x, it.y)).collect()
+    }
+
+    #[test]
+    fn ends_on_the_target() {
+        for path in PATHS {
+            for to in [Point::new(0, 0), Point::new(3, -2), Point::new(1500, 900), Point::new(-640, 200)] {
+                let points = generate_mouse_path(Point::new(10, 20), to.clone(), options(path, 250, 100, 7));
+                assert_eq!(points.last().map(|it| (it.x, it.y)), Some((to.x, to.y)), "{:?}", path);
+            }
+        }
+    }
+
+    #[test]
+    fn has_one_point_per_interval() {
+        for path in PATHS {
+            let count = |duration, rate| generate_mouse_path(Point::new(0, 0), Point::new(400, 300), options(path, duration, rate, 1)).len();
+
+            assert_eq!(count(300, 120), 36);
+            assert_eq!(count(1000, 60), 60);
+            assert_eq!(count(2, 60), 1);
+            assert_eq!(count(0, 0), 1);
+            assert_eq!(count(u32::MAX, u32::MAX), MAX_STEPS as usize);
+        }
+
+        let options = MoveOptions { duration: Some(500), rate: Some(50), ..Default::default() };
+        assert_eq!(options.interval(), Duration::from_millis(20));
+    }
+
+    #[test]
+    fn seeded_paths_are_reproducible() {
+        for path in [MousePath::Bezier, MousePath::WindMouse] {
+            let generate = |seed| coordinates(&generate_mouse_path(Point::new(0, 0), Point::new(800, 450), options(path, 400, 120, seed)));
+
+            assert_eq!(generate(42), generate(42));
+            assert_ne!(generate(42), generate(43));
+        }
+    }
+
+    #[test]
+    fn relative_steps_add_up_to_the_move() {
+        for path in PATHS {
+            for (dx, dy) in [(400, 0), (-35, 17), (1, 1), (0, 0), (-1200, -700)] {
+                let steps = relative_mouse_steps(dx, dy, options(path, 300, 120, 5));
+                let sum = steps.iter().fold((0, 0), |sum, step| (sum.0 + step.0, sum.1 + step.1));
+                assert_eq!(sum, (dx, dy), "{:?}", path);
+            }
+        }
+    }
+}
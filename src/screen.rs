@@ -1,17 +1,148 @@
+use std::ops::Deref;
+use std::sync::{Arc, OnceLock};
 use napi::bindgen_prelude::*;
 use napi_derive::napi;
+use rayon::prelude::*;
 use crate::geometry::Point;
 use crate::utils::handle_result;
 
+/// Pixel storage shared between JS and Rust.
+///
+/// Pixels Rust produced, e.g. a screenshot, stay `Owned` until JS first reads them, when they move
+/// into a `Buffer` that every later access returns. The other variants are views over memory
+/// owned by a JS value. Cloning only bumps a reference count and never copies the pixels.
+#[derive(Clone)]
+pub enum PixelData {
+    Owned(Arc<Vec<u8>>),
+    Buffer(Buffer),
+    Uint8Array(Uint8Array),
+    Uint8ClampedArray(Uint8ClampedArray),
+}
+
+// SAFETY: the async search functions only read through `PixelData`, handing out `&[u8]` views so
+// an `ImageData` can be borrowed across worker threads. The only writes are the drawing methods,
+// which run synchronously on the JS thread. Mutating the pixels while a search is running is the
+// caller's responsibility, as with any shared `Buffer`.
+unsafe impl Sync for PixelData {}
+
+impl PixelData {
+    pub fn as_slice(&self) -> &[u8] {
+        match self {
+            PixelData::Owned(data) => data,
+            PixelData::Buffer(buffer) => buffer.as_ref(),
+            PixelData::Uint8Array(array) => array.as_ref(),
+            PixelData::Uint8ClampedArray(array) => array.as_ref(),
+        }
+    }
+
+    pub fn as_mut_slice(&mut self) -> &mut [u8] {
+        match self {
+            // Copies the pixels only while a clone, e.g. of a running search, still reads them.
+            PixelData::Owned(data) => Arc::make_mut(data).as_mut_slice(),
+            PixelData::Buffer(buffer) => buffer.as_mut(),
+            PixelData::Uint8Array(array) => array.as_mut(),
+            PixelData::Uint8ClampedArray(array) => array.as_mut(),
+        }
+    }
+}
+
+impl Deref for PixelData {
+    type Target = [u8];
+
+    fn deref(&self) -> &Self::Target {
+        self.as_slice()
+    }
+}
+
+impl From<Vec<u8>> for PixelData {
+    fn from(data: Vec<u8>) -> Self {
+        PixelData::Owned(Arc::new(data))
+    }
+}
+
+impl std::fmt::Debug for PixelData {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let kind = match self {
+            PixelData::Owned(_) => "Owned",
+            PixelData::Buffer(_) => "Buffer",
+            PixelData::Uint8Array(_) => "Uint8Array",
+            PixelData::Uint8ClampedArray(_) => "Uint8ClampedArray",
+        };
+
+        write!(f, "{}({} bytes)", kind, self.len())
+    }
+}
+
 #[napi]
 #[derive(Debug, Clone)]
 pub struct ImageData {
-    pub data: Vec<u8>,
+    pub(crate) data: PixelData,
     pub width: u32,
     pub height: u32,
     pub pixel_width: u8,
 }
 
+#[napi]
+impl ImageData {
+    /// Wraps existing pixel memory without copying it, e.g. a `Buffer` or the `Uint8ClampedArray`
+    /// of a canvas `ImageData`. `pixelWidth` defaults to 4 (RGBA).
+    #[napi(constructor)]
+    pub fn new(
+        data: Either3<Buffer, Uint8ClampedArray, Uint8Array>,
+        width: u32,
+        height: u32,
+        pixel_width: Option<u8>,
+    ) -> Result<Self> {
+        let pixel_width = pixel_width.unwrap_or(4);
+        let data = match data {
+            Either3::A(buffer) => PixelData::Buffer(buffer),
+            Either3::B(array) => PixelData::Uint8ClampedArray(array),
+            Either3::C(array) => PixelData::Uint8Array(array),
+        };
+
+        if pixel_width < 3 {
+            return Err(Error::new(
+                Status::InvalidArg,
+                format!("Unsupported pixel width: {}", pixel_width),
+            ));
+        }
+
+        let expected = width as usize * height as usize * pixel_width as usize;
+        if data.len() < expected {
+            return Err(Error::new(
+                Status::InvalidArg,
+                format!("Expected at least {} bytes of pixel data, got {}", expected, data.len()),
+            ));
+        }
+
+        Ok(Self {
+            data,
+            width,
+            height,
+            pixel_width,
+        })
+    }
+
+    /// The underlying pixel memory, shared with (not copied from) the native side.
+    #[napi(getter)]
+    pub fn data(&mut self, env: Env) -> Result<Either3<Buffer, Uint8ClampedArray, Uint8Array>> {
+        let buffer = match &mut self.data {
+            // Keeps a reference to the JS buffer, so that the next access returns the same one.
+            PixelData::Owned(data) => unsafe {
+                let data = Arc::try_unwrap(std::mem::take(data)).unwrap_or_else(|data| data.as_ref().clone());
+                let value = Buffer::to_napi_value(env.raw(), data.into())?;
+                Buffer::from_napi_value(env.raw(), value)?
+            },
+            PixelData::Buffer(buffer) => return Ok(Either3::A(buffer.clone())),
+            PixelData::Uint8ClampedArray(array) => return Ok(Either3::B(array.clone())),
+            PixelData::Uint8Array(array) => return Ok(Either3::C(array.clone())),
+        };
+
+        self.data = PixelData::Buffer(buffer.clone());
+        Ok(Either3::A(buffer))
+    }
+}
+
 #[napi(object)]
 #[derive(Debug, Clone)]
 pub struct Color {
@@ -27,6 +158,30 @@ pub const MAGENTA: Color = Color {
     b: 255,
 };
 
+#[napi]
+pub enum SearchStrategy {
+    /// Compares the target against every position of the source.
+    Exhaustive,
+    /// Finds candidates on downscaled copies of both images, then verifies them at full
+    /// resolution. Returns the same matches as `Exhaustive`, but is much faster on large sources.
+    Pyramid,
+}
+
+#[napi(object)]
+pub struct SearchOptions {
+    pub variant: Option<i32>,
+    pub trans_color: Option<Color>,
+    pub strategy: Option<SearchStrategy>,
+}
+
+#[napi(object)]
+#[derive(Debug, Clone)]
+pub struct SearchResult {
+    pub point: Option<Point>,
+    /// How closely the match resembles the target, from 0 to 1 (identical). 0 when not found.
+    pub score: f64,
+}
+
 #[napi]
 pub async fn read_image_data(path: String) -> Result<ImageData> {
     let task = tokio::spawn(async move {
@@ -38,10 +193,10 @@ pub async fn read_image_data(path: String) -> Result<ImageData> {
         let width = img.width();
         let height = img.height();
         let pixel_width = img.color().bytes_per_pixel();
-        let data = img.as_bytes().to_vec();
+        let data = img.into_bytes();
 
         Ok(ImageData {
-            data,
+            data: data.into(),
             width,
             height,
             pixel_width,
@@ -53,17 +208,16 @@ pub async fn read_image_data(path: String) -> Result<ImageData> {
 
 #[napi]
 pub async fn save_image_data(path: String, image_data: &ImageData) -> Result<()> {
-    let path = path.clone();
     let image_data = image_data.clone();
 
     let task = tokio::spawn(async move {
         let image_buffer =
-            match image::RgbaImage::from_raw(image_data.width, image_data.height, image_data.data) {
+            match image::ImageBuffer::<image::Rgba<u8>, _>::from_raw(image_data.width, image_data.height, image_data.data.as_slice()) {
                 Some(buffer) => buffer,
                 None => return Err("Failed to create image buffer".to_string()),
             };
 
-        match image::DynamicImage::ImageRgba8(image_buffer).save(path) {
+        match image_buffer.save(path) {
             Ok(_) => Ok(()),
             Err(e) => Err(format!("Failed to save image: {:?}", e)),
         }
@@ -78,13 +232,17 @@ pub async fn image_search(
     target: &ImageData,
     variant: Option<i32>,
     trans_color: Option<Color>,
+    strategy: Option<SearchStrategy>,
 ) -> Result<Option<Point>> {
     let variant = variant.unwrap_or(0);
+    let strategy = strategy.unwrap_or(SearchStrategy::Exhaustive);
     let source = source.clone();
     let target = target.clone();
 
     let task = tokio::spawn(async move {
-        Ok(if let Some(trans_color) = trans_color {
+        Ok(if let SearchStrategy::Pyramid = strategy {
+            pyramid_search_inner(&source, &target, variant, trans_color, true).into_iter().next()
+        } else if let Some(trans_color) = trans_color {
             image_search_trans_inner(&source, &target, variant, trans_color)
         } else {
             image_search_inner(&source, &target, variant)
@@ -100,13 +258,17 @@ pub async fn multiple_image_search(
     target: &ImageData,
     variant: Option<i32>,
     trans_color: Option<Color>,
+    strategy: Option<SearchStrategy>,
 ) -> Result<Vec<Point>> {
     let variant = variant.unwrap_or(0);
+    let strategy = strategy.unwrap_or(SearchStrategy::Exhaustive);
     let source = source.clone();
     let target = target.clone();
 
     let task = tokio::spawn(async move {
-        Ok(if let Some(trans_color) = trans_color {
+        Ok(if let SearchStrategy::Pyramid = strategy {
+            pyramid_search_inner(&source, &target, variant, trans_color, false)
+        } else if let Some(trans_color) = trans_color {
             multiple_image_search_trans_inner(&source, &target, variant, trans_color)
         } else {
             multiple_image_search_inner(&source, &target, variant)
@@ -116,6 +278,139 @@ pub async fn multiple_image_search(
     handle_result(task).await
 }
 
+/// Searches `source` for each of `targets`, returning one result per target in the same order.
+///
+/// The targets are searched in parallel against the same source, which is only prepared once
+/// (e.g. downscaled once per level with `SearchStrategy.Pyramid`), so this is much cheaper than
+/// calling `imageSearch` for every target.
+#[napi(ts_return_type = "Promise<Array<SearchResult>>")]
+pub fn image_search_many(
+    env: Env,
+    source: &ImageData,
+    targets: Vec<ClassInstance<ImageData>>,
+    options: Option<SearchOptions>,
+) -> Result<Object> {
+    let (variant, trans_color, strategy) = match options {
+        Some(options) => (options.variant, options.trans_color, options.strategy),
+        None => (None, None, None),
+    };
+    let variant = variant.unwrap_or(0);
+    let strategy = strategy.unwrap_or(SearchStrategy::Exhaustive);
+    let source = source.clone();
+    let targets: Vec<ImageData> = targets.iter().map(|target| (**target).clone()).collect();
+
+    env.spawn_future(async move {
+        let task = tokio::spawn(async move {
+            Ok(image_search_many_inner(&source, &targets, variant, trans_color, strategy))
+        });
+
+        handle_result(task).await
+    })
+}
+
+fn image_search_many_inner(
+    source: &ImageData,
+    targets: &[ImageData],
+    variant: i32,
+    trans_color: Option<Color>,
+    strategy: SearchStrategy,
+) -> Vec<SearchResult> {
+    let trans_color = trans_color.as_ref();
+
+    // Every target sharing a pyramid factor shares the same downscaled source.
+    let mut coarse_sources: Vec<(u32, Downscaled)> = Vec::new();
+    if let SearchStrategy::Pyramid = strategy {
+        let mut factors: Vec<u32> = targets
+            .iter()
+            .filter(|target| target.width <= source.width && target.height <= source.height)
+            .map(pyramid_factor)
+            .filter(|&factor| factor > 1)
+            .collect();
+        factors.sort_unstable();
+        factors.dedup();
+
+        coarse_sources = factors
+            .into_par_iter()
+            .map(|factor| (factor, downscale(source, factor, 0, 0, None)))
+            .collect();
+    }
+
+    // The rest compare at full resolution against the source unpacked once, one cell per pixel,
+    // when the first of them needs it.
+    let full_source = OnceLock::new();
+
+    targets
+        .par_iter()
+        .map(|target| {
+            let coarse_source = coarse_sources
+                .iter()
+                .find(|(factor, _)| *factor == pyramid_factor(target));
+
+            let point = if target.width > source.width || target.height > source.height {
+                None
+            } else if let Some((factor, coarse_source)) = coarse_source {
+                pyramid_search_coarse(source, coarse_source, *factor, target, variant, trans_color, true)
+                    .into_iter()
+                    .next()
+            } else {
+                let full_target = downscale(target, 1, 0, 0, trans_color);
+                let full_source = full_source.get_or_init(|| downscale(source, 1, 0, 0, None));
+                downscaled_search_first(full_source, &full_target, variant)
+            };
+
+            let score = match &point {
+                Some(point) => match_score(source, target, point, trans_color),
+                None => 0.0,
+            };
+
+            SearchResult { point, score }
+        })
+        .collect()
+}
+
+/// One minus the mean absolute channel difference between `target` placed at `point` and
+/// `source`, ignoring transparent target pixels.
+fn match_score(source: &ImageData, target: &ImageData, point: &Point, trans_color: Option<&Color>) -> f64 {
+    let source_pixels = source.data.as_slice();
+    let target_pixels = target.data.as_slice();
+
+    let source_pixel_width = source.pixel_width as u32;
+    let target_pixel_width = target.pixel_width as u32;
+
+    let mut difference = 0u64;
+    let mut compared = 0u64;
+
+    for ty in 0..target.height {
+        for tx in 0..target.width {
+            let sx = point.x as u32 + tx;
+            let sy = point.y as u32 + ty;
+            let source_index = ((sy * source.width + sx) * source_pixel_width) as usize;
+            let target_index = ((ty * target.width + tx) * target_pixel_width) as usize;
+
+            let red = target_pixels[target_index];
+            let green = target_pixels[target_index + 1];
+            let blue = target_pixels[target_index + 2];
+
+            if let Some(trans_color) = trans_color {
+                if trans_color.r == red && trans_color.g == green && trans_color.b == blue {
+                    continue;
+                }
+            }
+
+            difference += source_pixels[source_index].abs_diff(red) as u64
+                + source_pixels[source_index + 1].abs_diff(green) as u64
+                + source_pixels[source_index + 2].abs_diff(blue) as u64;
+            compared += 3;
+        }
+    }
+
+    if compared == 0 {
+        return 1.0;
+    }
+
+    1.0 - difference as f64 / (compared * 255) as f64
+}
+
 fn multiple_image_search_inner(
     source: &ImageData,
     target: &ImageData,
@@ -703,4 +998,386 @@ fn multiple_image_search_trans_inner(
     }
 
     points
-}
\ No newline at end of file
+}
+
+const PYRAMID_MAX_FACTOR: u32 = 8;
+const PYRAMID_MIN_CELLS: u32 = 4;
+
+/// An image averaged over `factor`x`factor` cells. Cells covering a transparent pixel are `None`
+/// and match anything.
+struct Downscaled {
+    width: u32,
+    height: u32,
+    cells: Vec<Option<[u8; 3]>>,
+}
+
+fn downscale(
+    image: &ImageData,
+    factor: u32,
+    offset_x: u32,
+    offset_y: u32,
+    trans_color: Option<&Color>,
+) -> Downscaled {
+    let pixels = image.data.as_slice();
+    let pixel_width = image.pixel_width as u32;
+
+    let width = image.width.saturating_sub(offset_x) / factor;
+    let height = image.height.saturating_sub(offset_y) / factor;
+    let area = factor * factor;
+    let mut cells = Vec::with_capacity((width * height) as usize);
+
+    for cy in 0..height {
+        for cx in 0..width {
+            let mut sum = [0u32; 3];
+            let mut is_transparent = false;
+
+            for dy in 0..factor {
+                for dx in 0..factor {
+                    let x = offset_x + cx * factor + dx;
+                    let y = offset_y + cy * factor + dy;
+                    let index = ((y * image.width + x) * pixel_width) as usize;
+
+                    let red = pixels[index];
+                    let green = pixels[index + 1];
+                    let blue = pixels[index + 2];
+
+                    if let Some(trans_color) = trans_color {
+                        if trans_color.r == red && trans_color.g == green && trans_color.b == blue {
+                            is_transparent = true;
+                        }
+                    }
+
+                    sum[0] += red as u32;
+                    sum[1] += green as u32;
+                    sum[2] += blue as u32;
+                }
+            }
+
+            cells.push(if is_transparent {
+                None
+            } else {
+                Some([
+                    (sum[0] / area) as u8,
+                    (sum[1] / area) as u8,
+                    (sum[2] / area) as u8,
+                ])
+            });
+        }
+    }
+
+    Downscaled { width, height, cells }
+}
+
+fn downscaled_match_at(source: &Downscaled, target: &Downscaled, cx: u32, cy: u32, variant: i32) -> bool {
+    for ty in 0..target.height {
+        for tx in 0..target.width {
+            let target_cell = match target.cells[(ty * target.width + tx) as usize] {
+                Some(cell) => cell,
+                None => continue,
+            };
+
+            let source_cell = match source.cells[((cy + ty) * source.width + cx + tx) as usize] {
+                Some(cell) => cell,
+                None => continue,
+            };
+
+            for channel in 0..3 {
+                if (source_cell[channel] as i32 - target_cell[channel] as i32).abs() > variant {
+                    return false;
+                }
+            }
+        }
+    }
+
+    true
+}
+
+/// The first position, row by row, where `target` matches `source` cell for cell.
+fn downscaled_search_first(source: &Downscaled, target: &Downscaled, variant: i32) -> Option<Point> {
+    for cy in 0..=source.height.checked_sub(target.height)? {
+        for cx in 0..=source.width.checked_sub(target.width)? {
+            if downscaled_match_at(source, target, cx, cy, variant) {
+                return Some(Point { x: cx as i32, y: cy as i32 });
+            }
+        }
+    }
+
+    None
+}
+
+fn image_match_at(
+    source: &ImageData,
+    target: &ImageData,
+    sx: u32,
+    sy: u32,
+    variant: i32,
+    trans_color: Option<&Color>,
+) -> bool {
+    let source_pixels = source.data.as_slice();
+    let target_pixels = target.data.as_slice();
+
+    let source_pixel_width = source.pixel_width as u32;
+    let target_pixel_width = target.pixel_width as u32;
+
+    for ty in 0..target.height {
+        for tx in 0..target.width {
+            let source_index = (((sy + ty) * source.width + sx + tx) * source_pixel_width) as usize;
+            let target_index = ((ty * target.width + tx) * target_pixel_width) as usize;
+
+            let red = target_pixels[target_index];
+            let green = target_pixels[target_index + 1];
+            let blue = target_pixels[target_index + 2];
+
+            if let Some(trans_color) = trans_color {
+                if trans_color.r == red && trans_color.g == green && trans_color.b == blue {
+                    continue;
+                }
+            }
+
+            let is_found = (source_pixels[source_index] as i32 - red as i32).abs() <= variant
+                && (source_pixels[source_index + 1] as i32 - green as i32).abs() <= variant
+                && (source_pixels[source_index + 2] as i32 - blue as i32).abs() <= variant;
+
+            if !is_found {
+                return false;
+            }
+        }
+    }
+
+    true
+}
+
+/// Coarse-to-fine search.
+///
+/// The source is averaged once over `factor`x`factor` cells. The target is averaged once per
+/// phase (its offset modulo `factor` inside the source), so that for every possible position
+/// there is a target grid lining up exactly with the source grid. Averaging preserves the
+/// per-channel `variant` bound and transparent cells are skipped, so the coarse pass never
+/// rejects a real match; every candidate it keeps is then verified at full resolution.
+fn pyramid_search_inner(
+    source: &ImageData,
+    target: &ImageData,
+    variant: i32,
+    trans_color: Option<Color>,
+    first_only: bool,
+) -> Vec<Point> {
+    if target.width > source.width || target.height > source.height {
+        return Vec::new();
+    }
+
+    let factor = pyramid_factor(target);
+    if factor == 1 {
+        return match (trans_color, first_only) {
+            (Some(trans_color), true) => image_search_trans_inner(source, target, variant, trans_color).into_iter().collect(),
+            (Some(trans_color), false) => multiple_image_search_trans_inner(source, target, variant, trans_color),
+            (None, true) => image_search_inner(source, target, variant).into_iter().collect(),
+            (None, false) => multiple_image_search_inner(source, target, variant),
+        };
+    }
+
+    let coarse_source = downscale(source, factor, 0, 0, None);
+    pyramid_search_coarse(source, &coarse_source, factor, target, variant, trans_color.as_ref(), first_only)
+}
+
+/// The largest power of two, up to `PYRAMID_MAX_FACTOR`, that still leaves `target` at least
+/// `PYRAMID_MIN_CELLS` cells across once downscaled.
+fn pyramid_factor(target: &ImageData) -> u32 {
+    let mut factor = 1;
+    while factor < PYRAMID_MAX_FACTOR && target.width.min(target.height) / (factor * 2) >= PYRAMID_MIN_CELLS {
+        factor *= 2;
+    }
+
+    factor
+}
+
+/// The part of `pyramid_search_inner` after the source has been downscaled by `factor`.
+fn pyramid_search_coarse(
+    source: &ImageData,
+    coarse_source: &Downscaled,
+    factor: u32,
+    target: &ImageData,
+    variant: i32,
+    trans_color: Option<&Color>,
+    first_only: bool,
+) -> Vec<Point> {
+    let mut candidates = Vec::new();
+
+    for offset_y in 0..factor {
+        for offset_x in 0..factor {
+            let coarse_target = downscale(target, factor, offset_x, offset_y, trans_color);
+
+            // The target cell grid starts `offset` pixels into the target, so a coarse hit at
+            // (cx, cy) places the target at (cx * factor - offset_x, cy * factor - offset_y).
+            let first_cx = if offset_x == 0 { 0 } else { 1 };
+            let first_cy = if offset_y == 0 { 0 } else { 1 };
+
+            for cy in first_cy..coarse_source.height {
+                let sy = cy * factor - offset_y;
+                if sy + target.height > source.height {
+                    break;
+                }
+
+                for cx in first_cx..coarse_source.width {
+                    let sx = cx * factor - offset_x;
+                    if sx + target.width > source.width {
+                        break;
+                    }
+
+                    if downscaled_match_at(coarse_source, &coarse_target, cx, cy, variant) {
+                        candidates.push((sy, sx));
+                    }
+                }
+            }
+        }
+    }
+
+    candidates.sort_unstable();
+
+    let mut points = Vec::new();
+    for (sy, sx) in candidates {
+        if image_match_at(source, target, sx, sy, variant, trans_color) {
+            points.push(Point { x: sx as i32, y: sy as i32 });
+
+            if first_only {
+                break;
+            }
+        }
+    }
+
+    points
+}
+
+#[cfg(test)]
+mod tests {
+    use std::mem::ManuallyDrop;
+    use super::*;
+
+    /// Leaked rather than dropped: dropping an `ImageData` links in the N-API calls releasing a
+    /// JS `Buffer`, which are unresolved outside of Node.
+    fn image(width: u32, height: u32, pixel: impl Fn(u32, u32) -> [u8; 3]) -> &'static mut ImageData {
+        let mut data = Vec::with_capacity((width * height * 4) as usize);
+        for y in 0..height {
+            for x in 0..width {
+                data.extend_from_slice(&pixel(x, y));
+                data.push(255);
+            }
+        }
+
+        let image = Box::leak(Box::new(ManuallyDrop::new(ImageData { data: data.into(), width, height, pixel_width: 4 })));
+        &mut *image
+    }
+
+    /// Copies of `images` side by side, leaked like `image`.
+    fn images(images: &[&ImageData]) -> &'static [ImageData] {
+        let copies = images.iter().map(|it| ManuallyDrop::new((*it).clone())).collect::<Vec<_>>().leak();
+        // SAFETY: `ManuallyDrop` is `repr(transparent)`.
+        unsafe { std::slice::from_raw_parts(copies.as_ptr().cast(), copies.len()) }
+    }
+
+    fn noise(x: u32, y: u32) -> [u8; 3] {
+        let hash = (x.wrapping_mul(73_856_093) ^ y.wrapping_mul(19_349_663)).wrapping_mul(2_654_435_761);
+        [(hash >> 8) as u8, (hash >> 16) as u8, (hash >> 24) as u8]
+    }
+
+    fn crop(source: &ImageData, x: u32, y: u32, width: u32, height: u32) -> &'static mut ImageData {
+        let pixels = source.data.as_slice();
+        image(width, height, |tx, ty| {
+            let index = (((y + ty) * source.width + x + tx) * 4) as usize;
+            [pixels[index], pixels[index + 1], pixels[index + 2]]
+        })
+    }
+
+    fn coordinates(points: &[Point]) -> Vec<(i32, i32)> {
+        points.iter().map(|it| (it.x, it.y)).collect()
+    }
+
+    /// Both strategies must find the same matches, with the target at a position that doesn't
+    /// line up with the coarse grid.
+    fn assert_strategies_agree(source: &ImageData, target: &ImageData, variant: i32) {
+        let exhaustive = multiple_image_search_inner(source, target, variant);
+        let pyramid = pyramid_search_inner(source, target, variant, None, false);
+        assert!(!exhaustive.is_empty());
+        assert_eq!(coordinates(&pyramid), coordinates(&exhaustive));
+
+        let first = image_search_inner(source, target, variant).map(|it| (it.x, it.y));
+        let pyramid_first = pyramid_search_inner(source, target, variant, None, true);
+        assert_eq!(coordinates(&pyramid_first).first().copied(), first);
+    }
+
+    #[test]
+    fn pyramid_finds_what_exhaustive_finds() {
+        // A 20x16 target downscales by 4, the largest factor leaving 4 cells across.
+        let target = image(20, 16, |x, y| noise(x + 500, y + 500));
+        assert_eq!(pyramid_factor(target), 4);
+
+        let placed = [(37, 21), (3, 45)];
+        let source = image(96, 72, |x, y| {
+            match placed.iter().find(|(px, py)| x >= *px && x < px + 20 && y >= *py && y < py + 16) {
+                Some((px, py)) => noise(x - px + 500, y - py + 500),
+                None => noise(x, y),
+            }
+        });
+
+        assert_strategies_agree(source, target, 0);
+    }
+
+    #[test]
+    fn pyramid_agrees_with_a_variant() {
+        let source = image(64, 48, noise);
+        let exact = crop(source, 29, 11, 16, 16);
+        let target = image(16, 16, |x, y| {
+            let index = ((y * 16 + x) * 4) as usize;
+            let pixels = exact.data.as_slice();
+            [pixels[index].saturating_add(3), pixels[index + 1].saturating_sub(3), pixels[index + 2]]
+        });
+
+        assert_strategies_agree(source, target, 3);
+    }
+
+    #[test]
+    fn many_finds_what_single_searches_find() {
+        let source = image(48, 40, noise);
+        let hole = crop(source, 17, 23, 6, 5);
+        hole.data.as_mut_slice()[(2 * 6 + 3) * 4..][..3].copy_from_slice(&[1, 2, 3]);
+        let transparent = Color { r: 1, g: 2, b: 3 };
+
+        let targets = images(&[
+            crop(source, 5, 7, 9, 4),
+            crop(source, 30, 2, 16, 16),
+            image(3, 3, |_, _| [0, 0, 0]),
+            image(49, 1, noise),
+            hole,
+        ]);
+
+        for strategy in [SearchStrategy::Exhaustive, SearchStrategy::Pyramid] {
+            let results = image_search_many_inner(source, targets, 0, None, strategy);
+            let points: Vec<Option<(i32, i32)>> = results.iter().map(|it| it.point.as_ref().map(|it| (it.x, it.y))).collect();
+            assert_eq!(points, vec![Some((5, 7)), Some((30, 2)), None, None, None]);
+            assert_eq!(results[0].score, 1.0);
+
+            let results = image_search_many_inner(source, &targets[4..], 0, Some(transparent.clone()), strategy);
+            assert_eq!(results[0].point.as_ref().map(|it| (it.x, it.y)), Some((17, 23)));
+            let single = image_search_trans_inner(source, &targets[4], 0, transparent.clone());
+            assert_eq!(single.map(|it| (it.x, it.y)), Some((17, 23)));
+        }
+    }
+
+    #[test]
+    fn pyramid_handles_targets_near_the_minimum_size() {
+        let source = image(40, 30, noise);
+
+        // 8 pixels across is the smallest target that downscales at all, by 2.
+        let smallest = crop(source, 13, 9, 8, 8);
+        assert_eq!(pyramid_factor(smallest), 2);
+        assert_strategies_agree(source, smallest, 0);
+
+        // One pixel less and the pyramid falls back to the exhaustive scan.
+        let too_small = crop(source, 13, 9, 7, 8);
+        assert_eq!(pyramid_factor(too_small), 1);
+        assert_strategies_agree(source, too_small, 0);
+
+        // A target filling the source has a single position to check.
+        let whole = crop(source, 0, 0, 40, 30);
+        assert_strategies_agree(source, whole, 0);
+    }
+}
@@ -0,0 +1,270 @@
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+use crate::key::{key_from_name, Key};
+
+/// The most times `{Name count}` repeats a key, so that a typo can't queue billions of events.
+const MAX_REPEAT_COUNT: u32 = 1000;
+
+#[napi]
+#[derive(Debug, PartialEq)]
+pub enum SendEventKind {
+    Press,
+    Release,
+    /// Literal text, typed as characters regardless of the keyboard layout.
+    Text,
+}
+
+#[napi(object)]
+#[derive(Debug, Clone)]
+pub struct SendEvent {
+    pub kind: SendEventKind,
+    /// The key pressed or released, for `Press` and `Release`.
+    pub key: Option<Key>,
+    pub text: Option<String>,
+}
+
+/// Compiles a key sequence in AutoHotkey's Send syntax into the input events `Keyboard.send`
+/// generates for it:
+///
+/// - Plain characters are typed as text, newlines and tabs as Enter and Tab.
+/// - `{Name}` clicks a key, `{Name down}` and `{Name up}` press and release it, and `{Name 3}`
+///   clicks it three times, up to 1000. `{{}`, `{}}`, `{^}` etc. type the character itself.
+/// - `^` (Ctrl), `!` (Alt), `+` (Shift) and `#` (Win) hold the modifier for the next character
+///   or key, e.g. `^a` or `+{Tab}`.
+#[napi]
+pub fn parse_send_sequence(sequence: String) -> Result<Vec<SendEvent>> {
+    parse_send_sequence_inner(&sequence).map_err(|e| Error::new(Status::InvalidArg, e))
+}
+
+pub fn parse_send_sequence_inner(sequence: &str) -> std::result::Result<Vec<SendEvent>, String> {
+    let mut events = SendEvents::default();
+    let mut mods: Vec<Key> = Vec::new();
+    let mut chars = sequence.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        let modifier = match c {
+            '^' => Some(Key::Control),
+            '!' => Some(Key::Alt),
+            '+' => Some(Key::Shift),
+            '#' => Some(Key::LeftWin),
+            _ => None,
+        };
+        if let Some(modifier) = modifier {
+            if !mods.contains(&modifier) {
+                mods.push(modifier);
+            }
+            continue;
+        }
+
+        mods.iter().for_each(|it| events.press(*it));
+
+        match c {
+            '{' => {
+                // The first character may be a brace itself, as in "{{}" and "{}}".
+                let mut name = String::new();
+                if let Some(first) = chars.next() {
+                    name.push(first);
+                }
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some(c) => name.push(c),
+                        None => return Err(format!("Missing \"}}\" after \"{{{}\"", name)),
+                    }
+                }
+
+                braced(&mut events, &name, &mods)?;
+            }
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\n' | '\r' => events.click(Key::Enter),
+            '\t' => events.click(Key::Tab),
+            _ => character(&mut events, c, &mods),
+        }
+
+        mods.drain(..).rev().for_each(|it| events.release(it));
+    }
+
+    if !mods.is_empty() {
+        return Err(format!("Modifier symbol at the end of \"{}\" has no key to apply to", sequence));
+    }
+
+    Ok(events.0)
+}
+
+/// Handles the content of a `{...}`: a key or character name, optionally followed by `down`,
+/// `up` or a repeat count.
+fn braced(events: &mut SendEvents, content: &str, mods: &[Key]) -> std::result::Result<(), String> {
+    let (name, argument) = match content.trim().rsplit_once(' ') {
+        Some((name, argument)) if !name.trim().is_empty() => (name.trim(), Some(argument.trim().to_lowercase())),
+        _ => (content.trim(), None),
+    };
+    // Keep "{ }" and "{}}" working, trimming would leave nothing.
+    let name = if name.is_empty() { content } else { name };
+
+    let key = key_from_name(name);
+    let mut single_char = name.chars();
+    let character_name = match (single_char.next(), single_char.next()) {
+        (Some(c), None) => Some(c),
+        _ => None,
+    };
+
+    if key.is_none() && character_name.is_none() {
+        return Err(format!("Unknown key \"{}\" in \"{{{}}}\"", name, content));
+    }
+
+    let count = match argument.as_deref() {
+        Some("down") | Some("up") => {
+            let key = match key {
+                Some(key) => key,
+                None => return Err(format!("\"{}\" can't be pressed or released on its own", name)),
+            };
+
+            if argument.as_deref() == Some("down") {
+                events.press(key);
+            } else {
+                events.release(key);
+            }
+            return Ok(());
+        }
+        Some(count) => match count.parse::<u32>() {
+            Ok(count) if count > MAX_REPEAT_COUNT => {
+                return Err(format!("Repeat count {} in \"{{{}}}\" is more than {}", count, content, MAX_REPEAT_COUNT));
+            }
+            Ok(count) => count,
+            Err(_) => return Err(format!("Invalid argument \"{}\" in \"{{{}}}\"", count, content)),
+        },
+        None => 1,
+    };
+
+    for _ in 0..count {
+        // "{a}" and "{!}" type the character like plain text would.
+        match (character_name, key) {
+            (Some(c), _) => character(events, c, mods),
+            (None, Some(key)) => events.click(key),
+            (None, None) => {}
+        }
+    }
+
+    Ok(())
+}
+
+/// Types a plain character. Under `mods`, letters and digits become key clicks so that
+/// shortcuts like `^a` reach applications as Ctrl+A rather than as a typed character.
+fn character(events: &mut SendEvents, c: char, mods: &[Key]) {
+    let key = if !mods.is_empty() && c.is_ascii_alphanumeric() {
+        key_from_name(&c.to_string())
+    } else {
+        None
+    };
+
+    match key {
+        Some(key) if c.is_ascii_uppercase() && !mods.contains(&Key::Shift) => {
+            events.press(Key::Shift);
+            events.click(key);
+            events.release(Key::Shift);
+        }
+        Some(key) => events.click(key),
+        None => events.text(c),
+    }
+}
+
+#[derive(Default)]
+struct SendEvents(Vec<SendEvent>);
+
+impl SendEvents {
+    fn press(&mut self, key: Key) {
+        self.0.push(SendEvent { kind: SendEventKind::Press, key: Some(key), text: None });
+    }
+
+    fn release(&mut self, key: Key) {
+        self.0.push(SendEvent { kind: SendEventKind::Release, key: Some(key), text: None });
+    }
+
+    fn click(&mut self, key: Key) {
+        self.press(key);
+        self.release(key);
+    }
+
+    /// Appends to the previous event when it is text as well.
+    fn text(&mut self, c: char) {
+        if let Some(SendEvent { kind: SendEventKind::Text, text: Some(text), .. }) = self.0.last_mut() {
+            text.push(c);
+            return;
+        }
+
+        self.0.push(SendEvent { kind: SendEventKind::Text, key: None, text: Some(c.to_string()) });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The events of `sequence` as "+Key" for presses, "-Key" for releases and the text itself.
+    fn parse(sequence: &str) -> Vec<String> {
+        parse_send_sequence_inner(sequence)
+            .unwrap()
+            .into_iter()
+            .map(|event| match (event.kind, event.key, event.text) {
+                (SendEventKind::Press, Some(key), _) => format!("+{:?}", key),
+                (SendEventKind::Release, Some(key), _) => format!("-{:?}", key),
+                (_, _, text) => text.unwrap_or_default(),
+            })
+            .collect()
+    }
+
+    fn error(sequence: &str) -> String {
+        parse_send_sequence_inner(sequence).unwrap_err()
+    }
+
+    #[test]
+    fn types_plain_text() {
+        assert_eq!(parse("Hello, World."), vec!["Hello, World."]);
+        assert_eq!(parse("a\r\nb\tc"), vec!["a", "+Enter", "-Enter", "b", "+Tab", "-Tab", "c"]);
+        assert!(parse("").is_empty());
+    }
+
+    #[test]
+    fn clicks_braced_keys() {
+        assert_eq!(parse("{Enter}"), vec!["+Enter", "-Enter"]);
+        assert_eq!(parse("{Tab 3}"), vec!["+Tab", "-Tab", "+Tab", "-Tab", "+Tab", "-Tab"]);
+        assert_eq!(parse("{a 2}"), vec!["aa"]);
+        assert_eq!(parse("{Tab 0}"), Vec::<String>::new());
+    }
+
+    #[test]
+    fn holds_keys_down() {
+        assert_eq!(parse("{Ctrl down}c{Ctrl up}"), vec!["+Control", "c", "-Control"]);
+    }
+
+    #[test]
+    fn applies_modifier_symbols_to_the_next_key() {
+        assert_eq!(parse("^a"), vec!["+Control", "+A", "-A", "-Control"]);
+        assert_eq!(parse("+A"), vec!["+Shift", "+A", "-A", "-Shift"]);
+        assert_eq!(parse("^A"), vec!["+Control", "+Shift", "+A", "-A", "-Shift", "-Control"]);
+        assert_eq!(parse("^!{Delete}x"), vec!["+Control", "+Alt", "+Delete", "-Delete", "-Alt", "-Control", "x"]);
+    }
+
+    #[test]
+    fn types_braced_symbols() {
+        assert_eq!(parse("{{}"), vec!["{"]);
+        assert_eq!(parse("{}}"), vec!["}"]);
+        assert_eq!(parse("{^}{+}{ }"), vec!["^+ "]);
+    }
+
+    #[test]
+    fn rejects_malformed_sequences() {
+        assert_eq!(error("abc^"), "Modifier symbol at the end of \"abc^\" has no key to apply to");
+        assert_eq!(error("{Enter"), "Missing \"}\" after \"{Enter\"");
+        assert_eq!(error("{Banana}"), "Unknown key \"Banana\" in \"{Banana}\"");
+        assert_eq!(error("{Tab twice}"), "Invalid argument \"twice\" in \"{Tab twice}\"");
+        assert_eq!(error("{! down}"), "\"!\" can't be pressed or released on its own");
+    }
+
+    #[test]
+    fn caps_the_repeat_count() {
+        assert_eq!(parse("{a 1000}"), vec!["a".repeat(1000)]);
+        assert_eq!(error("{a 1001}"), "Repeat count 1001 in \"{a 1001}\" is more than 1000");
+        assert_eq!(error("{a 4000000000}"), "Repeat count 4000000000 in \"{a 4000000000}\" is more than 1000");
+    }
+}
@@ -39,6 +39,7 @@ pub fn bigint_to_usize(bigint: BigInt) -> usize {
     bigint_to_u64(bigint) as usize
 }
 
+#[cfg(target_os = "windows")]
 pub fn encode_wide<S: AsRef<std::ffi::OsStr>>(string: S) -> Vec<u16> {
     std::os::windows::prelude::OsStrExt::encode_wide(string.as_ref())
         .chain(std::iter::once(0))
@@ -65,4 +66,12 @@ pub async fn handle_result<T>(task: tokio::task::JoinHandle<std::result::Result<
             format!("Task join failed: {:?}", e),
         )),
     }
-}
\ No newline at end of file
+}
+
+/// `handle_result` for keyboard and mouse actions, waiting the input delay after them, see
+/// `setInputDelay`.
+pub async fn handle_input<T>(task: tokio::task::JoinHandle<std::result::Result<T, String>>) -> Result<T> {
+    let result = handle_result(task).await;
+    crate::delay::wait_input_delay().await;
+    result
+}
@@ -0,0 +1,109 @@
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+use windows::Win32::Foundation::RECT;
+use windows::Win32::UI::WindowsAndMessaging::{ClipCursor, CURSOR_SHOWING, CURSORINFO, GetCursorInfo, IDC_APPSTARTING, IDC_ARROW, IDC_CROSS, IDC_HAND, IDC_IBEAM, IDC_WAIT, LoadCursorW};
+use crate::geometry::Rect;
+use crate::utils::handle_result;
+use super::mouse::Mouse;
+use super::screen::ensure_dpi_awareness;
+
+#[napi]
+#[derive(Debug, PartialEq)]
+pub enum CursorShape {
+    /// No cursor is shown.
+    Hidden,
+    Arrow,
+    /// The text cursor.
+    IBeam,
+    /// The busy cursor (an hourglass or spinner).
+    Wait,
+    /// An arrow with a small busy indicator, shown while an application starts or works in the
+    /// background.
+    Progress,
+    /// The pointing hand over links.
+    Hand,
+    Crosshair,
+    /// Any other cursor, e.g. resize arrows or custom cursors.
+    Other,
+}
+
+#[napi]
+impl Mouse {
+    /// Keeps the cursor inside `rect` until `unconfine` is called, moving it there first if it
+    /// is outside. Windows lifts the confinement by itself when the foreground window changes.
+    #[napi]
+    pub async fn confine(rect: Rect) -> Result<()> {
+        let task = tokio::spawn(async move {
+            ensure_dpi_awareness();
+
+            let rect = RECT {
+                left: rect.left,
+                top: rect.top,
+                right: rect.right,
+                bottom: rect.bottom,
+            };
+
+            match unsafe { ClipCursor(Some(&rect as *const RECT)) } {
+                Ok(_) => Ok(()),
+                Err(e) => Err(format!("Failed to confine the cursor: {:?}", e)),
+            }
+        });
+
+        handle_result(task).await
+    }
+
+    /// Lets the cursor leave the area given to `confine` again.
+    #[napi]
+    pub async fn unconfine() -> Result<()> {
+        let task = tokio::spawn(async move {
+            match unsafe { ClipCursor(None) } {
+                Ok(_) => Ok(()),
+                Err(e) => Err(format!("Failed to release the cursor: {:?}", e)),
+            }
+        });
+
+        handle_result(task).await
+    }
+
+    /// Compares the current cursor with the standard system cursors. Applications that load
+    /// their own cursor images report `Other`.
+    #[napi]
+    pub async fn get_cursor_shape() -> Result<CursorShape> {
+        let task = tokio::spawn(async move {
+            let mut info = CURSORINFO {
+                cbSize: std::mem::size_of::<CURSORINFO>() as u32,
+                ..Default::default()
+            };
+
+            if let Err(e) = unsafe { GetCursorInfo(&mut info) } {
+                return Err(format!("Failed to get the cursor: {:?}", e));
+            }
+
+            if info.flags.0 & CURSOR_SHOWING.0 == 0 || info.hCursor.is_invalid() {
+                return Ok(CursorShape::Hidden);
+            }
+
+            let shapes = [
+                (IDC_ARROW, CursorShape::Arrow),
+                (IDC_IBEAM, CursorShape::IBeam),
+                (IDC_WAIT, CursorShape::Wait),
+                (IDC_APPSTARTING, CursorShape::Progress),
+                (IDC_HAND, CursorShape::Hand),
+                (IDC_CROSS, CursorShape::Crosshair),
+            ];
+
+            for (id, shape) in shapes {
+                // The standard cursors are shared, so every process gets the same handle for them.
+                if let Ok(cursor) = unsafe { LoadCursorW(None, id) } {
+                    if cursor == info.hCursor {
+                        return Ok(shape);
+                    }
+                }
+            }
+
+            Ok(CursorShape::Other)
+        });
+
+        handle_result(task).await
+    }
+}
@@ -0,0 +1,287 @@
+use std::sync::Mutex;
+use std::time::Duration;
+use crossbeam_channel::{Sender, unbounded};
+use lazy_static::lazy_static;
+use windows::core::PWSTR;
+use windows::Win32::Foundation::{CloseHandle, ERROR_HOTKEY_ALREADY_REGISTERED, HWND, LPARAM, WPARAM};
+use windows::Win32::System::Threading::{GetCurrentThreadId, OpenProcess, PROCESS_NAME_WIN32, PROCESS_QUERY_LIMITED_INFORMATION, QueryFullProcessImageNameW};
+use windows::Win32::UI::Input::KeyboardAndMouse::{GetAsyncKeyState, HOT_KEY_MODIFIERS, INPUT, MOD_ALT, MOD_CONTROL, MOD_SHIFT, MOD_WIN, RegisterHotKey, SendInput, UnregisterHotKey};
+use windows::Win32::UI::WindowsAndMessaging::{GetClassNameW, GetForegroundWindow, GetMessageW, GetWindowTextLengthW, GetWindowTextW, GetWindowThreadProcessId, KillTimer, MSG, PeekMessageW, PM_NOREMOVE, PostThreadMessageW, SetTimer, WM_APP, WM_HOTKEY, WM_TIMER};
+use crate::hotkey::{lose_hotkey, settle, trigger_hotkey, ForegroundWindow, Hotkey, HotkeyAction, HotkeyDeferred, HotkeyDone, HotkeyError, HotkeyErrorCode};
+use crate::key::key_info;
+use crate::modifiers::SIDED_MODIFIERS;
+use crate::utils::decode_wide;
+use super::keyboard::{key_input, Modifiers};
+
+enum HotkeyMessage {
+    Register(Hotkey, HotkeyDone),
+    Unregister(u32),
+    Shutdown(HotkeyDeferred<()>),
+}
+
+/// Milliseconds between two checks whether the keys of held hotkeys are released yet, as
+/// `WM_HOTKEY` only tells about presses.
+const RELEASE_POLL_INTERVAL: u32 = 20;
+
+/// The highest id `RegisterHotKey` takes from applications, ids above are for shared DLLs.
+const MAX_REGISTRATION_ID: i32 = 0xBFFF;
+
+lazy_static! {
+    /// The thread the hotkeys are registered on, which receives their `WM_HOTKEY` messages,
+    /// and the channel of the requests for it.
+    static ref HOTKEY_THREAD: Mutex<Option<(u32, Sender<HotkeyMessage>)>> = Mutex::new(None);
+}
+
+pub fn register(hotkey: Hotkey, done: HotkeyDone) {
+    let mut thread = HOTKEY_THREAD.lock().unwrap();
+    let started = match thread.as_ref() {
+        Some(_) => Ok(()),
+        None => start_hotkey_thread().map(|started| *thread = Some(started)),
+    };
+
+    if let Err(e) = started {
+        // `done` locks the hotkeys, which are locked before the thread elsewhere.
+        drop(thread);
+        return done(Err(HotkeyError::new(HotkeyErrorCode::Failed, e)));
+    }
+
+    if let Some(thread) = thread.as_ref() {
+        send(thread, HotkeyMessage::Register(hotkey, done));
+    }
+}
+
+pub fn unregister(id: u32) {
+    if let Some(thread) = HOTKEY_THREAD.lock().unwrap().as_ref() {
+        send(thread, HotkeyMessage::Unregister(id));
+    }
+}
+
+pub fn shutdown(deferred: HotkeyDeferred<()>) {
+    match HOTKEY_THREAD.lock().unwrap().take() {
+        Some(thread) => send(&thread, HotkeyMessage::Shutdown(deferred)),
+        None => settle(deferred, Ok(())),
+    }
+}
+
+/// Queues `message` for the hotkey thread and wakes it up with `WM_APP`.
+fn send((thread, sender): &(u32, Sender<HotkeyMessage>), message: HotkeyMessage) {
+    let _ = sender.send(message);
+
+    unsafe {
+        let _ = PostThreadMessageW(*thread, WM_APP, WPARAM(0), LPARAM(0));
+    }
+}
+
+/// Starts the thread registering the hotkeys. They are registered without a window, so their
+/// `WM_HOTKEY` messages go to the thread's queue, where `GetMessageW` waits for them. Another
+/// `WM_HOTKEY` while the key is held down is auto-repeat, and a timer checks when it is released.
+/// The hotkeys are registered under the lowest free id rather than their own, which is a hash
+/// too large for `RegisterHotKey`. Returns the id of that thread once its queue exists, with the
+/// channel of its requests.
+fn start_hotkey_thread() -> std::result::Result<(u32, Sender<HotkeyMessage>), String> {
+    let (sender, receiver) = std::sync::mpsc::channel();
+    let (messages, requests) = unbounded();
+
+    std::thread::spawn(move || unsafe {
+        let mut msg = MSG::default();
+        // Creates the message queue, so that messages posted from now on aren't lost.
+        let _ = PeekMessageW(&mut msg, None, 0, 0, PM_NOREMOVE);
+        let _ = sender.send(GetCurrentThreadId());
+
+        // The hotkeys with the id they are registered under.
+        let mut registered: Vec<(i32, Hotkey)> = Vec::new();
+        let mut held: Vec<Hotkey> = Vec::new();
+        let mut timer = 0;
+
+        while GetMessageW(&mut msg, None, 0, 0).0 > 0 {
+            match msg.message {
+                WM_HOTKEY => {
+                    let (registration_id, hotkey) = match registered.iter().find(|(it, _)| *it as usize == msg.wParam.0) {
+                        Some((registration_id, hotkey)) => (*registration_id, hotkey.clone()),
+                        None => continue,
+                    };
+                    let id = hotkey.id;
+
+                    if held.iter().any(|it| it.id == id) {
+                        trigger_hotkey(id, HotkeyAction::Repeat);
+                    } else if trigger_hotkey(id, HotkeyAction::Press) {
+                        held.push(hotkey);
+                        if timer == 0 {
+                            timer = SetTimer(HWND::default(), 0, RELEASE_POLL_INTERVAL, None);
+                        }
+                    } else if let Err(e) = pass_through(registration_id, &hotkey) {
+                        registered.retain(|(it, _)| *it != registration_id);
+                        lose_hotkey(id, e);
+                    }
+                }
+                WM_TIMER if msg.wParam.0 == timer => {
+                    held.retain(|hotkey| {
+                        let down = GetAsyncKeyState(key_info(hotkey.key).vk as i32) < 0;
+                        if !down {
+                            trigger_hotkey(hotkey.id, HotkeyAction::Release);
+                        }
+                        down
+                    });
+
+                    if held.is_empty() {
+                        let _ = KillTimer(HWND::default(), timer);
+                        timer = 0;
+                    }
+                }
+                WM_APP => {
+                    while let Ok(message) = requests.try_recv() {
+                        match message {
+                            HotkeyMessage::Register(hotkey, done) => {
+                                let result = match free_registration_id(&registered) {
+                                    Some(registration_id) => register_hotkey(registration_id, &hotkey).map(|_| registration_id),
+                                    None => Err(HotkeyError::new(
+                                        HotkeyErrorCode::Failed,
+                                        format!("Windows allows no more than {} hotkeys", MAX_REGISTRATION_ID + 1),
+                                    )),
+                                };
+                                if let Ok(registration_id) = result {
+                                    registered.push((registration_id, hotkey));
+                                }
+
+                                done(result.map(|_| ()));
+                            }
+                            HotkeyMessage::Unregister(id) => {
+                                registered.retain(|(registration_id, hotkey)| {
+                                    if hotkey.id == id {
+                                        let _ = UnregisterHotKey(HWND::default(), *registration_id);
+                                    }
+                                    hotkey.id != id
+                                });
+                                held.retain(|it| it.id != id);
+                            }
+                            HotkeyMessage::Shutdown(deferred) => {
+                                for (registration_id, _) in registered.drain(..) {
+                                    let _ = UnregisterHotKey(HWND::default(), registration_id);
+                                }
+                                if timer != 0 {
+                                    let _ = KillTimer(HWND::default(), timer);
+                                }
+
+                                settle(deferred, Ok(()));
+                                return;
+                            }
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    });
+
+    match receiver.recv() {
+        Ok(thread) => Ok((thread, messages)),
+        Err(e) => Err(format!("Failed to start the hotkey thread: {:?}", e)),
+    }
+}
+
+/// Lets the keystroke of `hotkey` through to the foreground window, by sending its key again
+/// while the hotkey is unregistered. The modifiers are still held down. The hotkey is registered
+/// again once the key reads as released, i.e. the sent keystroke has been processed, which fails
+/// when another application took the key combination in the meantime.
+unsafe fn pass_through(registration_id: i32, hotkey: &Hotkey) -> std::result::Result<(), HotkeyError> {
+    let _ = UnregisterHotKey(HWND::default(), registration_id);
+
+    SendInput(&[key_input(hotkey.key, false), key_input(hotkey.key, true)], std::mem::size_of::<INPUT>() as i32);
+
+    for _ in 0..50 {
+        if GetAsyncKeyState(key_info(hotkey.key).vk as i32) >= 0 {
+            break;
+        }
+        std::thread::sleep(Duration::from_millis(5));
+    }
+
+    register_hotkey(registration_id, hotkey)
+}
+
+/// The lowest id no hotkey is registered under.
+fn free_registration_id(registered: &[(i32, Hotkey)]) -> Option<i32> {
+    (0..=MAX_REGISTRATION_ID).find(|id| registered.iter().all(|(it, _)| it != id))
+}
+
+unsafe fn register_hotkey(registration_id: i32, hotkey: &Hotkey) -> std::result::Result<(), HotkeyError> {
+    let flags = modifier_flags(&hotkey.mods)?;
+
+    match RegisterHotKey(HWND::default(), registration_id, flags, key_info(hotkey.key).vk as u32) {
+        Ok(_) => Ok(()),
+        Err(e) if e.code() == ERROR_HOTKEY_ALREADY_REGISTERED.to_hresult() => Err(HotkeyError::new(
+            HotkeyErrorCode::AlreadyRegistered,
+            format!("Another application already registered {:?} with {:?}", hotkey.key, hotkey.mods),
+        )),
+        Err(e) => Err(HotkeyError::new(HotkeyErrorCode::Failed, format!("Failed to register the hotkey: {:?}", e))),
+    }
+}
+
+/// The modifiers of one side whose key is held down, read while handling `WM_HOTKEY` as the
+/// message doesn't tell the side.
+pub fn held_sides() -> Vec<Modifiers> {
+    SIDED_MODIFIERS
+        .iter()
+        .filter(|(_, _, key)| unsafe { GetAsyncKeyState(key_info(*key).vk as i32) } < 0)
+        .map(|(sided, _, _)| *sided)
+        .collect()
+}
+
+/// The foreground window's title, class and executable name.
+pub fn foreground_window() -> ForegroundWindow {
+    unsafe {
+        let hwnd = GetForegroundWindow();
+        if hwnd.0 == 0 {
+            return ForegroundWindow::default();
+        }
+
+        let mut title = vec![0u16; GetWindowTextLengthW(hwnd) as usize + 1];
+        GetWindowTextW(hwnd, &mut title);
+
+        let mut class_name = [0u16; 256];
+        let length = GetClassNameW(hwnd, &mut class_name);
+
+        let mut pid = 0u32;
+        GetWindowThreadProcessId(hwnd, Some(&mut pid));
+
+        ForegroundWindow {
+            title: decode_wide(&title),
+            class_names: vec![decode_wide(&class_name[..length.max(0) as usize])],
+            process: process_name(pid).unwrap_or_default(),
+        }
+    }
+}
+
+/// The file name of the executable of process `pid`.
+unsafe fn process_name(pid: u32) -> Option<String> {
+    let process = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, pid).ok()?;
+
+    let mut buffer = [0u16; 1024];
+    let mut length = buffer.len() as u32;
+    let queried = QueryFullProcessImageNameW(process, PROCESS_NAME_WIN32, PWSTR(buffer.as_mut_ptr()), &mut length);
+    let _ = CloseHandle(process);
+    queried.ok()?;
+
+    let path = decode_wide(&buffer[..length as usize]);
+    path.rsplit('\\').next().map(str::to_string)
+}
+
+/// The `RegisterHotKey` flags of hotkey modifiers. Windows hotkeys only know Shift, Ctrl, Alt
+/// and Win, the other modifiers are refused rather than left out.
+fn modifier_flags(mods: &[Modifiers]) -> std::result::Result<HOT_KEY_MODIFIERS, HotkeyError> {
+    let mut flags = HOT_KEY_MODIFIERS(0);
+
+    for modifier in mods {
+        flags |= match modifier {
+            Modifiers::Shift => MOD_SHIFT,
+            Modifiers::Super | Modifiers::Meta => MOD_WIN,
+            Modifiers::Alt => MOD_ALT,
+            Modifiers::Control => MOD_CONTROL,
+            _ => return Err(HotkeyError::new(
+                HotkeyErrorCode::Unsupported,
+                format!("Hotkeys on Windows can't use the {:?} modifier", modifier),
+            )),
+        };
+    }
+
+    Ok(flags)
+}
@@ -0,0 +1,154 @@
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Mutex;
+use lazy_static::lazy_static;
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+use windows::Win32::UI::Input::KeyboardAndMouse::{GetAsyncKeyState, GetKeyboardLayout, GetKeyState, INPUT, KEYBD_EVENT_FLAGS, KEYEVENTF_KEYUP, KEYEVENTF_UNICODE, SendInput, ToUnicodeEx, VIRTUAL_KEY, VK_BACK, VK_CAPITAL, VK_CONTROL, VK_LCONTROL, VK_LMENU, VK_LSHIFT, VK_LWIN, VK_MENU, VK_NUMLOCK, VK_RCONTROL, VK_RETURN, VK_RMENU, VK_RSHIFT, VK_RWIN, VK_SCROLL, VK_SHIFT, VK_TAB};
+use windows::Win32::UI::WindowsAndMessaging::{GetForegroundWindow, GetWindowThreadProcessId, KBDLLHOOKSTRUCT, LLKHF_INJECTED, WM_KEYDOWN, WM_SYSKEYDOWN};
+use crate::hotstring::{Hotstring, HotstringInput, HotstringMatch, HotstringMatcher, HotstringOptions};
+use super::keyboard::{keyboard_input, Keyboard};
+use super::keyboard_listener::{start_interception, stop_interception_if_unused};
+
+lazy_static! {
+    static ref HOTSTRINGS: Mutex<HotstringMatcher> = Mutex::new(HotstringMatcher::default());
+}
+
+static NEXT_HOTSTRING_ID: AtomicU32 = AtomicU32::new(1);
+
+/// Keys that only modify others, which don't break up the typed text.
+const MODIFIER_KEYS: [VIRTUAL_KEY; 14] = [
+    VK_SHIFT, VK_LSHIFT, VK_RSHIFT,
+    VK_CONTROL, VK_LCONTROL, VK_RCONTROL,
+    VK_MENU, VK_LMENU, VK_RMENU,
+    VK_LWIN, VK_RWIN,
+    VK_CAPITAL, VK_NUMLOCK, VK_SCROLL,
+];
+
+/// Tells `ToUnicodeEx` to leave the keyboard state alone, so that translating a key here
+/// doesn't swallow the dead key the application is about to see.
+const TO_UNICODE_KEEP_STATE: u32 = 1 << 2;
+
+#[napi]
+impl Keyboard {
+    /// Replaces `abbreviation` whenever it is typed as a word of its own, erasing it with
+    /// Backspace and typing `replacement` instead. Returns an id for `unregisterHotstring`.
+    #[napi]
+    pub fn register_hotstring(abbreviation: String, replacement: String, options: Option<HotstringOptions>) -> Result<u32> {
+        let hotstring = Hotstring::new(&abbreviation, &replacement, options.unwrap_or_default())
+            .map_err(|e| Error::new(Status::InvalidArg, e))?;
+
+        let id = NEXT_HOTSTRING_ID.fetch_add(1, Ordering::Relaxed);
+        HOTSTRINGS.lock().unwrap().add(id, hotstring);
+
+        if let Err(e) = start_interception() {
+            HOTSTRINGS.lock().unwrap().remove(id);
+            return Err(Error::new(Status::GenericFailure, e));
+        }
+
+        Ok(id)
+    }
+
+    #[napi]
+    pub fn unregister_hotstring(id: u32) {
+        HOTSTRINGS.lock().unwrap().remove(id);
+        stop_interception_if_unused();
+    }
+}
+
+pub fn has_hotstrings() -> bool {
+    !HOTSTRINGS.lock().unwrap().is_empty()
+}
+
+/// Feeds a key press seen by the keyboard hook to the hotstrings, and types the replacement of
+/// the one it completes on a thread of its own. Synthetic input is skipped, which keeps
+/// replacements from triggering hotstrings themselves.
+pub fn hotstring_key_event(message: u32, info: &KBDLLHOOKSTRUCT) {
+    if (message != WM_KEYDOWN && message != WM_SYSKEYDOWN) || info.flags.0 & LLKHF_INJECTED.0 != 0 {
+        return;
+    }
+
+    let mut hotstrings = HOTSTRINGS.lock().unwrap();
+    if hotstrings.is_empty() {
+        return;
+    }
+
+    let mut found = None;
+    for input in key_inputs(info) {
+        found = hotstrings.input(input).or(found);
+    }
+
+    if let Some(found) = found {
+        std::thread::spawn(move || expand(&found));
+    }
+}
+
+/// Translates a key press into what it means to hotstrings, with the layout of the foreground
+/// window. Modifier keys and dead keys mean nothing until the key they modify comes.
+fn key_inputs(info: &KBDLLHOOKSTRUCT) -> Vec<HotstringInput> {
+    if MODIFIER_KEYS.iter().any(|it| it.0 as u32 == info.vkCode) {
+        return Vec::new();
+    }
+
+    let is_down = |key: VIRTUAL_KEY| unsafe { GetAsyncKeyState(key.0 as i32) } < 0;
+
+    // AltGr arrives as Ctrl+Alt, either one alone or Win makes a shortcut.
+    let (control, alt) = (is_down(VK_CONTROL), is_down(VK_MENU));
+    if control != alt || is_down(VK_LWIN) || is_down(VK_RWIN) {
+        return vec![HotstringInput::Reset];
+    }
+
+    let mut state = [0u8; 256];
+    for key in [VK_SHIFT, VK_LSHIFT, VK_RSHIFT, VK_CONTROL, VK_LCONTROL, VK_RCONTROL, VK_MENU, VK_LMENU, VK_RMENU] {
+        if is_down(key) {
+            state[key.0 as usize] = 0x80;
+        }
+    }
+    if unsafe { GetKeyState(VK_CAPITAL.0 as i32) } & 1 != 0 {
+        state[VK_CAPITAL.0 as usize] = 0x01;
+    }
+
+    let mut buffer = [0u16; 8];
+    let count = unsafe {
+        let thread = GetWindowThreadProcessId(GetForegroundWindow(), None);
+        ToUnicodeEx(info.vkCode, info.scanCode, &state, &mut buffer, TO_UNICODE_KEEP_STATE, GetKeyboardLayout(thread))
+    };
+
+    match count {
+        count if count > 0 => char::decode_utf16(buffer[..count as usize].iter().copied())
+            .map(|it| it.map_or(HotstringInput::Reset, HotstringInput::from_char))
+            .collect(),
+        0 => vec![HotstringInput::Reset],
+        _ => Vec::new(),
+    }
+}
+
+/// Erases the abbreviation and types the replacement in one `SendInput` batch, line breaks and
+/// tabs as Enter and Tab.
+fn expand(found: &HotstringMatch) {
+    let mut inputs = Vec::new();
+    let mut push = |vk: VIRTUAL_KEY, scan: u16, flags: KEYBD_EVENT_FLAGS| {
+        inputs.push(keyboard_input(vk, scan, flags));
+        inputs.push(keyboard_input(vk, scan, flags | KEYEVENTF_KEYUP));
+    };
+
+    for _ in 0..found.erase {
+        push(VK_BACK, 0, KEYBD_EVENT_FLAGS(0));
+    }
+
+    for c in found.text.chars() {
+        match c {
+            '\n' => push(VK_RETURN, 0, KEYBD_EVENT_FLAGS(0)),
+            '\t' => push(VK_TAB, 0, KEYBD_EVENT_FLAGS(0)),
+            c => {
+                let mut units = [0u16; 2];
+                for unit in c.encode_utf16(&mut units) {
+                    push(VIRTUAL_KEY(0), *unit, KEYEVENTF_UNICODE);
+                }
+            }
+        }
+    }
+
+    unsafe {
+        SendInput(inputs.as_slice(), std::mem::size_of::<INPUT>() as i32);
+    }
+}
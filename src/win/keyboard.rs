@@ -1,418 +1,13 @@
-use std::collections::HashMap;
-use std::fmt::Display;
-use std::hash::Hash;
-use std::sync::Mutex;
-use crossbeam_channel::{Receiver, Sender, unbounded};
-use lazy_static::lazy_static;
 use napi::bindgen_prelude::*;
 use napi_derive::napi;
-use napi::threadsafe_function::{ThreadsafeFunction, ThreadsafeFunctionCallMode};
-use windows::core::PCWSTR;
-use windows::Win32::Foundation::{HINSTANCE, HWND, LPARAM, LRESULT, WPARAM};
-use windows::Win32::System::LibraryLoader::GetModuleHandleW;
-use windows::Win32::UI::Input::KeyboardAndMouse::{KEYBD_EVENT_FLAGS, KEYEVENTF_UNICODE, SendInput, INPUT, INPUT_KEYBOARD, KEYEVENTF_KEYUP, VIRTUAL_KEY, RegisterHotKey, MOD_NOREPEAT, MOD_SHIFT, MOD_ALT, MOD_CONTROL, UnregisterHotKey};
-use windows::Win32::UI::WindowsAndMessaging::{CreateWindowExW, CW_USEDEFAULT, DefWindowProcW, DispatchMessageW, HMENU, PeekMessageW, PM_REMOVE, RegisterClassW, TranslateMessage, WM_HOTKEY, WM_QUIT, WNDCLASSW, WS_EX_LAYERED, WS_EX_NOACTIVATE, WS_EX_TOOLWINDOW, WS_EX_TRANSPARENT, WS_OVERLAPPED,};
-use crate::utils::{encode_wide, handle_result};
+use windows::Win32::UI::Input::KeyboardAndMouse::{GetAsyncKeyState, GetKeyState, KEYBD_EVENT_FLAGS, KEYBDINPUT, KEYEVENTF_EXTENDEDKEY, KEYEVENTF_UNICODE, SendInput, INPUT, INPUT_0, INPUT_KEYBOARD, KEYEVENTF_KEYUP, VIRTUAL_KEY, VK_CAPITAL, VK_CONTROL, VK_LWIN, VK_MENU, VK_NUMLOCK, VK_RWIN, VK_SCROLL, VK_SHIFT};
+use crate::delay::{typing_steps, TypingOptions};
+use crate::key::key_info;
+use crate::send::{parse_send_sequence_inner, SendEventKind};
+use crate::utils::{handle_input, handle_result};
 
-#[napi]
-#[derive(Debug, PartialEq)]
-pub enum Modifiers {
-    Alt = 0x01,
-    AltGraph = 0x2,
-    CapsLock = 0x4,
-    Control = 0x8,
-    Fn = 0x10,
-    FnLock = 0x20,
-    Meta = 0x40,
-    NumLock = 0x80,
-    ScrollLock = 0x100,
-    Shift = 0x200,
-    Symbol = 0x400,
-    SymbolLock = 0x800,
-    Hyper = 0x1000,
-    Super = 0x2000,
-}
-
-
-#[napi]
-#[derive(Debug, PartialEq)]
-pub enum Key {
-    None = 0,
-    Back = 8,
-    Tab = 9,
-    LineFeed = 10,
-    Clear = 12,
-    Enter = 13,
-    Shift = 16,
-    Control = 17,
-    Alt = 18,
-    Pause = 19,
-    CapsLock = 20,
-    Esc = 27,
-    Space = 32,
-    PageUp = 33,
-    PageDown = 34,
-    End = 35,
-    Home = 36,
-    ArrowLeft = 37,
-    ArrowUp = 38,
-    ArrowRight = 39,
-    ArrowDown = 40,
-    Insert = 45,
-    Delete = 46,
-    D0 = 48,
-    D1 = 49,
-    D2 = 50,
-    D3 = 51,
-    D4 = 52,
-    D5 = 53,
-    D6 = 54,
-    D7 = 55,
-    D8 = 56,
-    D9 = 57,
-    A = 65,
-    B = 66,
-    C = 67,
-    D = 68,
-    E = 69,
-    F = 70,
-    G = 71,
-    H = 72,
-    I = 73,
-    J = 74,
-    K = 75,
-    L = 76,
-    M = 77,
-    N = 78,
-    O = 79,
-    P = 80,
-    Q = 81,
-    R = 82,
-    S = 83,
-    T = 84,
-    U = 85,
-    V = 86,
-    W = 87,
-    X = 88,
-    Y = 89,
-    Z = 90,
-    LeftWin = 91,
-    RightWin = 92,
-    Apps = 93,
-    Sleep = 95,
-    NumPad0 = 96,
-    NumPad1 = 97,
-    NumPad2 = 98,
-    NumPad3 = 99,
-    NumPad4 = 100,
-    NumPad5 = 101,
-    NumPad6 = 102,
-    NumPad7 = 103,
-    NumPad8 = 104,
-    NumPad9 = 105,
-    Multiply = 106,
-    Add = 107,
-    Separator = 108,
-    Subtract = 109,
-    Decimal = 110,
-    Divide = 111,
-    F1 = 112,
-    F2 = 113,
-    F3 = 114,
-    F4 = 115,
-    F5 = 116,
-    F6 = 117,
-    F7 = 118,
-    F8 = 119,
-    F9 = 120,
-    F10 = 121,
-    F11 = 122,
-    F12 = 123,
-    F13 = 124,
-    F14 = 125,
-    F15 = 126,
-    F16 = 127,
-    F17 = 128,
-    F18 = 129,
-    F19 = 130,
-    F20 = 131,
-    F21 = 132,
-    F22 = 133,
-    F23 = 134,
-    F24 = 135,
-    NumLock = 144,
-    ScrollLock = 145,
-    LeftShift = 160,
-    RightShift = 161,
-    LeftControl = 162,
-    RightControl = 163,
-    LeftAlt = 164,
-    RightAlt = 165,
-}
-
-impl Display for Key {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let str = match self {
-            Key::None => "None".to_string(),
-            Key::Back => "Back".to_string(),
-            Key::Tab => "Tab".to_string(),
-            Key::LineFeed => "LineFeed".to_string(),
-            Key::Clear => "Clear".to_string(),
-            Key::Enter => "Enter".to_string(),
-            Key::Shift => "Shift".to_string(),
-            Key::Control => "Control".to_string(),
-            Key::Alt => "Alt".to_string(),
-            Key::Pause => "Pause".to_string(),
-            Key::CapsLock => "CapsLock".to_string(),
-            Key::Esc => "Esc".to_string(),
-            Key::Space => "Space".to_string(),
-            Key::PageUp => "PageUp".to_string(),
-            Key::PageDown => "PageDown".to_string(),
-            Key::End => "End".to_string(),
-            Key::Home => "Home".to_string(),
-            Key::ArrowLeft => "ArrowLeft".to_string(),
-            Key::ArrowUp => "ArrowUp".to_string(),
-            Key::ArrowRight => "ArrowRight".to_string(),
-            Key::ArrowDown => "ArrowDown".to_string(),
-            Key::Insert => "Insert".to_string(),
-            Key::Delete => "Delete".to_string(),
-            Key::D0 => "D0".to_string(),
-            Key::D1 => "D1".to_string(),
-            Key::D2 => "D2".to_string(),
-            Key::D3 => "D3".to_string(),
-            Key::D4 => "D4".to_string(),
-            Key::D5 => "D5".to_string(),
-            Key::D6 => "D6".to_string(),
-            Key::D7 => "D7".to_string(),
-            Key::D8 => "D8".to_string(),
-            Key::D9 => "D9".to_string(),
-            Key::A => "A".to_string(),
-            Key::B => "B".to_string(),
-            Key::C => "C".to_string(),
-            Key::D => "D".to_string(),
-            Key::E => "E".to_string(),
-            Key::F => "F".to_string(),
-            Key::G => "G".to_string(),
-            Key::H => "H".to_string(),
-            Key::I => "I".to_string(),
-            Key::J => "J".to_string(),
-            Key::K => "K".to_string(),
-            Key::L => "L".to_string(),
-            Key::M => "M".to_string(),
-            Key::N => "N".to_string(),
-            Key::O => "O".to_string(),
-            Key::P => "P".to_string(),
-            Key::Q => "Q".to_string(),
-            Key::R => "R".to_string(),
-            Key::S => "S".to_string(),
-            Key::T => "T".to_string(),
-            Key::U => "U".to_string(),
-            Key::V => "V".to_string(),
-            Key::W => "W".to_string(),
-            Key::X => "X".to_string(),
-            Key::Y => "Y".to_string(),
-            Key::Z => "Z".to_string(),
-            Key::LeftWin => "LeftWin".to_string(),
-            Key::RightWin => "RightWin".to_string(),
-            Key::Apps => "Apps".to_string(),
-            Key::Sleep => "Sleep".to_string(),
-            Key::NumPad0 => "NumPad0".to_string(),
-            Key::NumPad1 => "NumPad1".to_string(),
-            Key::NumPad2 => "NumPad2".to_string(),
-            Key::NumPad3 => "NumPad3".to_string(),
-            Key::NumPad4 => "NumPad4".to_string(),
-            Key::NumPad5 => "NumPad5".to_string(),
-            Key::NumPad6 => "NumPad6".to_string(),
-            Key::NumPad7 => "NumPad7".to_string(),
-            Key::NumPad8 => "NumPad8".to_string(),
-            Key::NumPad9 => "NumPad9".to_string(),
-            Key::Multiply => "Multiply".to_string(),
-            Key::Add => "Add".to_string(),
-            Key::Separator => "Separator".to_string(),
-            Key::Subtract => "Subtract".to_string(),
-            Key::Decimal => "Decimal".to_string(),
-            Key::Divide => "Divide".to_string(),
-            Key::F1 => "F1".to_string(),
-            Key::F2 => "F2".to_string(),
-            Key::F3 => "F3".to_string(),
-            Key::F4 => "F4".to_string(),
-            Key::F5 => "F5".to_string(),
-            Key::F6 => "F6".to_string(),
-            Key::F7 => "F7".to_string(),
-            Key::F8 => "F8".to_string(),
-            Key::F9 => "F9".to_string(),
-            Key::F10 => "F10".to_string(),
-            Key::F11 => "F11".to_string(),
-            Key::F12 => "F12".to_string(),
-            Key::F13 => "F13".to_string(),
-            Key::F14 => "F14".to_string(),
-            Key::F15 => "F15".to_string(),
-            Key::F16 => "F16".to_string(),
-            Key::F17 => "F17".to_string(),
-            Key::F18 => "F18".to_string(),
-            Key::F19 => "F19".to_string(),
-            Key::F20 => "F20".to_string(),
-            Key::F21 => "F21".to_string(),
-            Key::F22 => "F22".to_string(),
-            Key::F23 => "F23".to_string(),
-            Key::F24 => "F24".to_string(),
-            Key::NumLock => "NumLock".to_string(),
-            Key::ScrollLock => "ScrollLock".to_string(),
-            Key::LeftShift => "LeftShift".to_string(),
-            Key::RightShift => "RightShift".to_string(),
-            Key::LeftControl => "LeftControl".to_string(),
-            Key::RightControl => "RightControl".to_string(),
-            Key::LeftAlt => "LeftAlt".to_string(),
-            Key::RightAlt => "RightAlt".to_string(),
-        };
-        write!(f, "{}", str)
-    }
-}
-
-lazy_static! {
-    static ref GLOBAL_HOTKEY_PREPARED: Mutex<bool> = Mutex::new(false);
-    static ref GLOBAL_HOTKEY_CHANNEL: (Sender<GlobalHotkeyMessage>, Receiver<GlobalHotkeyMessage>) = unbounded();
-    static ref GLOBAL_HOTKEY_CALLBACKS: Mutex<HashMap<u32, Vec<ThreadsafeFunction<()>>>> = Mutex::new(HashMap::new());
-}
-
-#[derive(Debug)]
-pub struct Hotkey {
-    id: u32,
-    key: Key,
-    mods: Vec<Modifiers>,
-}
-
-impl Hotkey {
-    pub fn new(mods: Vec<Modifiers>, key: Key) -> Self {
-        let id = Hotkey::generate_hash(mods.clone(), key);
-
-        Self {
-            id,
-            key,
-            mods,
-        }
-    }
-
-    pub fn generate_hash(mods: Vec<Modifiers>, key: Key) -> u32 {
-        let mut hotkey_str = String::new();
-        if mods.contains(&Modifiers::Shift) {
-            hotkey_str.push_str("Shift+");
-        }
-
-        if mods.contains(&Modifiers::Control) {
-            hotkey_str.push_str("Control+");
-        }
-
-        if mods.contains(&Modifiers::Alt) {
-            hotkey_str.push_str("Alt+");
-        }
-
-        if mods.contains(&Modifiers::Super) {
-            hotkey_str.push_str("Super+");
-        }
-
-        hotkey_str.push_str(&key.to_string());
-
-        let mut hasher = std::collections::hash_map::DefaultHasher::new();
-        hotkey_str.hash(&mut hasher);
-        std::hash::Hasher::finish(&hasher) as u32
-    }
-}
-
-enum GlobalHotkeyMessage {
-    Register(Hotkey),
-    Unregister(u32),
-}
-
-
-fn prepare_global_hotkey() {
-    std::thread::spawn(move || {
-        unsafe {
-            let class_name = PCWSTR(encode_wide("global_hotkey_manager").as_ptr());
-            let hinstance = GetModuleHandleW(None).unwrap();
-
-            let mut wnd_class = WNDCLASSW::default();
-            wnd_class.lpfnWndProc = Some(global_hotkey_proc);
-            wnd_class.lpszClassName = class_name;
-            wnd_class.hInstance = HINSTANCE(hinstance.0 as _);
-
-            RegisterClassW(&wnd_class);
-
-            let hwnd = CreateWindowExW(
-                WS_EX_NOACTIVATE | WS_EX_TRANSPARENT | WS_EX_LAYERED | WS_EX_TOOLWINDOW,
-                class_name,
-                None,
-                WS_OVERLAPPED,
-                CW_USEDEFAULT,
-                0,
-                CW_USEDEFAULT,
-                0,
-                HWND::default(),
-                HMENU::default(),
-                hinstance,
-                None
-            );
-
-            let mut msg = std::mem::zeroed();
-
-            loop {
-                while PeekMessageW(&mut msg, None, 0, 0, PM_REMOVE).0 != 0 {
-                    if msg.message == WM_QUIT {
-                        break;
-                    }
-
-                    TranslateMessage(&msg);
-                    DispatchMessageW(&msg);
-                }
-
-                while let Ok(msg) = GLOBAL_HOTKEY_CHANNEL.1.try_recv() {
-                    match msg {
-                        GlobalHotkeyMessage::Register(hotkey) => {
-                            let mut mods = MOD_NOREPEAT;
-
-                            hotkey.mods.iter().for_each(|it| {
-                                match it {
-                                    Modifiers::Shift => mods |= MOD_SHIFT,
-                                    Modifiers::Super | Modifiers::Meta => mods |= MOD_CONTROL,
-                                    Modifiers::Alt => mods |= MOD_ALT,
-                                    Modifiers::Control => mods |= MOD_CONTROL,
-                                    _ => {}
-                                }
-                            });
-
-                            // @todo: error handling
-                            let _ = RegisterHotKey(hwnd, hotkey.id as i32, mods, hotkey.key as u32);
-                        }
-                        GlobalHotkeyMessage::Unregister(id) => {
-                            // @todo: error handling
-                            let _ = UnregisterHotKey(hwnd, id as i32);
-                        }
-                    }
-                }
-            }
-        }
-    });
-}
-
-unsafe extern "system" fn global_hotkey_proc(
-    hwnd: HWND,
-    msg: u32,
-    wparam: WPARAM,
-    lparam: LPARAM,
-) -> LRESULT {
-    if msg == WM_HOTKEY {
-        let id = wparam.0 as u32;
-
-        let callbacks = GLOBAL_HOTKEY_CALLBACKS.lock().unwrap();
-        if let Some(vec) = callbacks.get(&id) {
-            vec.iter().for_each(|it| {
-                it.call(Ok(()), ThreadsafeFunctionCallMode::NonBlocking);
-            });
-        }
-    }
-
-    DefWindowProcW(hwnd, msg, wparam, lparam)
-}
+pub use crate::key::Key;
+pub use crate::modifiers::Modifiers;
 
 #[napi]
 pub struct Keyboard {
@@ -425,83 +20,86 @@ impl Keyboard {
     pub async fn press(key: Key) -> Result<()> {
         let task = tokio::spawn(async move {
             unsafe {
-                let mut input = INPUT::default();
-                input.r#type = INPUT_KEYBOARD;
-                input.Anonymous.ki.wVk = VIRTUAL_KEY(key as u16);
-                input.Anonymous.ki.dwFlags = KEYBD_EVENT_FLAGS::from(KEYEVENTF_UNICODE);
-                input.Anonymous.ki.time = 0;
-                SendInput(&[input], std::mem::size_of::<INPUT>() as i32);
+                SendInput(&[key_input(key, false)], std::mem::size_of::<INPUT>() as i32);
             }
 
             Ok(())
         });
 
-        handle_result(task).await
+        handle_input(task).await
     }
 
     #[napi]
     pub async fn release(key: Key) -> Result<()> {
         let task = tokio::spawn(async move {
             unsafe {
-                let mut input = INPUT::default();
-                input.r#type = INPUT_KEYBOARD;
-                input.Anonymous.ki.wVk = VIRTUAL_KEY(key as u16);
-                input.Anonymous.ki.dwFlags = KEYBD_EVENT_FLAGS::from(KEYEVENTF_UNICODE | KEYEVENTF_KEYUP);
-                input.Anonymous.ki.time = 0;
-                SendInput(&[input], std::mem::size_of::<INPUT>() as i32);
+                SendInput(&[key_input(key, true)], std::mem::size_of::<INPUT>() as i32);
             }
 
             Ok(())
         });
 
-        handle_result(task).await
+        handle_input(task).await
     }
 
     #[napi]
     pub async fn click(key: Key) -> Result<()> {
         let task = tokio::spawn(async move {
             unsafe {
-                let mut inputs = Vec::new();
+                SendInput(&[key_input(key, false), key_input(key, true)], std::mem::size_of::<INPUT>() as i32);
+            }
 
-                let mut input = INPUT::default();
-                input.r#type = INPUT_KEYBOARD;
-                input.Anonymous.ki.wVk = VIRTUAL_KEY(key as u16);
-                input.Anonymous.ki.dwFlags = KEYBD_EVENT_FLAGS::from(KEYEVENTF_UNICODE);
-                input.Anonymous.ki.time = 0;
-                inputs.push(input);
+            Ok(())
+        });
 
-                input.Anonymous.ki.dwFlags |= KEYEVENTF_KEYUP;
-                inputs.push(input);
+        handle_input(task).await
+    }
 
-                SendInput(inputs.as_slice(), std::mem::size_of::<INPUT>() as i32);
+    /// Types `text` as Unicode characters, independent of the keyboard layout, with the pauses
+    /// `options` asks for.
+    #[napi]
+    pub async fn typing(text: String, options: Option<TypingOptions>) -> Result<()> {
+        let task = tokio::spawn(async move {
+            for step in typing_steps(&text, &options.unwrap_or_default()) {
+                if step.hold.is_zero() {
+                    send_text(&step.text, true, true);
+                } else {
+                    send_text(&step.text, true, false);
+                    tokio::time::sleep(step.hold).await;
+                    send_text(&step.text, false, true);
+                }
+
+                tokio::time::sleep(step.pause).await;
             }
 
             Ok(())
         });
 
-        handle_result(task).await
+        handle_input(task).await
     }
 
+    /// Sends a key sequence in AutoHotkey's Send syntax, e.g. "Hello{Enter}^a{Tab 3}", see
+    /// `parseSendSequence`.
     #[napi]
-    pub async fn typing(text: String) -> Result<()> {
+    pub async fn send(sequence: String) -> Result<()> {
         let task = tokio::spawn(async move {
-            unsafe {
-                let text = text
-                    .encode_utf16()
-                    .collect::<Vec<_>>();
+            let events = parse_send_sequence_inner(&sequence)?;
 
+            unsafe {
                 let mut inputs = Vec::new();
 
-                for c in text {
-                    let mut input = INPUT::default();
-                    input.r#type = INPUT_KEYBOARD;
-                    input.Anonymous.ki.dwFlags = KEYEVENTF_UNICODE;
-                    input.Anonymous.ki.wScan = c;
-                    input.Anonymous.ki.time = 0;
-                    inputs.push(input);
-
-                    input.Anonymous.ki.dwFlags |= KEYEVENTF_KEYUP;
-                    inputs.push(input);
+                for event in events {
+                    match (event.kind, event.key, event.text) {
+                        (SendEventKind::Press, Some(key), _) => inputs.push(key_input(key, false)),
+                        (SendEventKind::Release, Some(key), _) => inputs.push(key_input(key, true)),
+                        (SendEventKind::Text, _, Some(text)) => {
+                            for unit in text.encode_utf16() {
+                                inputs.push(unicode_input(unit, false));
+                                inputs.push(unicode_input(unit, true));
+                            }
+                        }
+                        _ => {}
+                    }
                 }
 
                 SendInput(inputs.as_slice(), std::mem::size_of::<INPUT>() as i32);
@@ -510,35 +108,104 @@ impl Keyboard {
             Ok(())
         });
 
-        handle_result(task).await
+        handle_input(task).await
     }
 
+    /// The modifiers held down right now, plus `CapsLock`, `NumLock` and `ScrollLock` when those
+    /// are toggled on.
     #[napi]
-    pub fn register_hotkey(mods: Vec<Modifiers>, key: Key, callback: JsFunction) -> u32 {
-        if !*GLOBAL_HOTKEY_PREPARED.lock().unwrap() {
-            prepare_global_hotkey();
-            *GLOBAL_HOTKEY_PREPARED.lock().unwrap() = true;
+    pub async fn get_modifiers() -> Result<Vec<Modifiers>> {
+        let task = tokio::spawn(async move {
+            Ok(get_modifiers_inner())
+        });
+
+        handle_result(task).await
+    }
+}
+
+/// Sends the characters of `text` as Unicode key events in one batch, pressing and releasing
+/// each as asked.
+fn send_text(text: &str, down: bool, up: bool) {
+    let mut inputs = Vec::new();
+
+    for unit in text.encode_utf16() {
+        if down {
+            inputs.push(unicode_input(unit, false));
+        }
+        if up {
+            inputs.push(unicode_input(unit, true));
         }
+    }
 
-        let tsfn: ThreadsafeFunction<()> = callback.create_threadsafe_function(0, |_ctx| {
-            Ok(vec![0])
-        }).unwrap();
+    unsafe {
+        SendInput(inputs.as_slice(), std::mem::size_of::<INPUT>() as i32);
+    }
+}
+
+/// A `SendInput` event pressing or releasing `key`. It carries the scan code too, for the
+/// applications reading that, and is flagged as extended for the keys prefixed with 0xE0.
+pub fn key_input(key: Key, up: bool) -> INPUT {
+    let info = key_info(key);
+    let mut flags = KEYBD_EVENT_FLAGS(0);
+    if info.scan_code >> 8 == 0xe0 {
+        flags |= KEYEVENTF_EXTENDEDKEY;
+    }
+    if up {
+        flags |= KEYEVENTF_KEYUP;
+    }
 
-        let hotkey = Hotkey::new(mods, key);
-        let mut callbacks = GLOBAL_HOTKEY_CALLBACKS.lock().unwrap();
-        let id = hotkey.id;
-        let vec: &mut Vec<ThreadsafeFunction<()>> = callbacks.entry(id).or_insert(Vec::new());
-        vec.push(tsfn);
+    keyboard_input(VIRTUAL_KEY(info.vk), info.scan_code & 0xff, flags)
+}
 
-        GLOBAL_HOTKEY_CHANNEL.0.send(GlobalHotkeyMessage::Register(hotkey)).unwrap();
+/// A `SendInput` event typing or lifting one UTF-16 unit, independent of the keyboard layout.
+pub fn unicode_input(unit: u16, up: bool) -> INPUT {
+    let flags = if up { KEYEVENTF_UNICODE | KEYEVENTF_KEYUP } else { KEYEVENTF_UNICODE };
+    keyboard_input(VIRTUAL_KEY(0), unit, flags)
+}
 
-        id
+/// A `SendInput` keyboard event. Built as a whole, since writing to a field of the `INPUT`
+/// union after the fact needs `unsafe`.
+pub fn keyboard_input(vk: VIRTUAL_KEY, scan: u16, flags: KEYBD_EVENT_FLAGS) -> INPUT {
+    INPUT {
+        r#type: INPUT_KEYBOARD,
+        Anonymous: INPUT_0 {
+            ki: KEYBDINPUT {
+                wVk: vk,
+                wScan: scan,
+                dwFlags: flags,
+                ..Default::default()
+            },
+        },
     }
+}
 
-    #[napi]
-    pub fn unregister_hotkey(id: u32) {
-        let mut callbacks = GLOBAL_HOTKEY_CALLBACKS.lock().unwrap();
-        callbacks.remove(&id);
-        GLOBAL_HOTKEY_CHANNEL.0.send(GlobalHotkeyMessage::Unregister(id)).unwrap();
+/// The modifiers held down right now, plus the lock keys that are toggled on.
+pub fn get_modifiers_inner() -> Vec<Modifiers> {
+    let is_down = |key: VIRTUAL_KEY| unsafe { GetAsyncKeyState(key.0 as i32) } < 0;
+    let is_toggled = |key: VIRTUAL_KEY| unsafe { GetKeyState(key.0 as i32) } & 1 != 0;
+
+    let mut mods = Vec::new();
+    if is_down(VK_SHIFT) {
+        mods.push(Modifiers::Shift);
+    }
+    if is_down(VK_CONTROL) {
+        mods.push(Modifiers::Control);
+    }
+    if is_down(VK_MENU) {
+        mods.push(Modifiers::Alt);
     }
-}
\ No newline at end of file
+    if is_down(VK_LWIN) || is_down(VK_RWIN) {
+        mods.push(Modifiers::Meta);
+    }
+    if is_toggled(VK_CAPITAL) {
+        mods.push(Modifiers::CapsLock);
+    }
+    if is_toggled(VK_NUMLOCK) {
+        mods.push(Modifiers::NumLock);
+    }
+    if is_toggled(VK_SCROLL) {
+        mods.push(Modifiers::ScrollLock);
+    }
+
+    mods
+}
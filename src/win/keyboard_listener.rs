@@ -0,0 +1,173 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+use lazy_static::lazy_static;
+use napi::bindgen_prelude::*;
+use napi::threadsafe_function::{ErrorStrategy, ThreadsafeFunction, ThreadsafeFunctionCallMode};
+use napi_derive::napi;
+use windows::Win32::Foundation::{HINSTANCE, LPARAM, LRESULT, WPARAM};
+use windows::Win32::System::LibraryLoader::GetModuleHandleW;
+use windows::Win32::System::Threading::GetCurrentThreadId;
+use windows::Win32::UI::WindowsAndMessaging::{CallNextHookEx, DispatchMessageW, GetMessageW, HC_ACTION, HHOOK, KBDLLHOOKSTRUCT, PostThreadMessageW, SetWindowsHookExW, TranslateMessage, UnhookWindowsHookEx, WH_KEYBOARD_LL, WM_KEYDOWN, WM_KEYUP, WM_QUIT, WM_SYSKEYDOWN, WM_SYSKEYUP};
+use crate::key::key_from_vk;
+use super::hotstring::{has_hotstrings, hotstring_key_event};
+use super::keyboard::{get_modifiers_inner, Key, Keyboard, Modifiers};
+
+#[napi]
+#[derive(Debug, PartialEq)]
+pub enum KeyboardEventKind {
+    Down,
+    Up,
+}
+
+#[napi(object)]
+#[derive(Debug, Clone)]
+pub struct KeyboardEvent {
+    pub kind: KeyboardEventKind,
+    /// The key, when `Key` has a variant for it.
+    pub key: Option<Key>,
+    /// The layout-dependent key code: the virtual-key code on Windows, the keysym on X11.
+    pub code: u32,
+    /// The layout-independent key code: the scan code on Windows, the keycode on X11.
+    pub scan_code: u32,
+    /// The modifiers held when the event happened, not counting the key itself.
+    pub modifiers: Vec<Modifiers>,
+    /// Milliseconds since the Unix epoch.
+    pub timestamp: f64,
+}
+
+lazy_static! {
+    static ref KEYBOARD_LISTENERS: Mutex<HashMap<u32, ThreadsafeFunction<KeyboardEvent, ErrorStrategy::Fatal>>> = Mutex::new(HashMap::new());
+    static ref KEYBOARD_HOOK_THREAD: Mutex<Option<u32>> = Mutex::new(None);
+}
+
+static NEXT_LISTENER_ID: AtomicU32 = AtomicU32::new(1);
+
+#[napi]
+impl Keyboard {
+    /// Calls `callback` with every key press and release, including synthetic ones and those of
+    /// registered hotkeys. Returns an id for `unlisten`.
+    #[napi(ts_args_type = "callback: (event: KeyboardEvent) => void")]
+    pub fn listen(callback: JsFunction) -> Result<u32> {
+        let tsfn: ThreadsafeFunction<KeyboardEvent, ErrorStrategy::Fatal> = callback.create_threadsafe_function(0, |ctx| {
+            Ok(vec![ctx.value])
+        })?;
+
+        let id = NEXT_LISTENER_ID.fetch_add(1, Ordering::Relaxed);
+        KEYBOARD_LISTENERS.lock().unwrap().insert(id, tsfn);
+
+        if let Err(e) = start_interception() {
+            KEYBOARD_LISTENERS.lock().unwrap().remove(&id);
+            return Err(Error::new(Status::GenericFailure, e));
+        }
+
+        Ok(id)
+    }
+
+    #[napi]
+    pub fn unlisten(id: u32) {
+        KEYBOARD_LISTENERS.lock().unwrap().remove(&id);
+        stop_interception_if_unused();
+    }
+}
+
+/// Installs the keyboard hook unless it already is, for listeners and hotstrings alike.
+pub fn start_interception() -> std::result::Result<(), String> {
+    let mut thread = KEYBOARD_HOOK_THREAD.lock().unwrap();
+    if thread.is_none() {
+        *thread = Some(start_hook()?);
+    }
+
+    Ok(())
+}
+
+/// Removes the keyboard hook once no listener and no hotstring is left.
+pub fn stop_interception_if_unused() {
+    if !KEYBOARD_LISTENERS.lock().unwrap().is_empty() || has_hotstrings() {
+        return;
+    }
+
+    if let Some(thread) = KEYBOARD_HOOK_THREAD.lock().unwrap().take() {
+        unsafe {
+            let _ = PostThreadMessageW(thread, WM_QUIT, WPARAM(0), LPARAM(0));
+        }
+    }
+}
+
+/// Installs a low-level keyboard hook on a thread of its own, which has to keep pumping messages
+/// for the hook to be called. Returns the id of that thread, to post `WM_QUIT` to.
+fn start_hook() -> std::result::Result<u32, String> {
+    let (sender, receiver) = std::sync::mpsc::channel();
+
+    std::thread::spawn(move || unsafe {
+        let hinstance = match GetModuleHandleW(None) {
+            Ok(module) => HINSTANCE(module.0 as _),
+            Err(e) => {
+                let _ = sender.send(Err(format!("Failed to get the module handle: {:?}", e)));
+                return;
+            }
+        };
+
+        let hook = match SetWindowsHookExW(WH_KEYBOARD_LL, Some(keyboard_hook_proc), hinstance, 0) {
+            Ok(hook) => hook,
+            Err(e) => {
+                let _ = sender.send(Err(format!("Failed to install the keyboard hook: {:?}", e)));
+                return;
+            }
+        };
+
+        let _ = sender.send(Ok(GetCurrentThreadId()));
+
+        let mut msg = std::mem::zeroed();
+        while GetMessageW(&mut msg, None, 0, 0).0 > 0 {
+            TranslateMessage(&msg);
+            DispatchMessageW(&msg);
+        }
+
+        let _ = UnhookWindowsHookEx(hook);
+    });
+
+    match receiver.recv() {
+        Ok(result) => result,
+        Err(e) => Err(format!("Failed to start the keyboard hook thread: {:?}", e)),
+    }
+}
+
+unsafe extern "system" fn keyboard_hook_proc(code: i32, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
+    if code == HC_ACTION as i32 {
+        let info = &*(lparam.0 as *const KBDLLHOOKSTRUCT);
+        hotstring_key_event(wparam.0 as u32, info);
+
+        if let Some(event) = parse_hook_event(wparam.0 as u32, info) {
+            let listeners = KEYBOARD_LISTENERS.lock().unwrap();
+            for listener in listeners.values() {
+                listener.call(event.clone(), ThreadsafeFunctionCallMode::NonBlocking);
+            }
+        }
+    }
+
+    CallNextHookEx(HHOOK::default(), code, wparam, lparam)
+}
+
+/// Turns a low-level hook message into a keyboard event. The hook runs before the key state is
+/// updated, so the modifiers are those held before this key.
+fn parse_hook_event(message: u32, info: &KBDLLHOOKSTRUCT) -> Option<KeyboardEvent> {
+    let kind = match message {
+        WM_KEYDOWN | WM_SYSKEYDOWN => KeyboardEventKind::Down,
+        WM_KEYUP | WM_SYSKEYUP => KeyboardEventKind::Up,
+        _ => return None,
+    };
+
+    Some(KeyboardEvent {
+        kind,
+        key: key_from_vk(info.vkCode),
+        code: info.vkCode,
+        scan_code: info.scanCode,
+        modifiers: get_modifiers_inner(),
+        timestamp: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|it| it.as_secs_f64() * 1000.0)
+            .unwrap_or(0.0),
+    })
+}
@@ -1,5 +1,10 @@
+pub mod cursor;
+pub mod hotkey;
+pub mod hotstring;
 pub mod keyboard;
+pub mod keyboard_listener;
 pub mod mouse;
+pub mod mouse_listener;
 pub mod screen;
 pub mod window;
 pub mod memory;
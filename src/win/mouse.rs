@@ -1,17 +1,44 @@
 use napi::bindgen_prelude::*;
 use napi_derive::napi;
-use windows::Win32::UI::Input::KeyboardAndMouse::{MOUSE_EVENT_FLAGS, MOUSEEVENTF_ABSOLUTE, MOUSEEVENTF_LEFTDOWN, MOUSEEVENTF_LEFTUP, MOUSEEVENTF_MIDDLEDOWN, MOUSEEVENTF_MIDDLEUP, MOUSEEVENTF_MOVE, MOUSEEVENTF_RIGHTDOWN, MOUSEEVENTF_RIGHTUP};
-use windows::Win32::UI::WindowsAndMessaging::{GetCursorPos, GetSystemMetrics, SM_CXSCREEN, SM_CYSCREEN};
+use windows::Win32::UI::Input::KeyboardAndMouse::{GetAsyncKeyState, GetDoubleClickTime, MOUSE_EVENT_FLAGS, MOUSEEVENTF_ABSOLUTE, MOUSEEVENTF_HWHEEL, MOUSEEVENTF_LEFTDOWN, MOUSEEVENTF_LEFTUP, MOUSEEVENTF_MIDDLEDOWN, MOUSEEVENTF_MIDDLEUP, MOUSEEVENTF_MOVE, MOUSEEVENTF_RIGHTDOWN, MOUSEEVENTF_RIGHTUP, MOUSEEVENTF_VIRTUALDESK, MOUSEEVENTF_WHEEL, MOUSEEVENTF_XDOWN, MOUSEEVENTF_XUP, VK_LBUTTON, VK_MBUTTON, VK_RBUTTON, VK_XBUTTON1, VK_XBUTTON2};
+use windows::Win32::UI::WindowsAndMessaging::{GetCursorPos, GetSystemMetrics, SM_SWAPBUTTON, WHEEL_DELTA, XBUTTON1, XBUTTON2};
+use crate::desktop::normalize_absolute;
 use crate::geometry::Point;
-use crate::utils::handle_result;
+use crate::mouse_path::{generate_mouse_path, relative_mouse_steps, MoveOptions};
+use crate::utils::{handle_input, handle_result};
+use super::screen::{ensure_dpi_awareness, get_virtual_desktop_bounds};
 
 #[napi]
+#[derive(Debug)]
 pub enum MouseButton {
     Left,
     Right,
     Middle,
+    /// The "back" side button.
+    X1,
+    /// The "forward" side button.
+    X2,
 }
 
+#[napi]
+#[derive(Debug)]
+pub enum ScrollAxis {
+    Vertical,
+    Horizontal,
+}
+
+#[napi(object)]
+pub struct DragOptions {
+    /// The button held during the drag, `Left` by default.
+    pub button: Option<MouseButton>,
+    /// Milliseconds to wait after pressing and before releasing the button, 50 by default.
+    pub hold: Option<u32>,
+    /// How to move between the two points, as in `moveSmooth`.
+    pub movement: Option<MoveOptions>,
+}
+
+const DEFAULT_DRAG_HOLD: u32 = 50;
+
 #[napi]
 pub struct Mouse {
 
@@ -27,60 +54,179 @@ impl Mouse {
             Ok(())
         });
 
-        handle_result(task).await
+        handle_input(task).await
+    }
+
+    /// Moves to (`x`, `y`) through intermediate points instead of jumping there, see
+    /// `generateMousePath`.
+    #[napi]
+    pub async fn move_smooth(x: i32, y: i32, options: Option<MoveOptions>) -> Result<()> {
+        let task = tokio::spawn(async move {
+            move_smooth_inner(x, y, options.unwrap_or_default()).await;
+
+            Ok(())
+        });
+
+        handle_input(task).await
+    }
+
+    /// Moves by (`dx`, `dy`) mickeys as raw relative motion, which games that capture the mouse
+    /// read instead of the cursor position. The system pointer speed and acceleration settings
+    /// apply, so the cursor may travel a different number of pixels.
+    #[napi]
+    pub async fn move_relative(dx: i32, dy: i32) -> Result<()> {
+        let task = tokio::spawn(async move {
+            mouse_event(MOUSEEVENTF_MOVE, dx, dy, 0, 0);
+
+            Ok(())
+        });
+
+        handle_input(task).await
+    }
+
+    /// Moves by (`dx`, `dy`) in small relative steps along a path like `moveSmooth`'s.
+    #[napi]
+    pub async fn move_relative_smooth(dx: i32, dy: i32, options: Option<MoveOptions>) -> Result<()> {
+        let task = tokio::spawn(async move {
+            let options = options.unwrap_or_default();
+            let interval = options.interval();
+
+            let start = tokio::time::Instant::now();
+            for (i, (step_x, step_y)) in relative_mouse_steps(dx, dy, Some(options)).into_iter().enumerate() {
+                if step_x != 0 || step_y != 0 {
+                    mouse_event(MOUSEEVENTF_MOVE, step_x, step_y, 0, 0);
+                }
+                tokio::time::sleep_until(start + interval * (i as u32 + 1)).await;
+            }
+
+            Ok(())
+        });
+
+        handle_input(task).await
     }
 
     #[napi]
     pub async fn press(button: MouseButton) -> Result<()> {
         let task = tokio::spawn(async move {
-            let down = match button {
-                MouseButton::Left => MOUSEEVENTF_LEFTDOWN,
-                MouseButton::Right => MOUSEEVENTF_RIGHTDOWN,
-                MouseButton::Middle => MOUSEEVENTF_MIDDLEDOWN,
-            };
+            let (down, _, data) = button_flags(button);
 
-            mouse_event(down, 0, 0, 0, 0);
+            mouse_event(down, 0, 0, data, 0);
 
             Ok(())
         });
 
-        handle_result(task).await
+        handle_input(task).await
     }
 
     #[napi]
     pub async fn release(button: MouseButton) -> Result<()> {
         let task = tokio::spawn(async move {
-            let up = match button {
-                MouseButton::Left => MOUSEEVENTF_LEFTUP,
-                MouseButton::Right => MOUSEEVENTF_RIGHTUP,
-                MouseButton::Middle => MOUSEEVENTF_MIDDLEUP,
-            };
+            let (_, up, data) = button_flags(button);
 
-            mouse_event(up, 0, 0, 0, 0);
+            mouse_event(up, 0, 0, data, 0);
 
             Ok(())
         });
 
-        handle_result(task).await
+        handle_input(task).await
     }
 
     #[napi]
     pub async fn click(button: MouseButton, x: i32, y: i32) -> Result<()> {
         let task = tokio::spawn(async move {
-            let (down, up) = match button {
-                MouseButton::Left => (MOUSEEVENTF_LEFTDOWN, MOUSEEVENTF_LEFTUP),
-                MouseButton::Right => (MOUSEEVENTF_RIGHTDOWN, MOUSEEVENTF_RIGHTUP),
-                MouseButton::Middle => (MOUSEEVENTF_MIDDLEDOWN, MOUSEEVENTF_MIDDLEUP),
-            };
+            let (down, up, data) = button_flags(button);
 
             mouse_move_inner(x, y);
-            mouse_event(down, x, y, 0, 0);
-            mouse_event(up, x, y, 0, 0);
+            mouse_event(down, 0, 0, data, 0);
+            mouse_event(up, 0, 0, data, 0);
 
             Ok(())
         });
 
-        handle_result(task).await
+        handle_input(task).await
+    }
+
+    /// Turns the wheel by whole notches. Positive values scroll down (or right), like the deltas
+    /// of DOM wheel events.
+    #[napi]
+    pub async fn scroll(notches: i32, axis: Option<ScrollAxis>) -> Result<()> {
+        let task = tokio::spawn(async move {
+            scroll_inner(notches.saturating_mul(WHEEL_DELTA as i32), axis.unwrap_or(ScrollAxis::Vertical));
+
+            Ok(())
+        });
+
+        handle_input(task).await
+    }
+
+    /// Turns the wheel by `delta`, where 120 is one notch. Smaller deltas give the fine-grained
+    /// scrolling of touchpads and free-spinning wheels to applications that support it.
+    #[napi]
+    pub async fn scroll_delta(delta: i32, axis: Option<ScrollAxis>) -> Result<()> {
+        let task = tokio::spawn(async move {
+            scroll_inner(delta, axis.unwrap_or(ScrollAxis::Vertical));
+
+            Ok(())
+        });
+
+        handle_input(task).await
+    }
+
+    /// Presses `button` at `from`, moves to `to` along a smooth path and releases it there.
+    #[napi]
+    pub async fn drag(from: Point, to: Point, options: Option<DragOptions>) -> Result<()> {
+        let task = tokio::spawn(async move {
+            let (button, hold, movement) = match options {
+                Some(options) => (options.button, options.hold, options.movement),
+                None => (None, None, None),
+            };
+            let (down, up, data) = button_flags(button.unwrap_or(MouseButton::Left));
+            let hold = std::time::Duration::from_millis(hold.unwrap_or(DEFAULT_DRAG_HOLD) as u64);
+
+            mouse_move_inner(from.x, from.y);
+            mouse_event(down, 0, 0, data, 0);
+            tokio::time::sleep(hold).await;
+
+            move_smooth_inner(to.x, to.y, movement.unwrap_or_default()).await;
+            tokio::time::sleep(hold).await;
+
+            mouse_event(up, 0, 0, data, 0);
+
+            Ok(())
+        });
+
+        handle_input(task).await
+    }
+
+    #[napi]
+    pub async fn double_click(button: MouseButton, x: i32, y: i32) -> Result<()> {
+        let task = tokio::spawn(async move {
+            multi_click_inner(button, x, y, 2, None).await;
+
+            Ok(())
+        });
+
+        handle_input(task).await
+    }
+
+    /// Clicks `count` times in a row at (`x`, `y`), `interval` ms apart. The interval defaults to
+    /// a fifth of the double-click time and is kept below it, so that applications see a single
+    /// double (triple, ...) click.
+    #[napi]
+    pub async fn multi_click(button: MouseButton, x: i32, y: i32, count: u32, interval: Option<u32>) -> Result<()> {
+        let task = tokio::spawn(async move {
+            multi_click_inner(button, x, y, count, interval).await;
+
+            Ok(())
+        });
+
+        handle_input(task).await
+    }
+
+    /// The longest time between two clicks making a double click, in milliseconds.
+    #[napi]
+    pub async fn get_double_click_time() -> Result<u32> {
+        Ok(unsafe { GetDoubleClickTime() })
     }
 
     #[napi]
@@ -91,9 +237,52 @@ impl Mouse {
 
         handle_result(task).await
     }
+
+    /// Whether `button` is held down right now, by the user or by `press`.
+    #[napi]
+    pub async fn is_pressed(button: MouseButton) -> Result<bool> {
+        let task = tokio::spawn(async move {
+            // GetAsyncKeyState reports the physical buttons, swap them back for left-handed setups.
+            let swapped = unsafe { GetSystemMetrics(SM_SWAPBUTTON) } != 0;
+            let key = match button {
+                MouseButton::Left if swapped => VK_RBUTTON,
+                MouseButton::Left => VK_LBUTTON,
+                MouseButton::Right if swapped => VK_LBUTTON,
+                MouseButton::Right => VK_RBUTTON,
+                MouseButton::Middle => VK_MBUTTON,
+                MouseButton::X1 => VK_XBUTTON1,
+                MouseButton::X2 => VK_XBUTTON2,
+            };
+
+            Ok(unsafe { GetAsyncKeyState(key.0 as i32) } < 0)
+        });
+
+        handle_result(task).await
+    }
+}
+
+/// The down and up flags of `button`, and the `dwData` identifying it for the X buttons.
+fn button_flags(button: MouseButton) -> (MOUSE_EVENT_FLAGS, MOUSE_EVENT_FLAGS, i32) {
+    match button {
+        MouseButton::Left => (MOUSEEVENTF_LEFTDOWN, MOUSEEVENTF_LEFTUP, 0),
+        MouseButton::Right => (MOUSEEVENTF_RIGHTDOWN, MOUSEEVENTF_RIGHTUP, 0),
+        MouseButton::Middle => (MOUSEEVENTF_MIDDLEDOWN, MOUSEEVENTF_MIDDLEUP, 0),
+        MouseButton::X1 => (MOUSEEVENTF_XDOWN, MOUSEEVENTF_XUP, XBUTTON1 as i32),
+        MouseButton::X2 => (MOUSEEVENTF_XDOWN, MOUSEEVENTF_XUP, XBUTTON2 as i32),
+    }
+}
+
+fn scroll_inner(delta: i32, axis: ScrollAxis) {
+    match axis {
+        // A positive `MOUSEEVENTF_WHEEL` scrolls up, away from the user.
+        ScrollAxis::Vertical => mouse_event(MOUSEEVENTF_WHEEL, 0, 0, delta.saturating_neg(), 0),
+        ScrollAxis::Horizontal => mouse_event(MOUSEEVENTF_HWHEEL, 0, 0, delta, 0),
+    }
 }
 
 fn get_mouse_position_inner() -> Point {
+    ensure_dpi_awareness();
+
     let mut position = windows::Win32::Foundation::POINT { x: 0, y: 0 };
     unsafe {
         let _ = GetCursorPos(&mut position);
@@ -102,14 +291,43 @@ fn get_mouse_position_inner() -> Point {
     Point::new(position.x, position.y)
 }
 
+async fn move_smooth_inner(x: i32, y: i32, options: MoveOptions) {
+    let interval = options.interval();
+    let path = generate_mouse_path(get_mouse_position_inner(), Point::new(x, y), Some(options));
+
+    let start = tokio::time::Instant::now();
+    for (i, point) in path.iter().enumerate() {
+        mouse_move_inner(point.x, point.y);
+        tokio::time::sleep_until(start + interval * (i as u32 + 1)).await;
+    }
+}
+
+async fn multi_click_inner(button: MouseButton, x: i32, y: i32, count: u32, interval: Option<u32>) {
+    let (down, up, data) = button_flags(button);
+    let double_click_time = unsafe { GetDoubleClickTime() };
+    let interval = interval
+        .unwrap_or(double_click_time / 5)
+        .min(double_click_time.saturating_sub(1));
+
+    mouse_move_inner(x, y);
+    for i in 0..count {
+        if i > 0 {
+            tokio::time::sleep(std::time::Duration::from_millis(interval as u64)).await;
+        }
+
+        mouse_event(down, 0, 0, data, 0);
+        mouse_event(up, 0, 0, data, 0);
+    }
+}
+
 fn mouse_event(dw_flags: MOUSE_EVENT_FLAGS, dx: i32, dy: i32, dw_data: i32, dw_extra_info: usize) {
     unsafe {
-        let x = dx * 65536 / GetSystemMetrics(SM_CXSCREEN);
-        let y = dy * 65536 / GetSystemMetrics(SM_CYSCREEN);
-        windows::Win32::UI::Input::KeyboardAndMouse::mouse_event(dw_flags, x, y, dw_data, dw_extra_info);
+        windows::Win32::UI::Input::KeyboardAndMouse::mouse_event(dw_flags, dx, dy, dw_data, dw_extra_info);
     }
 }
 
+/// Moves to a physical pixel of the virtual desktop, on whichever monitor it is.
 fn mouse_move_inner(x: i32, y: i32) {
-    mouse_event(MOUSEEVENTF_MOVE | MOUSEEVENTF_ABSOLUTE, x, y, 0, 0);
+    let (dx, dy) = normalize_absolute(&Point::new(x, y), &get_virtual_desktop_bounds());
+    mouse_event(MOUSEEVENTF_MOVE | MOUSEEVENTF_ABSOLUTE | MOUSEEVENTF_VIRTUALDESK, dx, dy, 0, 0);
 }
\ No newline at end of file
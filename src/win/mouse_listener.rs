@@ -0,0 +1,199 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+use lazy_static::lazy_static;
+use napi::bindgen_prelude::*;
+use napi::threadsafe_function::{ErrorStrategy, ThreadsafeFunction, ThreadsafeFunctionCallMode};
+use napi_derive::napi;
+use windows::Win32::Foundation::{HINSTANCE, LPARAM, LRESULT, WPARAM};
+use windows::Win32::System::LibraryLoader::GetModuleHandleW;
+use windows::Win32::System::Threading::GetCurrentThreadId;
+use windows::Win32::UI::WindowsAndMessaging::{CallNextHookEx, DispatchMessageW, GetMessageW, HC_ACTION, HHOOK, MSLLHOOKSTRUCT, PostThreadMessageW, SetWindowsHookExW, TranslateMessage, UnhookWindowsHookEx, WH_MOUSE_LL, WM_LBUTTONDOWN, WM_LBUTTONUP, WM_MBUTTONDOWN, WM_MBUTTONUP, WM_MOUSEHWHEEL, WM_MOUSEMOVE, WM_MOUSEWHEEL, WM_QUIT, WM_RBUTTONDOWN, WM_RBUTTONUP, WM_XBUTTONDOWN, WM_XBUTTONUP, XBUTTON1};
+use super::mouse::{Mouse, MouseButton, ScrollAxis};
+use super::screen::ensure_dpi_awareness;
+
+#[napi]
+#[derive(Debug, PartialEq)]
+pub enum MouseEventKind {
+    Move,
+    Press,
+    Release,
+    Wheel,
+}
+
+#[napi(object)]
+#[derive(Debug, Clone)]
+pub struct MouseEvent {
+    pub kind: MouseEventKind,
+    /// Pointer position on the virtual desktop when the event happened.
+    pub x: i32,
+    pub y: i32,
+    /// The button pressed or released, for `Press` and `Release`.
+    pub button: Option<MouseButton>,
+    /// For `Wheel`, 120 per notch, positive when scrolling down (or right).
+    pub delta: Option<i32>,
+    pub axis: Option<ScrollAxis>,
+    /// Milliseconds since the Unix epoch.
+    pub timestamp: f64,
+}
+
+lazy_static! {
+    static ref MOUSE_LISTENERS: Mutex<HashMap<u32, ThreadsafeFunction<MouseEvent, ErrorStrategy::Fatal>>> = Mutex::new(HashMap::new());
+    static ref MOUSE_HOOK_THREAD: Mutex<Option<u32>> = Mutex::new(None);
+}
+
+static NEXT_LISTENER_ID: AtomicU32 = AtomicU32::new(1);
+
+#[napi]
+impl Mouse {
+    /// Calls `callback` with every move, button press or release and wheel turn of the mouse,
+    /// including synthetic ones. Returns an id for `unlisten`.
+    #[napi(ts_args_type = "callback: (event: MouseEvent) => void")]
+    pub fn listen(callback: JsFunction) -> Result<u32> {
+        let tsfn: ThreadsafeFunction<MouseEvent, ErrorStrategy::Fatal> = callback.create_threadsafe_function(0, |ctx| {
+            Ok(vec![ctx.value])
+        })?;
+
+        let id = NEXT_LISTENER_ID.fetch_add(1, Ordering::Relaxed);
+        MOUSE_LISTENERS.lock().unwrap().insert(id, tsfn);
+
+        let mut thread = MOUSE_HOOK_THREAD.lock().unwrap();
+        if thread.is_none() {
+            match start_hook() {
+                Ok(started) => *thread = Some(started),
+                Err(e) => {
+                    MOUSE_LISTENERS.lock().unwrap().remove(&id);
+                    return Err(Error::new(Status::GenericFailure, e));
+                }
+            }
+        }
+
+        Ok(id)
+    }
+
+    #[napi]
+    pub fn unlisten(id: u32) {
+        let is_empty = {
+            let mut listeners = MOUSE_LISTENERS.lock().unwrap();
+            listeners.remove(&id);
+            listeners.is_empty()
+        };
+
+        if is_empty {
+            if let Some(thread) = MOUSE_HOOK_THREAD.lock().unwrap().take() {
+                unsafe {
+                    let _ = PostThreadMessageW(thread, WM_QUIT, WPARAM(0), LPARAM(0));
+                }
+            }
+        }
+    }
+}
+
+/// Installs a low-level mouse hook on a thread of its own, which has to keep pumping messages
+/// for the hook to be called. Returns the id of that thread, to post `WM_QUIT` to.
+fn start_hook() -> std::result::Result<u32, String> {
+    let (sender, receiver) = std::sync::mpsc::channel();
+
+    std::thread::spawn(move || unsafe {
+        // The hook reports physical pixels only when the thread is DPI aware.
+        ensure_dpi_awareness();
+
+        let hinstance = match GetModuleHandleW(None) {
+            Ok(module) => HINSTANCE(module.0 as _),
+            Err(e) => {
+                let _ = sender.send(Err(format!("Failed to get the module handle: {:?}", e)));
+                return;
+            }
+        };
+
+        let hook = match SetWindowsHookExW(WH_MOUSE_LL, Some(mouse_hook_proc), hinstance, 0) {
+            Ok(hook) => hook,
+            Err(e) => {
+                let _ = sender.send(Err(format!("Failed to install the mouse hook: {:?}", e)));
+                return;
+            }
+        };
+
+        let _ = sender.send(Ok(GetCurrentThreadId()));
+
+        let mut msg = std::mem::zeroed();
+        while GetMessageW(&mut msg, None, 0, 0).0 > 0 {
+            TranslateMessage(&msg);
+            DispatchMessageW(&msg);
+        }
+
+        let _ = UnhookWindowsHookEx(hook);
+    });
+
+    match receiver.recv() {
+        Ok(result) => result,
+        Err(e) => Err(format!("Failed to start the mouse hook thread: {:?}", e)),
+    }
+}
+
+unsafe extern "system" fn mouse_hook_proc(code: i32, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
+    if code == HC_ACTION as i32 {
+        let info = &*(lparam.0 as *const MSLLHOOKSTRUCT);
+        if let Some(event) = parse_hook_event(wparam.0 as u32, info) {
+            let listeners = MOUSE_LISTENERS.lock().unwrap();
+            for listener in listeners.values() {
+                listener.call(event.clone(), ThreadsafeFunctionCallMode::NonBlocking);
+            }
+        }
+    }
+
+    CallNextHookEx(HHOOK::default(), code, wparam, lparam)
+}
+
+/// Turns a low-level hook message into a mouse event. The high word of `mouseData` holds the
+/// wheel delta, positive when scrolling up (or right), or which X button changed.
+fn parse_hook_event(message: u32, info: &MSLLHOOKSTRUCT) -> Option<MouseEvent> {
+    let high_word = (info.mouseData >> 16) as u16;
+
+    let mut event = MouseEvent {
+        kind: MouseEventKind::Move,
+        x: info.pt.x,
+        y: info.pt.y,
+        button: None,
+        delta: None,
+        axis: None,
+        timestamp: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|it| it.as_secs_f64() * 1000.0)
+            .unwrap_or(0.0),
+    };
+
+    let x_button = if high_word == XBUTTON1 { MouseButton::X1 } else { MouseButton::X2 };
+
+    match message {
+        WM_MOUSEMOVE => {}
+        WM_LBUTTONDOWN | WM_RBUTTONDOWN | WM_MBUTTONDOWN | WM_XBUTTONDOWN => {
+            event.kind = MouseEventKind::Press;
+        }
+        WM_LBUTTONUP | WM_RBUTTONUP | WM_MBUTTONUP | WM_XBUTTONUP => {
+            event.kind = MouseEventKind::Release;
+        }
+        WM_MOUSEWHEEL => {
+            event.kind = MouseEventKind::Wheel;
+            event.delta = Some(-(high_word as i16 as i32));
+            event.axis = Some(ScrollAxis::Vertical);
+        }
+        WM_MOUSEHWHEEL => {
+            event.kind = MouseEventKind::Wheel;
+            event.delta = Some(high_word as i16 as i32);
+            event.axis = Some(ScrollAxis::Horizontal);
+        }
+        _ => return None,
+    }
+
+    event.button = match message {
+        WM_LBUTTONDOWN | WM_LBUTTONUP => Some(MouseButton::Left),
+        WM_RBUTTONDOWN | WM_RBUTTONUP => Some(MouseButton::Right),
+        WM_MBUTTONDOWN | WM_MBUTTONUP => Some(MouseButton::Middle),
+        WM_XBUTTONDOWN | WM_XBUTTONUP => Some(x_button),
+        _ => None,
+    };
+
+    Some(event)
+}
@@ -1,11 +1,83 @@
+use std::sync::Once;
 use napi::bindgen_prelude::*;
 use napi_derive::napi;
-use windows::Win32::Graphics::Gdi::{BitBlt, CreateCompatibleBitmap, CreateCompatibleDC, DeleteDC, DeleteObject, GetDC, GetDIBits, ReleaseDC, SelectObject, BITMAPINFO, BITMAPINFOHEADER, BI_RGB, DIB_RGB_COLORS, RGBQUAD, SRCCOPY};
-use windows::Win32::UI::WindowsAndMessaging::{GetDesktopWindow, GetSystemMetrics, SM_CXSCREEN, SM_CYSCREEN};
+use windows::Win32::Foundation::{BOOL, LPARAM, RECT, TRUE};
+use windows::Win32::Graphics::Gdi::{BitBlt, CreateCompatibleBitmap, CreateCompatibleDC, DeleteDC, DeleteObject, EnumDisplayMonitors, GetDC, GetDIBits, GetMonitorInfoW, ReleaseDC, SelectObject, BITMAPINFO, BITMAPINFOHEADER, BI_RGB, DIB_RGB_COLORS, HDC, HMONITOR, MONITORINFO, RGBQUAD, SRCCOPY};
+use windows::Win32::UI::HiDpi::{GetDpiForMonitor, SetProcessDpiAwarenessContext, DPI_AWARENESS_CONTEXT_PER_MONITOR_AWARE_V2, MDT_EFFECTIVE_DPI};
+use windows::Win32::UI::WindowsAndMessaging::{GetDesktopWindow, GetSystemMetrics, MONITORINFOF_PRIMARY, SM_CXSCREEN, SM_CXVIRTUALSCREEN, SM_CYSCREEN, SM_CYVIRTUALSCREEN, SM_XVIRTUALSCREEN, SM_YVIRTUALSCREEN};
+use crate::desktop::{Monitor, VirtualDesktop};
 use crate::screen::{ImageData};
-use crate::geometry::Point;
+use crate::geometry::{Point, Rect};
 use crate::utils::handle_result;
 
+static DPI_AWARENESS: Once = Once::new();
+
+/// Opts the process into per-monitor DPI awareness, so that the cursor, screenshots and monitor
+/// bounds all use physical pixels instead of coordinates scaled for this application. Does
+/// nothing when the host (e.g. Electron) has already chosen an awareness.
+pub fn ensure_dpi_awareness() {
+    DPI_AWARENESS.call_once(|| unsafe {
+        let _ = SetProcessDpiAwarenessContext(DPI_AWARENESS_CONTEXT_PER_MONITOR_AWARE_V2);
+    });
+}
+
+pub fn get_virtual_desktop_bounds() -> Rect {
+    ensure_dpi_awareness();
+
+    unsafe {
+        let left = GetSystemMetrics(SM_XVIRTUALSCREEN);
+        let top = GetSystemMetrics(SM_YVIRTUALSCREEN);
+        let width = GetSystemMetrics(SM_CXVIRTUALSCREEN);
+        let height = GetSystemMetrics(SM_CYVIRTUALSCREEN);
+
+        Rect::new(left, top, left + width, top + height)
+    }
+}
+
+unsafe extern "system" fn enum_monitor_proc(monitor: HMONITOR, _: HDC, _: *mut RECT, data: LPARAM) -> BOOL {
+    let monitors = &mut *(data.0 as *mut Vec<Monitor>);
+
+    let mut info = MONITORINFO {
+        cbSize: std::mem::size_of::<MONITORINFO>() as u32,
+        ..Default::default()
+    };
+    if !GetMonitorInfoW(monitor, &mut info).as_bool() {
+        return TRUE;
+    }
+
+    let mut dpi_x = 96;
+    let mut dpi_y = 96;
+    let _ = GetDpiForMonitor(monitor, MDT_EFFECTIVE_DPI, &mut dpi_x, &mut dpi_y);
+
+    let rect = info.rcMonitor;
+    monitors.push(Monitor::new(
+        Rect::new(rect.left, rect.top, rect.right, rect.bottom),
+        dpi_x as f64 / 96.0,
+        info.dwFlags & MONITORINFOF_PRIMARY != 0,
+    ));
+
+    TRUE
+}
+
+#[napi]
+pub async fn get_virtual_desktop() -> Result<VirtualDesktop> {
+    let task = tokio::spawn(async move {
+        let bounds = get_virtual_desktop_bounds();
+        let mut monitors: Vec<Monitor> = Vec::new();
+
+        unsafe {
+            let res = EnumDisplayMonitors(HDC(0), None, Some(enum_monitor_proc), LPARAM(&mut monitors as *mut _ as isize));
+            if !res.as_bool() {
+                return Err("EnumDisplayMonitors failed".to_string());
+            }
+        }
+
+        Ok(VirtualDesktop { bounds, monitors })
+    });
+
+    handle_result(task).await
+}
+
 fn create_bitmap_info(width: i32, height: i32) -> BITMAPINFO {
     unsafe {
         let mut bmi = std::mem::zeroed::<BITMAPINFOHEADER>();
@@ -33,6 +105,8 @@ fn create_bitmap_info(width: i32, height: i32) -> BITMAPINFO {
 #[napi]
 pub async fn get_screen_size() -> Result<Point> {
     let task = tokio::spawn(async move {
+        ensure_dpi_awareness();
+
         unsafe {
             let width = GetSystemMetrics(SM_CXSCREEN);
             let height = GetSystemMetrics(SM_CYSCREEN);
@@ -43,9 +117,13 @@ pub async fn get_screen_size() -> Result<Point> {
     handle_result(task).await
 }
 
+/// Captures the given area of the virtual desktop, which may span several monitors. Pixel
+/// (`px`, `py`) of the result is at (`x + px`, `y + py`) for `Mouse`.
 #[napi]
 pub async fn take_screenshot(x: i32, y: i32, width: i32, height: i32) -> Result<ImageData> {
     let task = tokio::spawn(async move {
+        ensure_dpi_awareness();
+
         unsafe {
             let hwnd = GetDesktopWindow();
             let h_window_dc = GetDC(hwnd);
@@ -89,7 +167,7 @@ pub async fn take_screenshot(x: i32, y: i32, width: i32, height: i32) -> Result<
             }
 
             Ok(ImageData {
-                data: buf,
+                data: buf.into(),
                 width: width as u32,
                 height: height as u32,
                 pixel_width: 4,